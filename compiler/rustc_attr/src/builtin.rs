@@ -1096,7 +1096,7 @@ pub fn parse_repr_attr(sess: &Session, attr: &Attribute) -> Vec<ReprAttr> {
                 // the `check_mod_attrs` pass, but this pass doesn't always run
                 // (e.g. if we only pretty-print the source), so we have to gate
                 // the `delay_span_bug` call as follows:
-                if sess.opts.pretty.map_or(true, |pp| pp.needs_analysis()) {
+                if sess.opts.pretty.as_ref().map_or(true, |pp| pp.needs_analysis()) {
                     diagnostic.delay_span_bug(item.span(), "unrecognized representation hint");
                 }
             }
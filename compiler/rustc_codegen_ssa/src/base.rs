@@ -990,6 +990,9 @@ fn determine_cgu_reuse<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>) -> CguR
     if tcx.dep_graph.previous_work_product(work_product_id).is_none() {
         // We don't have anything cached for this CGU. This can happen
         // if the CGU did not exist in the previous session.
+        if tcx.sess.opts.unstable_opts.incremental_explain {
+            eprintln!("[incremental] CGU `{}` has no cached object file", cgu.name());
+        }
         return CguReuse::No;
     }
 
@@ -1020,6 +1023,9 @@ fn determine_cgu_reuse<'tcx>(tcx: TyCtxt<'tcx>, cgu: &CodegenUnit<'tcx>) -> CguR
             _ => CguReuse::PreLto,
         }
     } else {
+        if tcx.sess.opts.unstable_opts.incremental_explain {
+            tcx.dep_graph.print_invalidation(&format!("CGU `{}`", cgu.name()), &dep_node);
+        }
         CguReuse::No
     }
 }
@@ -6,6 +6,7 @@
 use rustc_middle::mir::mono::{Linkage, Visibility};
 use rustc_middle::ty;
 use rustc_middle::ty::layout::{HasTyCtxt, LayoutOf};
+use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::Instance;
 
 pub trait MonoItemExt<'a, 'tcx> {
@@ -28,6 +29,12 @@ fn define<Bx: BuilderMethods<'a, 'tcx>>(&self, cx: &'a Bx::CodegenCx) {
             cx.codegen_unit().name()
         );
 
+        // All instances of a generic function are attributed to the function.
+        let tcx = cx.tcx();
+        let _timer = tcx
+            .prof
+            .item_activity("codegen", || with_no_trimmed_paths!(tcx.def_path_str(self.def_id())));
+
         match *self {
             MonoItem::Static(def_id) => {
                 cx.codegen_static(def_id, cx.tcx().is_mutable_static(def_id));
@@ -85,6 +85,7 @@
 use crate::fx::FxHashMap;
 
 use std::borrow::Borrow;
+use std::cell::RefCell;
 use std::collections::hash_map::Entry;
 use std::error::Error;
 use std::fmt::Display;
@@ -97,7 +98,7 @@
 
 pub use measureme::EventId;
 use measureme::{EventIdBuilder, Profiler, SerializableString, StringId};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use smallvec::SmallVec;
 
 bitflags::bitflags! {
@@ -170,19 +171,30 @@ pub struct SelfProfilerRef {
 
     // Print verbose generic activities to stderr.
     print_verbose_generic_activities: Option<TimePassesFormat>,
+
+    // This field is `None` unless the time spent on each item is recorded
+    // for `-Z time-items`.
+    item_times: Option<Arc<ItemTimes>>,
 }
 
 impl SelfProfilerRef {
     pub fn new(
         profiler: Option<Arc<SelfProfiler>>,
         print_verbose_generic_activities: Option<TimePassesFormat>,
+        record_item_times: bool,
     ) -> SelfProfilerRef {
         // If there is no SelfProfiler then the filter mask is set to NONE,
         // ensuring that nothing ever tries to actually access it.
         let event_filter_mask =
             profiler.as_ref().map_or(EventFilter::empty(), |p| p.event_filter_mask);
+        let item_times = record_item_times.then(Default::default);
 
-        SelfProfilerRef { profiler, event_filter_mask, print_verbose_generic_activities }
+        SelfProfilerRef {
+            profiler,
+            event_filter_mask,
+            print_verbose_generic_activities,
+            item_times,
+        }
     }
 
     /// This shim makes sure that calls only get executed if the filter mask
@@ -396,12 +408,35 @@ pub fn generic_activity_with_args(
     }
 
     /// Start profiling a query provider. Profiling continues until the
-    /// TimingGuard returned from this call is dropped.
+    /// QueryTimingGuard returned from this call is dropped.
     #[inline(always)]
-    pub fn query_provider(&self) -> TimingGuard<'_> {
-        self.exec(EventFilter::QUERY_PROVIDERS, |profiler| {
+    pub fn query_provider(&self) -> QueryTimingGuard<'_> {
+        let guard = self.exec(EventFilter::QUERY_PROVIDERS, |profiler| {
             TimingGuard::start(profiler, profiler.query_event_kind, EventId::INVALID)
-        })
+        });
+        let item_timer =
+            self.item_times.as_deref().map(|times| ItemTimingGuard::start(times, None));
+        QueryTimingGuard { guard, item_timer }
+    }
+
+    /// Start profiling an activity that isn't a query but belongs to an item, like
+    /// the code generation of a function, for `-Z time-items`. Profiling continues
+    /// until the ItemTimingGuard returned from this call is dropped.
+    ///
+    /// `item` is only called if the time spent on items is recorded.
+    #[inline(always)]
+    pub fn item_activity(
+        &self,
+        activity: &'static str,
+        item: impl FnOnce() -> String,
+    ) -> Option<ItemTimingGuard<'_>> {
+        let times = self.item_times.as_deref()?;
+        Some(ItemTimingGuard::start(times, Some(ItemTimingTarget::Activity(activity, item()))))
+    }
+
+    /// The time spent on each item, if it is recorded.
+    pub fn item_times(&self) -> Option<&ItemTimes> {
+        self.item_times.as_deref()
     }
 
     /// Record a query in-memory cache hit.
@@ -717,6 +752,95 @@ pub fn run<R>(self, f: impl FnOnce() -> R) -> R {
     }
 }
 
+#[must_use]
+pub struct QueryTimingGuard<'a> {
+    guard: TimingGuard<'a>,
+    item_timer: Option<ItemTimingGuard<'a>>,
+}
+
+impl<'a> QueryTimingGuard<'a> {
+    #[inline]
+    pub fn finish_with_query_invocation_id(self, query_invocation_id: QueryInvocationId) {
+        if let Some(mut item_timer) = self.item_timer {
+            item_timer.target = Some(ItemTimingTarget::Query(query_invocation_id.0));
+        }
+        self.guard.finish_with_query_invocation_id(query_invocation_id);
+    }
+}
+
+/// The self time of the query invocations and of the other activities that
+/// belong to an item, recorded for `-Z time-items`. The query invocations are
+/// only attributed to items once the compilation is done, from the query keys.
+#[derive(Default)]
+pub struct ItemTimes {
+    queries: Mutex<FxHashMap<u32, Duration>>,
+    activities: Mutex<FxHashMap<(&'static str, String), Duration>>,
+}
+
+impl ItemTimes {
+    pub fn query_time(&self, query_invocation_id: QueryInvocationId) -> Option<Duration> {
+        self.queries.lock().get(&query_invocation_id.0).copied()
+    }
+
+    /// The time spent on each activity, by activity and item.
+    pub fn activity_times(&self) -> Vec<(&'static str, String, Duration)> {
+        let activities = self.activities.lock();
+        activities.iter().map(|((activity, item), &time)| (*activity, item.clone(), time)).collect()
+    }
+}
+
+enum ItemTimingTarget {
+    Query(u32),
+    Activity(&'static str, String),
+}
+
+thread_local! {
+    /// For each query or activity being timed on this thread, from the outermost
+    /// to the innermost, the time spent in the queries and activities nested in it.
+    /// It is subtracted from their time, so that only their self time is recorded.
+    static NESTED_ITEM_TIME: RefCell<Vec<Duration>> = RefCell::new(Vec::new());
+}
+
+#[must_use]
+pub struct ItemTimingGuard<'a> {
+    times: &'a ItemTimes,
+    start: Instant,
+    // This is `None` for a query until it finishes. Queries that don't finish,
+    // e.g. because of a cycle error, are not recorded.
+    target: Option<ItemTimingTarget>,
+}
+
+impl<'a> ItemTimingGuard<'a> {
+    fn start(times: &'a ItemTimes, target: Option<ItemTimingTarget>) -> ItemTimingGuard<'a> {
+        NESTED_ITEM_TIME.with(|nested| nested.borrow_mut().push(Duration::ZERO));
+        ItemTimingGuard { times, start: Instant::now(), target }
+    }
+}
+
+impl Drop for ItemTimingGuard<'_> {
+    fn drop(&mut self) {
+        let time = self.start.elapsed();
+        let nested_time = NESTED_ITEM_TIME.with(|nested| {
+            let mut nested = nested.borrow_mut();
+            let nested_time = nested.pop().unwrap_or_default();
+            if let Some(parent) = nested.last_mut() {
+                *parent += time;
+            }
+            nested_time
+        });
+        let self_time = time.saturating_sub(nested_time);
+        match self.target.take() {
+            Some(ItemTimingTarget::Query(id)) => {
+                *self.times.queries.lock().entry(id).or_default() += self_time;
+            }
+            Some(ItemTimingTarget::Activity(activity, item)) => {
+                *self.times.activities.lock().entry((activity, item)).or_default() += self_time;
+            }
+            None => {}
+        }
+    }
+}
+
 struct VerboseInfo {
     start_time: Instant,
     start_rss: Option<usize>,
@@ -1,4 +1,5 @@
-use super::JsonTimePassesEntry;
+use super::{ItemTimes, ItemTimingGuard, ItemTimingTarget, JsonTimePassesEntry};
+use std::time::Duration;
 
 #[test]
 fn with_rss() {
@@ -17,3 +18,28 @@ fn no_rss() {
         r#"{"pass":"typeck","time":56.1,"rss_start":null,"rss_end":null}"#
     )
 }
+
+#[test]
+fn item_times_exclude_nested_time() {
+    let times = ItemTimes::default();
+    let activity = |item: &str| Some(ItemTimingTarget::Activity("codegen", item.to_string()));
+
+    let outer = ItemTimingGuard::start(&times, activity("outer"));
+    let inner = ItemTimingGuard::start(&times, activity("inner"));
+    // A query that doesn't finish isn't recorded, but its time is still nested.
+    let unfinished_query = ItemTimingGuard::start(&times, None);
+    std::thread::sleep(Duration::from_millis(20));
+    drop(unfinished_query);
+    drop(inner);
+    drop(outer);
+
+    let mut activities = times.activity_times();
+    activities.sort();
+    let [(_, ref inner, inner_time), (_, ref outer, outer_time)] = activities[..] else {
+        panic!("expected two activities, found {activities:?}")
+    };
+    assert_eq!((inner.as_str(), outer.as_str()), ("inner", "outer"));
+    // The time spent in the query is neither the self time of `inner` nor of `outer`.
+    assert!(inner_time < Duration::from_millis(20), "inner self time: {inner_time:?}");
+    assert!(outer_time < Duration::from_millis(20), "outer self time: {outer_time:?}");
+}
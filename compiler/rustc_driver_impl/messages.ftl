@@ -1,3 +1,5 @@
+driver_impl_couldnt_dump_smir = unexpected error occurred while dumping stable MIR: {$error}
+
 driver_impl_ice = the compiler unexpectedly panicked. this is a bug.
 driver_impl_ice_bug_report = we would appreciate a bug report: {$bug_report_url}
 driver_impl_ice_exclude_cargo_defaults = some of the compiler flags provided by cargo are hidden
@@ -19,3 +21,8 @@ driver_impl_rlink_rustc_version_mismatch = .rlink file was produced by rustc ver
 driver_impl_rlink_unable_to_read = failed to read rlink file: `{$err}`
 
 driver_impl_rlink_wrong_file_type = The input does not look like a .rlink file
+
+driver_impl_unpretty_mir_no_item = no item with MIR matches `{$item}`
+
+driver_impl_unpretty_mir_pass_didnt_run = the pass `{$pass}` didn't run on the MIR of the requested items
+    .note = the pass doesn't exist, or it isn't enabled with the current options, like `-Zmir-opt-level`
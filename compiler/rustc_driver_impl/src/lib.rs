@@ -33,10 +33,13 @@
 use rustc_interface::util::{self, collect_crate_types, get_codegen_backend};
 use rustc_interface::{interface, Queries};
 use rustc_lint::LintStore;
+use rustc_metadata::creader::CStore;
 use rustc_metadata::locator;
+use rustc_middle::ty::TyCtxt;
 use rustc_session::config::{nightly_options, CG_OPTIONS, Z_OPTIONS};
-use rustc_session::config::{ErrorOutputType, Input, OutFileName, OutputType, TrimmedDefPaths};
-use rustc_session::cstore::MetadataLoader;
+use rustc_session::config::{ErrorOutputType, Input, OutFileName, OutputType, PrintKind};
+use rustc_session::config::{SwitchWithOptPath, TrimmedDefPaths};
+use rustc_session::cstore::{CrateDepKind, MetadataLoader};
 use rustc_session::getopts::{self, Matches};
 use rustc_session::lint::{Lint, LintId};
 use rustc_session::{config, EarlyErrorHandler, Session};
@@ -87,8 +90,8 @@
 mod session_diagnostics;
 
 use crate::session_diagnostics::{
-    RLinkEmptyVersionNumber, RLinkEncodingVersionMismatch, RLinkRustcVersionMismatch,
-    RLinkWrongFileType, RlinkNotAFile, RlinkUnableToRead,
+    CouldntDumpSmir, RLinkEmptyVersionNumber, RLinkEncodingVersionMismatch,
+    RLinkRustcVersionMismatch, RLinkWrongFileType, RlinkNotAFile, RlinkUnableToRead,
 };
 
 fluent_messages! { "../messages.ftl" }
@@ -388,12 +391,12 @@ fn run_compiler(
                 if ppm.needs_ast_map() {
                     queries.global_ctxt()?.enter(|tcx| {
                         tcx.ensure().early_lint_checks(());
-                        pretty::print_after_hir_lowering(tcx, *ppm);
+                        pretty::print_after_hir_lowering(tcx, ppm.clone());
                         Ok(())
                     })?;
                 } else {
                     let krate = queries.parse()?.steal();
-                    pretty::print_after_parsing(sess, &krate, *ppm);
+                    pretty::print_after_parsing(sess, &krate, ppm.clone());
                 }
                 trace!("finished pretty-printing");
                 return early_exit();
@@ -421,6 +424,10 @@ fn run_compiler(
             // Make sure name resolution and macro expansion is run.
             queries.global_ctxt()?.enter(|tcx| tcx.resolver_for_lowering(()));
 
+            if sess.opts.prints.iter().any(|p| p.kind == PrintKind::LoadedCrates) {
+                queries.global_ctxt()?.enter(|tcx| print_loaded_crates(tcx));
+            }
+
             if callbacks.after_expansion(compiler, queries) == Compilation::Stop {
                 return early_exit();
             }
@@ -441,6 +448,14 @@ fn run_compiler(
 
             queries.global_ctxt()?.enter(|tcx| tcx.analysis(()))?;
 
+            if let SwitchWithOptPath::Enabled(ref path) = sess.opts.unstable_opts.dump_smir {
+                queries.global_ctxt()?.enter(|tcx| {
+                    if let Err(err) = rustc_smir::rustc_internal::dump_smir(tcx, path) {
+                        sess.emit_fatal(CouldntDumpSmir { error: err.to_string() });
+                    }
+                });
+            }
+
             if callbacks.after_analysis(&handler, compiler, queries) == Compilation::Stop {
                 return early_exit();
             }
@@ -448,7 +463,7 @@ fn run_compiler(
             let ongoing_codegen = queries.ongoing_codegen()?;
 
             if sess.opts.unstable_opts.print_type_sizes {
-                sess.code_stats.print_type_sizes();
+                sess.code_stats.print_type_sizes(sess.opts.unstable_opts.print_type_sizes_format);
             }
 
             if sess.opts.unstable_opts.print_vtable_sizes {
@@ -550,35 +565,77 @@ fn handle_explain(handler: &EarlyErrorHandler, registry: Registry, code: &str, c
     let upper_cased_code = code.to_ascii_uppercase();
     let normalised =
         if upper_cased_code.starts_with('E') { upper_cased_code } else { format!("E{code:0>4}") };
-    match registry.try_find_description(&normalised) {
-        Ok(description) => {
-            let mut is_in_code_block = false;
-            let mut text = String::new();
-            // Slice off the leading newline and print.
-            for line in description.lines() {
-                let indent_level =
-                    line.find(|c: char| !c.is_whitespace()).unwrap_or_else(|| line.len());
-                let dedented_line = &line[indent_level..];
-                if dedented_line.starts_with("```") {
-                    is_in_code_block = !is_in_code_block;
-                    text.push_str(&line[..(indent_level + 3)]);
-                } else if is_in_code_block && dedented_line.starts_with("# ") {
-                    continue;
-                } else {
-                    text.push_str(line);
-                }
-                text.push('\n');
-            }
-            if io::stdout().is_terminal() {
-                show_md_content_with_pager(&text, color);
-            } else {
-                safe_print!("{text}");
+    let text = match registry.try_find_description(&normalised) {
+        Ok(description) => format_explanation(description),
+        Err(InvalidErrorCode) => match lint_explanation(code) {
+            Some(explanation) => format_explanation(&explanation),
+            None => handler.early_error(format!("{code} is not a valid error code or lint name")),
+        },
+    };
+    if io::stdout().is_terminal() {
+        show_md_content_with_pager(&text, color);
+    } else {
+        safe_print!("{text}");
+    }
+}
+
+/// Returns the documentation of the built-in lint or lint group called `name`, which can be
+/// written with dashes, like on the command line.
+fn lint_explanation(name: &str) -> Option<String> {
+    let name = name.to_ascii_lowercase().replace('-', "_");
+    let lint_store = rustc_lint::new_lint_store(false);
+
+    if let Some(lint) = lint_store.get_lints().iter().find(|lint| lint.name_lower() == name) {
+        // Doc comments keep the space that follows `///`.
+        let mut lines =
+            lint.doc.lines().map(|line| line.strip_prefix(' ').unwrap_or(line)).peekable();
+        let mut explanation = String::new();
+        while let Some(line) = lines.next() {
+            // This is replaced with the output of the example when the lint docs are generated
+            // for the book, it can't be shown here.
+            if line == "{{produces}}" {
+                lines.next_if_eq(&"");
+                continue;
             }
+            explanation.push_str(line);
+            explanation.push('\n');
         }
-        Err(InvalidErrorCode) => {
-            handler.early_error(format!("{code} is not a valid error code"));
+        if explanation.is_empty() {
+            explanation = format!("The `{name}` lint detects {}.\n", lint.desc);
         }
+        let level = lint.default_level.as_str();
+        explanation.push_str(&format!("\nThe `{name}` lint is {level}-by-default.\n"));
+        return Some(explanation);
     }
+
+    let (_, mut lints, _) = lint_store.get_lint_groups().find(|(group, ..)| *group == name)?;
+    lints.sort_by_key(|lint| lint.lint.name);
+    let mut explanation = format!("`{name}` is a lint group containing the following lints:\n\n");
+    for lint in lints {
+        explanation.push_str(&format!("* `{}`: {}\n", lint.to_string(), lint.lint.desc));
+    }
+    Some(explanation)
+}
+
+/// Prepares an explanation written in Markdown for printing, by removing the information for
+/// the doc tests from its code blocks.
+fn format_explanation(description: &str) -> String {
+    let mut is_in_code_block = false;
+    let mut text = String::new();
+    for line in description.lines() {
+        let indent_level = line.find(|c: char| !c.is_whitespace()).unwrap_or_else(|| line.len());
+        let dedented_line = &line[indent_level..];
+        if dedented_line.starts_with("```") {
+            is_in_code_block = !is_in_code_block;
+            text.push_str(&line[..(indent_level + 3)]);
+        } else if is_in_code_block && dedented_line.starts_with("# ") {
+            continue;
+        } else {
+            text.push_str(line);
+        }
+        text.push('\n');
+    }
+    text
 }
 
 /// If color is always or auto, print formatted & colorized markdown. If color is never or
@@ -732,7 +789,13 @@ fn print_crate_info(
 
     // NativeStaticLibs and LinkArgs are special - printed during linking
     // (empty iterator returns true)
-    if sess.opts.prints.iter().all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs) {
+    // LoadedCrates is printed once name resolution has loaded all crates
+    if sess
+        .opts
+        .prints
+        .iter()
+        .all(|p| p.kind == NativeStaticLibs || p.kind == LinkArgs || p.kind == LoadedCrates)
+    {
         return Compilation::Continue;
     }
 
@@ -847,6 +910,7 @@ fn print_crate_info(
             // Any output here interferes with Cargo's parsing of other printed output
             NativeStaticLibs => {}
             LinkArgs => {}
+            LoadedCrates => {}
             SplitDebuginfo => {
                 use rustc_target::spec::SplitDebuginfo::{Off, Packed, Unpacked};
 
@@ -877,6 +941,55 @@ fn print_crate_info(
     Compilation::Stop
 }
 
+/// Prints the crates loaded by the crate loader as JSON, for `--print=loaded-crates`.
+///
+/// This happens right after name resolution, so that it still works if loading one of the
+/// crates failed or if compilation fails later on.
+fn print_loaded_crates(tcx: TyCtxt<'_>) {
+    let sess = tcx.sess;
+    // This freezes the crate store, so it must happen before it is locked below.
+    let crates = tcx.crates(());
+    let cstore = CStore::from_tcx(tcx);
+    let crates = crates
+        .iter()
+        .map(|&cnum| {
+            let source = tcx.used_crate_source(cnum);
+            let path = |path: &Option<(PathBuf, _)>| {
+                path.as_ref().map(|(path, _)| path.display().to_string())
+            };
+            let dep_kind = tcx.dep_kind(cnum);
+            let target_features = cstore.crate_target_features_untracked(cnum);
+            let dependency_of = tcx.extern_crate(cnum.as_def_id()).map(|e| e.dependency_of);
+            serde_json::json!({
+                "name": tcx.crate_name(cnum).as_str(),
+                "cnum": cnum.as_u32(),
+                "svh": tcx.crate_hash(cnum).to_string(),
+                "dylib": path(&source.dylib),
+                "rlib": path(&source.rlib),
+                "rmeta": path(&source.rmeta),
+                "edition": cstore.crate_edition_untracked(cnum).to_string(),
+                "panic_strategy": tcx.required_panic_strategy(cnum).map(|s| s.desc().to_string()),
+                "target_features": target_features.iter().map(|f| f.as_str()).collect::<Vec<_>>(),
+                "dep_kind": match dep_kind {
+                    CrateDepKind::MacrosOnly => "macros-only",
+                    CrateDepKind::Implicit => "implicit",
+                    CrateDepKind::Explicit => "explicit",
+                },
+                // Crates that are only loaded for their macros are not linked.
+                "used": !dep_kind.macros_only(),
+                "dependency_of": dependency_of.map(|cnum| cnum.as_u32()),
+            })
+        })
+        .collect::<Vec<_>>();
+    let crates = serde_json::to_string_pretty(&crates).unwrap();
+
+    for req in &sess.opts.prints {
+        if req.kind == PrintKind::LoadedCrates {
+            req.out.overwrite(&format!("{crates}\n"), sess);
+        }
+    }
+}
+
 /// Prints version information
 ///
 /// NOTE: this is a macro to support drivers built at a different time than the main `rustc_driver` crate.
@@ -8,8 +8,11 @@
 use rustc_middle::hir::map as hir_map;
 use rustc_middle::mir::{write_mir_graphviz, write_mir_pretty};
 use rustc_middle::ty::{self, TyCtxt};
-use rustc_session::config::{OutFileName, PpAstTreeMode, PpHirMode, PpMode, PpSourceMode};
+use rustc_session::config::{
+    OutFileName, PpAstTreeMode, PpHirMode, PpMirFilter, PpMode, PpSourceMode,
+};
 use rustc_session::Session;
+use rustc_span::def_id::DefId;
 use rustc_span::symbol::Ident;
 use rustc_span::FileName;
 
@@ -19,6 +22,7 @@
 pub use self::PpMode::*;
 pub use self::PpSourceMode::*;
 use crate::abort_on_err;
+use crate::session_diagnostics::{UnprettyMirNoItem, UnprettyMirPassDidntRun};
 
 // This slightly awkward construction is to allow for each PpMode to
 // choose whether it needs to do analyses (which can consume the
@@ -460,15 +464,21 @@ pub fn print_after_hir_lowering<'tcx>(tcx: TyCtxt<'tcx>, ppm: PpMode) {
 fn print_with_analysis(tcx: TyCtxt<'_>, ppm: PpMode) -> Result<(), ErrorGuaranteed> {
     tcx.analysis(())?;
     let out = match ppm {
-        Mir => {
+        Mir(ref filter) | MirCFG(ref filter) if filter.after_pass.is_some() => {
+            print_mir_after_pass(tcx, filter, matches!(ppm, MirCFG(_)))?
+        }
+
+        Mir(filter) => {
+            let def_ids = filtered_mir_def_ids(tcx, &filter)?;
             let mut out = Vec::new();
-            write_mir_pretty(tcx, None, &mut out).unwrap();
+            write_mir_pretty(tcx, def_ids.as_deref(), &mut out).unwrap();
             String::from_utf8(out).unwrap()
         }
 
-        MirCFG => {
+        MirCFG(filter) => {
+            let def_ids = filtered_mir_def_ids(tcx, &filter)?;
             let mut out = Vec::new();
-            write_mir_graphviz(tcx, None, &mut out).unwrap();
+            write_mir_graphviz(tcx, def_ids.as_deref(), &mut out).unwrap();
             String::from_utf8(out).unwrap()
         }
 
@@ -499,3 +509,67 @@ fn print_with_analysis(tcx: TyCtxt<'_>, ppm: PpMode) -> Result<(), ErrorGuarante
 
     Ok(())
 }
+
+/// Returns the items matching the item filter of `-Zunpretty=mir=ITEM`, or `None` if there is
+/// no filter.
+fn filtered_mir_def_ids(
+    tcx: TyCtxt<'_>,
+    filter: &PpMirFilter,
+) -> Result<Option<Vec<DefId>>, ErrorGuaranteed> {
+    let Some(item) = &filter.item else { return Ok(None) };
+    let def_ids: Vec<_> = tcx
+        .mir_keys(())
+        .iter()
+        .map(|def_id| def_id.to_def_id())
+        .filter(|&def_id| {
+            let def_path = ty::print::with_forced_impl_filename_line!(tcx.def_path_str(def_id));
+            filter.matches_item(&def_path)
+        })
+        .collect();
+    if def_ids.is_empty() {
+        return Err(tcx.sess.emit_err(UnprettyMirNoItem { item: item.clone() }));
+    }
+    Ok(Some(def_ids))
+}
+
+/// Prints the MIR of `-Zunpretty=mir=ITEM@after:PASS`, which the pass manager renders when the
+/// pass runs on the bodies of the requested items.
+fn print_mir_after_pass(
+    tcx: TyCtxt<'_>,
+    filter: &PpMirFilter,
+    cfg: bool,
+) -> Result<String, ErrorGuaranteed> {
+    let def_ids = match filtered_mir_def_ids(tcx, filter)? {
+        Some(def_ids) => def_ids,
+        None => tcx.mir_keys(()).iter().map(|def_id| def_id.to_def_id()).collect(),
+    };
+
+    // Build the MIR printed by `-Zunpretty=mir`, running the pass on it unless it already ran.
+    for &def_id in &def_ids {
+        if tcx.is_const_fn_raw(def_id) {
+            tcx.ensure_with_value().optimized_mir(def_id);
+            tcx.ensure_with_value().mir_for_ctfe(def_id);
+        } else {
+            tcx.ensure_with_value().instance_mir(ty::InstanceDef::Item(def_id));
+        }
+        tcx.ensure_with_value().promoted_mir(def_id);
+    }
+
+    let rendered = tcx.sess.unpretty_mir.lock();
+    let bodies: Vec<&str> = def_ids
+        .iter()
+        .flat_map(|&def_id| {
+            rendered.iter().filter(move |(id, _)| *id == def_id).map(|(_, body)| body.as_str())
+        })
+        .collect();
+    if bodies.is_empty() {
+        let pass = filter.after_pass.clone().unwrap();
+        return Err(tcx.sess.emit_err(UnprettyMirPassDidntRun { pass }));
+    }
+
+    Ok(if cfg {
+        format!("digraph __crate__ {{\n{}}}\n", bodies.concat())
+    } else {
+        bodies.join("\n")
+    })
+}
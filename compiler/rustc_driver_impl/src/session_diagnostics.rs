@@ -1,5 +1,11 @@
 use rustc_macros::{Diagnostic, Subdiagnostic};
 
+#[derive(Diagnostic)]
+#[diag(driver_impl_couldnt_dump_smir)]
+pub(crate) struct CouldntDumpSmir {
+    pub error: String,
+}
+
 #[derive(Diagnostic)]
 #[diag(driver_impl_rlink_unable_to_read)]
 pub(crate) struct RlinkUnableToRead {
@@ -79,3 +85,16 @@ pub(crate) struct IceFlags {
 #[derive(Diagnostic)]
 #[diag(driver_impl_ice_exclude_cargo_defaults)]
 pub(crate) struct IceExcludeCargoDefaults;
+
+#[derive(Diagnostic)]
+#[diag(driver_impl_unpretty_mir_no_item)]
+pub(crate) struct UnprettyMirNoItem {
+    pub item: String,
+}
+
+#[derive(Diagnostic)]
+#[diag(driver_impl_unpretty_mir_pass_didnt_run)]
+#[note]
+pub(crate) struct UnprettyMirPassDidntRun {
+    pub pass: String,
+}
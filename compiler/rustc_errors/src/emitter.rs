@@ -200,6 +200,10 @@ fn emit_artifact_notification(&mut self, _path: &Path, _artifact_type: &str) {}
 
     fn emit_future_breakage_report(&mut self, _diags: Vec<Diagnostic>) {}
 
+    /// Write out the diagnostics that were collected instead of being emitted right away.
+    /// This is called at the end of the session, and is only needed by the SARIF format.
+    fn flush_diagnostics(&mut self) {}
+
     /// Emit list of unused externs
     fn emit_unused_externs(
         &mut self,
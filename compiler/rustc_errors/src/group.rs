@@ -0,0 +1,123 @@
+//! Grouping of similar diagnostics, enabled with `-Z group-diagnostics`.
+//!
+//! A change to a widely used item can cause the same error in hundreds of places. With grouping,
+//! only the first diagnostic with a given code and message template is printed. The locations of
+//! the others are listed at the end of the compilation, together with the number of diagnostics
+//! for each code.
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_error_messages::DiagnosticMessage;
+use rustc_span::source_map::SourceMap;
+use rustc_span::Span;
+
+use crate::emitter::Emitter;
+use crate::translation::{to_fluent_args, Translate};
+use crate::{pluralize, Diagnostic, DiagnosticId, Level};
+
+/// Diagnostics are grouped by their code and the template of their message.
+type GroupKey = (Option<String>, String);
+
+struct DiagnosticGroup {
+    /// The message of the first diagnostic of the group.
+    message: String,
+    code: Option<String>,
+    is_error: bool,
+    /// The primary spans of the diagnostics that weren't printed.
+    other_locations: Vec<Span>,
+}
+
+#[derive(Default)]
+pub(crate) struct DiagnosticGroups {
+    groups: FxIndexMap<GroupKey, DiagnosticGroup>,
+    /// The number of errors and warnings with each code, including the grouped ones.
+    code_counts: FxIndexMap<String, usize>,
+}
+
+impl DiagnosticGroups {
+    /// Records `diagnostic` and returns whether it should be printed, which is the case for the
+    /// first diagnostic of each group, and for diagnostics that aren't grouped.
+    pub(crate) fn record(&mut self, diagnostic: &Diagnostic, emitter: &dyn Emitter) -> bool {
+        let is_error = match diagnostic.level {
+            Level::Error { .. } => true,
+            Level::Warning(_) => false,
+            _ => return true,
+        };
+        let Some(&span) = diagnostic.span.primary_spans().first() else { return true };
+
+        let code = diagnostic.code.as_ref().map(|code| match code {
+            DiagnosticId::Error(code) => code.clone(),
+            DiagnosticId::Lint { name, .. } => name.clone(),
+        });
+        if let Some(code) = &code {
+            *self.code_counts.entry(code.clone()).or_default() += 1;
+        }
+
+        // Untranslated messages are their own template, translatable ones are grouped by their
+        // Fluent identifier, whatever their arguments.
+        let template = diagnostic
+            .message
+            .iter()
+            .map(|(message, _)| match message {
+                DiagnosticMessage::Str(s) | DiagnosticMessage::Eager(s) => s.to_string(),
+                DiagnosticMessage::FluentIdentifier(id, attr) => match attr {
+                    Some(attr) => format!("{id}.{attr}"),
+                    None => id.to_string(),
+                },
+            })
+            .collect::<String>();
+
+        if let Some(group) = self.groups.get_mut(&(code.clone(), template.clone())) {
+            group.other_locations.push(span);
+            return false;
+        }
+        let args = to_fluent_args(diagnostic.args());
+        let message = emitter.translate_messages(&diagnostic.message, &args).into_owned();
+        self.groups.insert(
+            (code.clone(), template),
+            DiagnosticGroup { message, code, is_error, other_locations: Vec::new() },
+        );
+        true
+    }
+
+    /// Returns notes listing the locations of the diagnostics that weren't printed, followed by
+    /// the number of diagnostics with each code.
+    pub(crate) fn summary(&self, sm: Option<&SourceMap>) -> Vec<Diagnostic> {
+        let location = |span: Span| match sm {
+            Some(sm) => {
+                let loc = sm.lookup_char_pos(span.lo());
+                let file = sm.filename_for_diagnostics(&loc.file.name);
+                format!("{file}:{}:{}", loc.line, loc.col.0 + 1)
+            }
+            None => format!("{span:?}"),
+        };
+
+        let mut summary = Vec::new();
+        for group in self.groups.values().filter(|group| !group.other_locations.is_empty()) {
+            let kind = if group.is_error { "error" } else { "warning" };
+            let code = group.code.as_ref().map(|code| format!(" [{code}]")).unwrap_or_default();
+            let count = group.other_locations.len();
+            let mut message = format!(
+                "the {kind} `{}`{code} was also reported at {count} other location{}:",
+                group.message,
+                pluralize!(count),
+            );
+            for &span in &group.other_locations {
+                message.push_str(&format!("\n  --> {}", location(span)));
+            }
+            summary.push(Diagnostic::new(Level::Note, message));
+        }
+
+        if !self.code_counts.is_empty() {
+            let mut counts = self.code_counts.iter().collect::<Vec<_>>();
+            counts.sort_by(|(a_code, a_count), (b_code, b_count)| {
+                b_count.cmp(a_count).then_with(|| a_code.cmp(b_code))
+            });
+            let mut message = String::from("number of diagnostics by code:");
+            for (code, count) in counts {
+                message.push_str(&format!("\n  {code}: {count}"));
+            }
+            summary.push(Diagnostic::new(Level::Note, message));
+        }
+        summary
+    }
+}
@@ -31,6 +31,7 @@
 use Level::*;
 
 use emitter::{is_case_difference, Emitter, EmitterWriter};
+use group::DiagnosticGroups;
 use registry::Registry;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap, FxIndexSet};
 use rustc_data_structures::stable_hasher::{Hash128, StableHasher};
@@ -63,10 +64,12 @@
 mod diagnostic_impls;
 pub mod emitter;
 pub mod error;
+mod group;
 pub mod json;
 mod lock;
 pub mod markdown;
 pub mod registry;
+pub mod sarif;
 mod snippet;
 mod styled_buffer;
 #[cfg(test)]
@@ -443,6 +446,9 @@ struct HandlerInner {
     /// The warning count, used for a recap upon finishing
     deduplicated_warn_count: usize,
 
+    /// Groups of similar diagnostics, see `HandlerFlags::group_diagnostics`.
+    diagnostic_groups: DiagnosticGroups,
+
     future_breakage_diagnostics: Vec<Diagnostic>,
 
     /// The [`Self::unstable_expect_diagnostics`] should be empty when this struct is
@@ -519,6 +525,10 @@ pub struct HandlerFlags {
     pub deduplicate_diagnostics: bool,
     /// Track where errors are created. Enabled with `-Ztrack-diagnostics`.
     pub track_diagnostics: bool,
+    /// If true, only the first error or warning with a given code and message is printed, and
+    /// the locations of the others are listed at the end.
+    /// (rustc: see `-Z group-diagnostics`)
+    pub group_diagnostics: bool,
 }
 
 impl Drop for HandlerInner {
@@ -589,6 +599,7 @@ pub fn with_emitter(emitter: Box<dyn Emitter + sync::Send>) -> Self {
                 warn_count: 0,
                 deduplicated_err_count: 0,
                 deduplicated_warn_count: 0,
+                diagnostic_groups: Default::default(),
                 emitter: IntoDynSyncSend(emitter),
                 delayed_span_bugs: Vec::new(),
                 delayed_good_path_bugs: Vec::new(),
@@ -1193,6 +1204,10 @@ pub fn emit_artifact_notification(&self, path: &Path, artifact_type: &str) {
         self.inner.borrow_mut().emit_artifact_notification(path, artifact_type)
     }
 
+    pub fn flush_diagnostics(&self) {
+        self.inner.borrow_mut().emitter.flush_diagnostics()
+    }
+
     pub fn emit_future_breakage_report(&self, diags: Vec<Diagnostic>) {
         self.inner.borrow_mut().emitter.emit_future_breakage_report(diags)
     }
@@ -1386,7 +1401,11 @@ fn emit_diagnostic(&mut self, diagnostic: &mut Diagnostic) -> Option<ErrorGuaran
 
                 diagnostic.children.extract_if(already_emitted_sub).for_each(|_| {});
 
-                self.emitter.emit_diagnostic(diagnostic);
+                if !self.flags.group_diagnostics
+                    || self.diagnostic_groups.record(diagnostic, &**self.emitter)
+                {
+                    self.emitter.emit_diagnostic(diagnostic);
+                }
                 if diagnostic.is_error() {
                     self.deduplicated_err_count += 1;
                 } else if let Warning(_) = diagnostic.level {
@@ -1433,6 +1452,13 @@ fn delayed_bug_count(&self) -> usize {
     fn print_error_count(&mut self, registry: &Registry) {
         self.emit_stashed_diagnostics();
 
+        if self.flags.group_diagnostics {
+            let summary = self.diagnostic_groups.summary(self.emitter.source_map().map(|sm| &**sm));
+            for diagnostic in summary {
+                self.emitter.emit_diagnostic(&diagnostic);
+            }
+        }
+
         let warnings = match self.deduplicated_warn_count {
             0 => Cow::from(""),
             1 => Cow::from("1 warning emitted"),
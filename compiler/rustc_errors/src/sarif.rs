@@ -0,0 +1,477 @@
+//! A SARIF emitter for errors.
+//!
+//! [SARIF] is the Static Analysis Results Interchange Format, which code scanning
+//! and code review platforms can import. A SARIF log is a single JSON document,
+//! so unlike the JSON emitter, which prints each diagnostic as soon as it is
+//! emitted, this emitter collects the diagnostics and writes the log when the
+//! session flushes them, at the end of the compilation.
+//!
+//! Every diagnostic becomes a result of the log, whose rule is the error code or
+//! lint name of the diagnostic. The primary spans are the locations of the result,
+//! the other spans and the children with a span are its related locations, and every
+//! substitution of a suggestion is a fix.
+//!
+//! [SARIF]: https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html
+
+use rustc_data_structures::fx::FxIndexMap;
+use rustc_data_structures::sync::Lrc;
+use rustc_error_messages::FluentArgs;
+use rustc_lint_defs::Applicability;
+use rustc_span::source_map::{FilePathMapping, SourceMap};
+use rustc_span::{SourceFile, Span};
+
+use crate::emitter::Emitter;
+use crate::registry::Registry;
+use crate::translation::{to_fluent_args, Translate};
+use crate::{CodeSuggestion, DiagnosticId, FluentBundle, LazyFallbackBundle, Level, Substitution};
+
+use std::error::Report;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+#[cfg(test)]
+mod tests;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+
+pub struct SarifEmitter {
+    dst: Box<dyn Write + Send>,
+    registry: Option<Registry>,
+    sm: Lrc<SourceMap>,
+    fluent_bundle: Option<Lrc<FluentBundle>>,
+    fallback_bundle: LazyFallbackBundle,
+    /// The rules the results refer to, by their id.
+    rules: FxIndexMap<String, Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl SarifEmitter {
+    pub fn stderr(
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter::new(
+            Box::new(io::BufWriter::new(io::stderr())),
+            registry,
+            source_map,
+            fluent_bundle,
+            fallback_bundle,
+        )
+    }
+
+    pub fn basic(
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        let file_path_mapping = FilePathMapping::empty();
+        SarifEmitter::stderr(
+            None,
+            Lrc::new(SourceMap::new(file_path_mapping)),
+            fluent_bundle,
+            fallback_bundle,
+        )
+    }
+
+    pub fn new(
+        dst: Box<dyn Write + Send>,
+        registry: Option<Registry>,
+        source_map: Lrc<SourceMap>,
+        fluent_bundle: Option<Lrc<FluentBundle>>,
+        fallback_bundle: LazyFallbackBundle,
+    ) -> SarifEmitter {
+        SarifEmitter {
+            dst,
+            registry,
+            sm: source_map,
+            fluent_bundle,
+            fallback_bundle,
+            rules: Default::default(),
+            results: Vec::new(),
+        }
+    }
+
+    /// Returns the id of the rule for `code` and its index in the rules of the run.
+    fn rule(&mut self, code: &DiagnosticId) -> (String, usize) {
+        let (id, is_error_code) = match code {
+            DiagnosticId::Error(code) => (code, true),
+            DiagnosticId::Lint { name, .. } => (name, false),
+        };
+        if let Some(index) = self.rules.get_index_of(id) {
+            return (id.clone(), index);
+        }
+
+        let explanation = self
+            .registry
+            .as_ref()
+            .and_then(|registry| registry.try_find_description(id).ok())
+            .filter(|_| is_error_code);
+        let rule = Rule {
+            id: id.clone(),
+            help_uri: explanation
+                .map(|_| format!("https://doc.rust-lang.org/error_codes/{id}.html")),
+            full_description: explanation.map(|text| Message { text: text.to_owned() }),
+        };
+        (id.clone(), self.rules.insert_full(id.clone(), rule).0)
+    }
+
+    fn location(&self, span: Span, message: Option<String>) -> Option<Location> {
+        if span.is_dummy() {
+            return None;
+        }
+        let start = self.sm.lookup_char_pos(span.lo());
+        Some(Location {
+            physical_location: PhysicalLocation {
+                artifact_location: self.artifact_location(&start.file),
+                region: self.region(span),
+            },
+            message: message.map(|text| Message { text }),
+        })
+    }
+
+    fn artifact_location(&self, file: &SourceFile) -> ArtifactLocation {
+        let path = self.sm.filename_for_diagnostics(&file.name).to_string();
+        ArtifactLocation { uri: path_to_uri(&path) }
+    }
+
+    fn region(&self, span: Span) -> Region {
+        let start = self.sm.lookup_char_pos(span.lo());
+        let end = self.sm.lookup_char_pos(span.hi());
+        let byte_offset = start.file.original_relative_byte_pos(span.lo()).0;
+        let byte_end = start.file.original_relative_byte_pos(span.hi()).0;
+        Region {
+            start_line: start.line,
+            start_column: start.col.0 + 1,
+            end_line: end.line,
+            end_column: end.col.0 + 1,
+            byte_offset,
+            byte_length: byte_end.saturating_sub(byte_offset),
+        }
+    }
+
+    fn fix(
+        &self,
+        suggestion: &CodeSuggestion,
+        substitution: &Substitution,
+        args: &FluentArgs<'_>,
+    ) -> Fix {
+        let description =
+            self.translate_message(&suggestion.msg, args).map_err(Report::new).unwrap();
+
+        // A substitution can change several files, and SARIF groups the replacements by file.
+        let mut changes: FxIndexMap<String, Vec<Replacement>> = Default::default();
+        for part in &substitution.parts {
+            let file = self.sm.lookup_source_file(part.span.lo());
+            changes.entry(self.artifact_location(&file).uri).or_default().push(Replacement {
+                deleted_region: self.region(part.span),
+                inserted_content: ArtifactContent { text: part.snippet.clone() },
+            });
+        }
+
+        Fix {
+            description: Message { text: description.into_owned() },
+            artifact_changes: changes
+                .into_iter()
+                .map(|(uri, replacements)| ArtifactChange {
+                    artifact_location: ArtifactLocation { uri },
+                    replacements,
+                })
+                .collect(),
+            properties: FixProperties { applicability: suggestion.applicability },
+        }
+    }
+
+    fn write_log(&mut self) -> io::Result<()> {
+        let log = SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: [Run {
+                tool: Tool {
+                    driver: ToolComponent {
+                        name: "rustc",
+                        information_uri: "https://www.rust-lang.org/",
+                        rules: self.rules.values().collect(),
+                    },
+                },
+                results: &self.results,
+                column_kind: "unicodeCodePoints",
+            }],
+        };
+        writeln!(&mut self.dst, "{}", serde_json::to_string_pretty(&log).unwrap())?;
+        self.dst.flush()
+    }
+}
+
+impl Translate for SarifEmitter {
+    fn fluent_bundle(&self) -> Option<&Lrc<FluentBundle>> {
+        self.fluent_bundle.as_ref()
+    }
+
+    fn fallback_fluent_bundle(&self) -> &FluentBundle {
+        &self.fallback_bundle
+    }
+}
+
+impl Emitter for SarifEmitter {
+    fn emit_diagnostic(&mut self, diag: &crate::Diagnostic) {
+        let level = match diag.level {
+            Level::Bug | Level::DelayedBug | Level::Fatal | Level::Error { .. } => "error",
+            Level::Warning(_) => "warning",
+            Level::Note | Level::OnceNote | Level::Help => "note",
+            Level::Allow | Level::Expect(_) => "none",
+            // Failure notes like "aborting due to previous error" only summarize the
+            // other diagnostics.
+            Level::FailureNote => return,
+        };
+        let args = to_fluent_args(diag.args());
+
+        let mut text = self.translate_messages(&diag.message, &args).into_owned();
+        let mut locations = Vec::new();
+        let mut related_locations = Vec::new();
+        for span_label in diag.span.span_labels() {
+            let label = span_label.label.as_ref().map(|label| {
+                self.translate_message(label, &args).map_err(Report::new).unwrap().into_owned()
+            });
+            let Some(location) = self.location(span_label.span, label) else { continue };
+            if span_label.is_primary {
+                locations.push(location);
+            } else {
+                related_locations.push(location);
+            }
+        }
+        for child in &diag.children {
+            let message = self.translate_messages(&child.message, &args).into_owned();
+            let span = child.render_span.as_ref().unwrap_or(&child.span);
+            let child_locations = span
+                .primary_spans()
+                .iter()
+                .filter_map(|&span| self.location(span, Some(message.clone())))
+                .collect::<Vec<_>>();
+            if child_locations.is_empty() {
+                // Without a location, the child is only meaningful as part of the message,
+                // so append it the way the human readable output does.
+                text.push_str(&format!("\n= {}: {message}", child.level));
+            } else {
+                related_locations.extend(child_locations);
+            }
+        }
+
+        let fixes = diag
+            .suggestions
+            .iter()
+            .flatten()
+            .flat_map(|suggestion| {
+                suggestion
+                    .substitutions
+                    .iter()
+                    .map(|substitution| self.fix(suggestion, substitution, &args))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let (rule_id, rule_index) = diag.code.as_ref().map(|code| self.rule(code)).unzip();
+        self.results.push(SarifResult {
+            rule_id,
+            rule_index,
+            level,
+            message: Message { text },
+            locations,
+            related_locations,
+            fixes,
+        });
+    }
+
+    fn source_map(&self) -> Option<&Lrc<SourceMap>> {
+        Some(&self.sm)
+    }
+
+    fn should_show_explain(&self) -> bool {
+        false
+    }
+
+    fn flush_diagnostics(&mut self) {
+        // Like the other emitters, don't print anything if there were no diagnostics. This also
+        // keeps the emitters of early errors, which are replaced once the session is created,
+        // from printing logs of their own.
+        if self.results.is_empty() {
+            return;
+        }
+        // This may run while unwinding from a fatal error, so a failure must not panic. It
+        // can't be reported as a diagnostic either, since writing diagnostics is what failed.
+        if let Err(e) = self.write_log() {
+            eprintln!("error: failed to write the SARIF log of the diagnostics: {e}");
+        }
+        self.rules.clear();
+        self.results.clear();
+    }
+}
+
+impl Drop for SarifEmitter {
+    fn drop(&mut self) {
+        // Early errors are emitted without a session, so nothing flushes them.
+        self.flush_diagnostics();
+    }
+}
+
+/// Converts a path to the URI reference of an artifact. Relative paths stay relative, and
+/// are resolved by SARIF consumers against the directory the compiler was invoked from.
+fn path_to_uri(path: &str) -> String {
+    let is_absolute = Path::new(path).is_absolute();
+    let mut uri = String::new();
+    if is_absolute {
+        uri.push_str("file://");
+        if !path.starts_with('/') {
+            // Windows paths start with the drive letter.
+            uri.push('/');
+        }
+    }
+    for b in path.bytes() {
+        match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(b as char)
+            }
+            b'\\' => uri.push('/'),
+            // In a relative reference, a colon would make the part before it a scheme.
+            b':' if is_absolute => uri.push(':'),
+            _ => uri.push_str(&format!("%{b:02X}")),
+        }
+    }
+    uri
+}
+
+// The following data types are provided just for serialisation.
+
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [Run<'a>; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run<'a> {
+    tool: Tool<'a>,
+    results: &'a [SarifResult],
+    /// Columns are counted in characters, like in the human readable output.
+    column_kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct Tool<'a> {
+    driver: ToolComponent<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolComponent<'a> {
+    name: &'static str,
+    information_uri: &'static str,
+    rules: Vec<&'a Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    /// The error code or the name of the lint.
+    id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help_uri: Option<String>,
+    /// The explanation of the error code, in Markdown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_description: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rule_index: Option<usize>,
+    /// "error", "warning", "note" or "none".
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<Location>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+    /// The label of the span.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<Message>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    /// 1-based.
+    start_line: usize,
+    /// 1-based, character offset.
+    start_column: usize,
+    end_line: usize,
+    /// Exclusive.
+    end_column: usize,
+    byte_offset: u32,
+    byte_length: u32,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+    properties: FixProperties,
+}
+
+#[derive(Serialize)]
+struct FixProperties {
+    applicability: Applicability,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
+}
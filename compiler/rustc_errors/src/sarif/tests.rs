@@ -0,0 +1,135 @@
+use super::*;
+
+use crate::{DiagnosticId, Handler};
+use rustc_span::{BytePos, Span};
+
+use std::str;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+
+struct Shared<T> {
+    data: Arc<Mutex<T>>,
+}
+
+impl<T: Write> Write for Shared<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.data.lock().unwrap().flush()
+    }
+}
+
+/// Emits diagnostics for `code` with `emit` and returns the SARIF log.
+fn sarif_log(code: &str, emit: impl FnOnce(&Handler)) -> Value {
+    rustc_span::create_default_session_globals_then(|| {
+        let sm = Lrc::new(SourceMap::new(FilePathMapping::empty()));
+        sm.new_source_file(Path::new("src/test.rs").to_owned().into(), code.to_owned());
+        let fallback_bundle =
+            crate::fallback_fluent_bundle(vec![crate::DEFAULT_LOCALE_RESOURCE], false);
+
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let emitter = SarifEmitter::new(
+            Box::new(Shared { data: output.clone() }),
+            None,
+            sm,
+            None,
+            fallback_bundle,
+        );
+        let handler = Handler::with_emitter(Box::new(emitter));
+        emit(&handler);
+        // The log is written when the session ends.
+        handler.flush_diagnostics();
+
+        let bytes = output.lock().unwrap();
+        serde_json::from_str(str::from_utf8(&bytes).unwrap()).unwrap()
+    })
+}
+
+#[test]
+fn result() {
+    let log = sarif_log("fn main() {\n    let x = 1;\n}\n", |handler| {
+        let span = Span::with_root_ctxt(BytePos(20), BytePos(21));
+        handler
+            .struct_span_warn(span, "unused variable: `x`")
+            .code(DiagnosticId::Lint {
+                name: "unused_variables".to_owned(),
+                has_future_breakage: false,
+                is_force_warn: false,
+            })
+            .note("`#[warn(unused_variables)]` on by default")
+            .span_suggestion(
+                span,
+                "prefix it with an underscore",
+                "_x",
+                Applicability::MaybeIncorrect,
+            )
+            .emit();
+    });
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "unused_variables");
+
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], "unused_variables");
+    assert_eq!(result["ruleIndex"], 0);
+    assert_eq!(result["level"], "warning");
+    assert_eq!(
+        result["message"]["text"],
+        "unused variable: `x`\n= note: `#[warn(unused_variables)]` on by default"
+    );
+
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/test.rs");
+    assert_eq!(location["region"]["startLine"], 2);
+    assert_eq!(location["region"]["startColumn"], 9);
+    assert_eq!(location["region"]["endColumn"], 10);
+    assert_eq!(location["region"]["byteOffset"], 20);
+    assert_eq!(location["region"]["byteLength"], 1);
+
+    let fix = &result["fixes"][0];
+    assert_eq!(fix["description"]["text"], "prefix it with an underscore");
+    assert_eq!(fix["properties"]["applicability"], "MaybeIncorrect");
+    let replacement = &fix["artifactChanges"][0]["replacements"][0];
+    assert_eq!(replacement["deletedRegion"]["byteOffset"], 20);
+    assert_eq!(replacement["insertedContent"]["text"], "_x");
+}
+
+#[test]
+fn levels_and_rules() {
+    let log = sarif_log("fn main() {}\n", |handler| {
+        for (lo, hi) in [(0, 2), (3, 7)] {
+            let span = Span::with_root_ctxt(BytePos(lo), BytePos(hi));
+            handler
+                .struct_span_err(span, "mismatched types")
+                .code(DiagnosticId::Error("E0308".to_owned()))
+                .emit();
+        }
+        handler.struct_err("no location").emit();
+    });
+
+    let run = &log["runs"][0];
+    // Both errors share the same rule.
+    assert_eq!(run["tool"]["driver"]["rules"].as_array().unwrap().len(), 1);
+
+    let results = run["results"].as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1]["level"], "error");
+    assert_eq!(results[1]["ruleIndex"], 0);
+    assert_eq!(results[2]["ruleId"], Value::Null);
+    assert_eq!(results[2]["locations"].as_array().unwrap().len(), 0);
+}
+
+#[test]
+fn uris() {
+    assert_eq!(path_to_uri("src/main.rs"), "src/main.rs");
+    assert_eq!(path_to_uri("src\\my file.rs"), "src/my%20file.rs");
+    assert_eq!(path_to_uri("a:b.rs"), "a%3Ab.rs");
+    #[cfg(unix)]
+    assert_eq!(path_to_uri("/home/user/main.rs"), "file:///home/user/main.rs");
+    #[cfg(windows)]
+    assert_eq!(path_to_uri("C:\\src\\main.rs"), "file:///C:/src/main.rs");
+}
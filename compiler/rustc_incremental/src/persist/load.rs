@@ -145,6 +145,7 @@ pub fn load_dep_graph(sess: &Session) -> DepGraphFuture {
     // Fortunately, we just checked that this isn't the case.
     let path = dep_graph_path(&sess);
     let report_incremental_info = sess.opts.unstable_opts.incremental_info;
+    let explain_invalidation = sess.opts.unstable_opts.incremental_explain;
     let expected_hash = sess.opts.dep_tracking_hash(false);
 
     let mut prev_work_products = UnordMap::default();
@@ -197,7 +198,7 @@ pub fn load_dep_graph(sess: &Session) -> DepGraphFuture {
                 let prev_commandline_args_hash = u64::decode(&mut decoder);
 
                 if prev_commandline_args_hash != expected_hash {
-                    if report_incremental_info {
+                    if report_incremental_info || explain_invalidation {
                         eprintln!(
                             "[incremental] completely ignoring cache because of \
                                     differing commandline arguments"
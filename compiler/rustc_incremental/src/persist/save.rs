@@ -43,6 +43,9 @@ pub fn save_dep_graph(tcx: TyCtxt<'_>) {
         if sess.opts.unstable_opts.incremental_info {
             tcx.dep_graph.print_incremental_info()
         }
+        if sess.opts.unstable_opts.incremental_explain {
+            tcx.dep_graph.print_changed_inputs()
+        }
 
         join(
             move || {
@@ -176,5 +179,6 @@ pub fn build_dep_graph(
         encoder,
         sess.opts.unstable_opts.query_dep_graph,
         sess.opts.unstable_opts.incremental_info,
+        sess.opts.unstable_opts.incremental_explain,
     ))
 }
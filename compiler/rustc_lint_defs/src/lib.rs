@@ -326,6 +326,12 @@ pub struct Lint {
     pub feature_gate: Option<Symbol>,
 
     pub crate_level_only: bool,
+
+    /// The documentation of the lint, taken from the doc comment of its declaration.
+    ///
+    /// This is the Markdown shown by `rustc --explain`. It is empty for lints declared without
+    /// documentation, like tool lints.
+    pub doc: &'static str,
 }
 
 /// Extra information for a future incompatibility lint.
@@ -397,6 +403,7 @@ pub const fn default_fields_for_macro() -> Self {
             future_incompatible: None,
             feature_gate: None,
             crate_level_only: false,
+            doc: "",
         }
     }
 
@@ -706,20 +713,21 @@ pub fn buffer_lint_with_diagnostic(
 /// commands to avoid rebuilding the compiler.
 #[macro_export]
 macro_rules! declare_lint {
-    ($(#[$attr:meta])* $vis: vis $NAME: ident, $Level: ident, $desc: expr) => (
+    ($(#[$($attr:tt)*])* $vis: vis $NAME: ident, $Level: ident, $desc: expr) => (
         $crate::declare_lint!(
-            $(#[$attr])* $vis $NAME, $Level, $desc,
+            $(#[$($attr)*])* $vis $NAME, $Level, $desc,
         );
     );
-    ($(#[$attr:meta])* $vis: vis $NAME: ident, $Level: ident, $desc: expr,
+    ($(#[$($attr:tt)*])* $vis: vis $NAME: ident, $Level: ident, $desc: expr,
      $(@feature_gate = $gate:expr;)?
      $(@future_incompatible = FutureIncompatibleInfo { $($field:ident : $val:expr),* $(,)*  }; )?
      $($v:ident),*) => (
-        $(#[$attr])*
+        $(#[$($attr)*])*
         $vis static $NAME: &$crate::Lint = &$crate::Lint {
             name: stringify!($NAME),
             default_level: $crate::$Level,
             desc: $desc,
+            doc: $crate::lint_doc!($([$($attr)*])*),
             edition_lint_opts: None,
             is_plugin: false,
             $($v: true,)*
@@ -731,10 +739,10 @@ macro_rules! declare_lint {
             ..$crate::Lint::default_fields_for_macro()
         };
     );
-    ($(#[$attr:meta])* $vis: vis $NAME: ident, $Level: ident, $desc: expr,
+    ($(#[$($attr:tt)*])* $vis: vis $NAME: ident, $Level: ident, $desc: expr,
      $lint_edition: expr => $edition_level: ident
     ) => (
-        $(#[$attr])*
+        $(#[$($attr)*])*
         $vis static $NAME: &$crate::Lint = &$crate::Lint {
             name: stringify!($NAME),
             default_level: $crate::$Level,
@@ -742,10 +750,33 @@ macro_rules! declare_lint {
             edition_lint_opts: Some(($lint_edition, $crate::Level::$edition_level)),
             report_in_external_macro: false,
             is_plugin: false,
+            doc: $crate::lint_doc!($([$($attr)*])*),
         };
     );
 }
 
+/// Collects the doc comments among the attributes of a `declare_lint!` invocation into a
+/// `&'static str`, one line per doc comment line.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! lint_doc {
+    ($([$($attr:tt)*])*) => {
+        concat!($($crate::lint_doc_line!($($attr)*)),*)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! lint_doc_line {
+    // Doc comments are passed to macros as `#[doc = "..."]` attributes.
+    (doc = $doc:literal) => {
+        concat!($doc, "\n")
+    };
+    ($($attr:tt)*) => {
+        ""
+    };
+}
+
 #[macro_export]
 macro_rules! declare_tool_lint {
     (
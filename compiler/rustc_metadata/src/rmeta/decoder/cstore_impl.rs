@@ -19,6 +19,7 @@
 use rustc_middle::ty::{self, TyCtxt};
 use rustc_session::cstore::CrateStore;
 use rustc_session::{Session, StableCrateId};
+use rustc_span::edition::Edition;
 use rustc_span::hygiene::{ExpnHash, ExpnId};
 use rustc_span::symbol::{kw, Symbol};
 use rustc_span::Span;
@@ -569,6 +570,16 @@ pub fn num_def_ids_untracked(&self, cnum: CrateNum) -> usize {
         self.get_crate_data(cnum).num_def_ids()
     }
 
+    pub fn crate_edition_untracked(&self, cnum: CrateNum) -> Edition {
+        self.get_crate_data(cnum).root.edition
+    }
+
+    /// The target features that were enabled when `cnum` was compiled.
+    pub fn crate_target_features_untracked(&self, cnum: CrateNum) -> Vec<Symbol> {
+        let cdata = self.get_crate_data(cnum);
+        cdata.root.target_features.decode(cdata).collect()
+    }
+
     pub fn get_proc_macro_quoted_span_untracked(
         &self,
         cnum: CrateNum,
@@ -579,6 +579,8 @@ macro_rules! stat {
 
         let lib_features = stat!("lib-features", || self.encode_lib_features());
 
+        let target_features = stat!("target-features", || self.encode_target_features());
+
         let stability_implications =
             stat!("stability-implications", || self.encode_stability_implications());
 
@@ -692,6 +694,7 @@ macro_rules! stat {
                 crate_deps,
                 dylib_dependency_formats,
                 lib_features,
+                target_features,
                 stability_implications,
                 lang_items,
                 diagnostic_items,
@@ -1879,6 +1882,11 @@ fn encode_lib_features(&mut self) -> LazyArray<(Symbol, Option<Symbol>)> {
         self.lazy_array(lib_features.to_vec())
     }
 
+    fn encode_target_features(&mut self) -> LazyArray<Symbol> {
+        let tcx = self.tcx;
+        self.lazy_array(tcx.sess.target_features.iter().copied())
+    }
+
     fn encode_stability_implications(&mut self) -> LazyArray<(Symbol, Symbol)> {
         empty_proc_macro!(self);
         let tcx = self.tcx;
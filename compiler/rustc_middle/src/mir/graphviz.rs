@@ -9,12 +9,17 @@
 use super::generic_graph::mir_fn_to_generic_graph;
 use super::pretty::dump_mir_def_ids;
 
-/// Write a graphviz DOT graph of a list of MIRs.
-pub fn write_mir_graphviz<W>(tcx: TyCtxt<'_>, single: Option<DefId>, w: &mut W) -> io::Result<()>
+/// Write a graphviz DOT graph of the MIR of the given items, or of all the items of the crate if
+/// `def_ids` is `None`.
+pub fn write_mir_graphviz<W>(
+    tcx: TyCtxt<'_>,
+    def_ids: Option<&[DefId]>,
+    w: &mut W,
+) -> io::Result<()>
 where
     W: Write,
 {
-    let def_ids = dump_mir_def_ids(tcx, single);
+    let def_ids = dump_mir_def_ids(tcx, def_ids);
 
     let mirs = def_ids
         .iter()
@@ -260,17 +260,18 @@ pub fn create_dump_file<'tcx>(
     )
 }
 
-/// Write out a human-readable textual representation for the given MIR.
+/// Write out a human-readable textual representation for the MIR of the given items, or of all
+/// the items of the crate if `def_ids` is `None`.
 pub fn write_mir_pretty<'tcx>(
     tcx: TyCtxt<'tcx>,
-    single: Option<DefId>,
+    def_ids: Option<&[DefId]>,
     w: &mut dyn Write,
 ) -> io::Result<()> {
     writeln!(w, "// WARNING: This output format is intended for human consumers only")?;
     writeln!(w, "// and is subject to change without notice. Knock yourself out.")?;
 
     let mut first = true;
-    for def_id in dump_mir_def_ids(tcx, single) {
+    for def_id in dump_mir_def_ids(tcx, def_ids) {
         if first {
             first = false;
         } else {
@@ -1079,9 +1080,9 @@ fn write_user_type_annotations(
     Ok(())
 }
 
-pub fn dump_mir_def_ids(tcx: TyCtxt<'_>, single: Option<DefId>) -> Vec<DefId> {
-    if let Some(i) = single {
-        vec![i]
+pub fn dump_mir_def_ids(tcx: TyCtxt<'_>, def_ids: Option<&[DefId]>) -> Vec<DefId> {
+    if let Some(def_ids) = def_ids {
+        def_ids.to_vec()
     } else {
         tcx.mir_keys(()).iter().map(|def_id| def_id.to_def_id()).collect()
     }
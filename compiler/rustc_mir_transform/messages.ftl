@@ -36,6 +36,8 @@ mir_transform_mutation_layout_constrained_label = mutation of layout constrained
 mir_transform_mutation_layout_constrained_note = mutating layout constrained fields cannot statically be checked for valid values
 mir_transform_operation_will_panic = this operation will panic at runtime
 
+mir_transform_optimization_remark = {$pass} ({$kind}): {$message}
+
 mir_transform_requires_unsafe = {$details} is unsafe and requires unsafe {$op_in_unsafe_fn_allowed ->
     [true] function or block
     *[false] block
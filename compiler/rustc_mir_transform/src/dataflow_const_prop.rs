@@ -19,6 +19,7 @@
 use rustc_span::DUMMY_SP;
 use rustc_target::abi::{Align, FieldIdx, VariantIdx};
 
+use crate::remark::{emit_remark, remarks_enabled, user_variable_name, RemarkKind};
 use crate::MirPass;
 
 // These constants are somewhat random guesses and have not been optimized.
@@ -38,6 +39,12 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
         debug!(def_id = ?body.source.def_id());
         if tcx.sess.mir_opt_level() < 4 && body.basic_blocks.len() > BLOCK_LIMIT {
             debug!("aborted dataflow const prop due too many basic blocks");
+            emit_remark(tcx, self.name(), RemarkKind::Missed, body.span, || {
+                format!(
+                    "the function wasn't analyzed: it has more than {BLOCK_LIMIT} basic blocks, \
+                     which requires `-Zmir-opt-level=4`"
+                )
+            });
             return;
         }
 
@@ -62,6 +69,9 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
         // Collect results and patch the body afterwards.
         let mut visitor = CollectAndPatch::new(tcx);
         debug_span!("collect").in_scope(|| results.visit_reachable_with(body, &mut visitor));
+        if remarks_enabled(tcx, self.name()) {
+            visitor.remark_replacements(body);
+        }
         debug_span!("patch").in_scope(|| visitor.visit_body(body));
     }
 }
@@ -415,6 +425,38 @@ fn make_operand(&self, scalar: ScalarTy<'tcx>) -> Operand<'tcx> {
             literal: ConstantKind::Val(ConstValue::Scalar(scalar.0), scalar.1),
         }))
     }
+
+    /// Emits the `-Zmir-remark` remarks for the assignments and the uses of variables of the
+    /// source code that are about to be replaced by constants.
+    fn remark_replacements(&self, body: &Body<'tcx>) {
+        let pass = DataflowConstProp.name();
+        let mut assignments: Vec<_> = self.assignments.iter().collect();
+        assignments.sort_unstable_by_key(|(location, _)| **location);
+        for (&location, value) in assignments {
+            let span = body.source_info(location).span;
+            let value = self.make_operand(value.clone());
+            emit_remark(self.tcx, pass, RemarkKind::Applied, span, || {
+                format!("the value computed here was replaced by `{value:?}`")
+            });
+        }
+
+        let mut uses: Vec<_> = self
+            .before_effect
+            .iter()
+            .filter_map(|((location, place), value)| {
+                let name = user_variable_name(body, place.as_local()?)?;
+                Some((*location, name, value))
+            })
+            .collect();
+        uses.sort_unstable_by_key(|&(location, name, _)| (location, name));
+        for (location, name, value) in uses {
+            let span = body.source_info(location).span;
+            let value = self.make_operand(value.clone());
+            emit_remark(self.tcx, pass, RemarkKind::Applied, span, || {
+                format!("this use of `{name}` was replaced by `{value:?}`")
+            });
+        }
+    }
 }
 
 impl<'mir, 'tcx>
@@ -133,6 +133,7 @@
 
 use std::collections::hash_map::{Entry, OccupiedEntry};
 
+use crate::remark::{emit_remark, remarks_enabled, user_variable_name, RemarkKind};
 use crate::simplify::remove_dead_blocks;
 use crate::MirPass;
 use rustc_data_structures::fx::FxHashMap;
@@ -147,6 +148,7 @@
 use rustc_middle::ty::TyCtxt;
 use rustc_mir_dataflow::impls::MaybeLiveLocals;
 use rustc_mir_dataflow::{Analysis, ResultsCursor};
+use rustc_span::Symbol;
 
 pub struct DestinationPropagation;
 
@@ -199,6 +201,10 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
                 .into_results_cursor(body);
             dest_prop_mir_dump(tcx, body, &mut live, round_count);
 
+            // The candidates removed in the later rounds were usually already reported.
+            let unfiltered_candidates = (round_count == 0 && remarks_enabled(tcx, self.name()))
+                .then(|| candidates.c.clone());
+
             FilterInformation::filter_liveness(
                 &mut candidates,
                 &mut live,
@@ -206,6 +212,10 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
                 body,
             );
 
+            if let Some(unfiltered_candidates) = unfiltered_candidates {
+                remark_conflicts(tcx, body, &unfiltered_candidates, &candidates);
+            }
+
             // Because we do not update liveness information, it is unsound to use a local for more
             // than one merge operation within a single round of optimizations. We store here which
             // ones we have already used.
@@ -227,6 +237,7 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
                 }) {
                     break;
                 }
+                remark_merge(tcx, body, RemarkKind::Applied, *src, *dest, "");
                 merges.insert(*src, *dest);
                 merged_locals.insert(*src);
                 merged_locals.insert(*dest);
@@ -251,6 +262,54 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
     }
 }
 
+/// Emits the `-Zmir-remark` remark for the merge of `src` into `dest`, if one of them is a variable
+/// of the source code.
+fn remark_merge<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    kind: RemarkKind,
+    src: Local,
+    dest: Local,
+    reason: &str,
+) {
+    let (src_name, dest_name) = (user_variable_name(body, src), user_variable_name(body, dest));
+    let span = match (src_name, dest_name) {
+        (Some(_), _) => body.local_decls[src].source_info.span,
+        (None, Some(_)) => body.local_decls[dest].source_info.span,
+        (None, None) => return,
+    };
+    emit_remark(tcx, DestinationPropagation.name(), kind, span, || {
+        let describe = |name: Option<Symbol>| match name {
+            Some(name) => format!("`{name}`"),
+            None => "a temporary".to_string(),
+        };
+        let (src, dest) = (describe(src_name), describe(dest_name));
+        match kind {
+            RemarkKind::Applied => format!("{src} was merged into {dest}"),
+            RemarkKind::Missed => format!("{src} was not merged into {dest}: {reason}"),
+        }
+    });
+}
+
+/// Emits the `-Zmir-remark` remarks for the candidates that were removed because of conflicts.
+fn remark_conflicts<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    body: &Body<'tcx>,
+    unfiltered_candidates: &FxHashMap<Local, Vec<Local>>,
+    candidates: &Candidates<'_>,
+) {
+    let mut removed: Vec<(Local, Local)> = unfiltered_candidates
+        .iter()
+        .flat_map(|(&src, dests)| dests.iter().map(move |&dest| (src, dest)))
+        .filter(|(src, dest)| !candidates.c.get(src).is_some_and(|dests| dests.contains(dest)))
+        .collect();
+    removed.sort_unstable();
+    for (src, dest) in removed {
+        let reason = "they are used at the same time";
+        remark_merge(tcx, body, RemarkKind::Missed, src, dest, reason);
+    }
+}
+
 /// Container for the various allocations that we need.
 ///
 /// We store these here and hand out `&mut` access to them, instead of dropping and recreating them
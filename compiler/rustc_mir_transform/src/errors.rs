@@ -265,3 +265,13 @@ pub(crate) struct SimdShuffleLastConst {
     #[primary_span]
     pub span: Span,
 }
+
+#[derive(Diagnostic)]
+#[diag(mir_transform_optimization_remark)]
+pub(crate) struct OptimizationRemark {
+    #[primary_span]
+    pub span: Span,
+    pub pass: &'static str,
+    pub kind: &'static str,
+    pub message: String,
+}
@@ -14,6 +14,7 @@
 use rustc_target::abi::FieldIdx;
 use rustc_target::spec::abi::Abi;
 
+use crate::remark::{emit_remark, RemarkKind};
 use crate::simplify::{remove_dead_blocks, CfgSimplifier};
 use crate::util;
 use crate::MirPass;
@@ -141,10 +142,12 @@ fn process_blocks(&mut self, caller_body: &mut Body<'tcx>, blocks: Range<BasicBl
             match self.try_inlining(caller_body, &callsite) {
                 Err(reason) => {
                     debug!("not-inlined {} [{}]", callsite.callee, reason);
+                    self.remark(caller_body, &callsite, RemarkKind::Missed, reason);
                     continue;
                 }
                 Ok(new_blocks) => {
                     debug!("inlined {}", callsite.callee);
+                    self.remark(caller_body, &callsite, RemarkKind::Applied, "");
                     self.changed = true;
 
                     self.history.push(callsite.callee.def_id());
@@ -161,6 +164,27 @@ fn process_blocks(&mut self, caller_body: &mut Body<'tcx>, blocks: Range<BasicBl
         }
     }
 
+    /// Emits the `-Zmir-remark` remark for the inlining of `callsite`, with the reason why it
+    /// wasn't inlined for missed remarks.
+    fn remark(
+        &self,
+        caller_body: &Body<'tcx>,
+        callsite: &CallSite<'tcx>,
+        kind: RemarkKind,
+        reason: &str,
+    ) {
+        let span = callsite.source_info.span;
+        emit_remark(self.tcx, Inline.name(), kind, span, || {
+            let caller = self.tcx.def_path_str(caller_body.source.def_id());
+            match kind {
+                RemarkKind::Applied => format!("`{}` was inlined into `{caller}`", callsite.callee),
+                RemarkKind::Missed => {
+                    format!("`{}` was not inlined into `{caller}`: {reason}", callsite.callee)
+                }
+            }
+        });
+    }
+
     /// Attempts to inline a callsite into the caller body. When successful returns basic blocks
     /// containing the inlined body. Otherwise returns an error describing why inlining didn't take
     /// place.
@@ -86,6 +86,7 @@
 mod nrvo;
 mod prettify;
 mod ref_prop;
+mod remark;
 mod remove_noop_landing_pads;
 mod remove_storage_markers;
 mod remove_uninit_drops;
@@ -1,5 +1,8 @@
+use rustc_middle::mir::graphviz::write_mir_fn_graphviz;
+use rustc_middle::mir::pretty::write_mir_fn;
 use rustc_middle::mir::{self, Body, MirPhase, RuntimePhase};
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_session::config::PpMode;
 use rustc_session::Session;
 
 use crate::{validate, MirPass};
@@ -126,6 +129,7 @@ fn run_passes_inner<'tcx>(
             if dump_enabled {
                 dump_mir_for_pass(tcx, body, &name, true);
             }
+            unpretty_mir_after_pass(tcx, body, &name);
             if validate {
                 validate_body(tcx, body, format!("after pass {name}"));
             }
@@ -171,6 +175,34 @@ pub fn dump_mir_for_pass<'tcx>(
     );
 }
 
+/// Renders `body` for `-Zunpretty=mir=ITEM@after:PASS` if `pass_name` is the requested pass.
+/// The driver prints the rendered bodies once it has built the MIR of the requested items.
+fn unpretty_mir_after_pass<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>, pass_name: &str) {
+    let (filter, cfg) = match &tcx.sess.opts.pretty {
+        Some(PpMode::Mir(filter)) => (filter, false),
+        Some(PpMode::MirCFG(filter)) => (filter, true),
+        _ => return,
+    };
+    if filter.after_pass.as_deref() != Some(pass_name) {
+        return;
+    }
+    // Like `-Zdump-mir`, see the notes on #41697 in `rustc_middle::mir::pretty`.
+    let def_id = body.source.def_id();
+    let def_path = ty::print::with_forced_impl_filename_line!(tcx.def_path_str(def_id));
+    if !filter.matches_item(&def_path) {
+        return;
+    }
+
+    let mut out = Vec::new();
+    if cfg {
+        // The driver puts all the bodies in a single graph.
+        write_mir_fn_graphviz(tcx, body, true, &mut out).unwrap();
+    } else {
+        write_mir_fn(tcx, body, &mut |_, _| Ok(()), &mut out).unwrap();
+    }
+    tcx.sess.unpretty_mir.lock().push((def_id, String::from_utf8(out).unwrap()));
+}
+
 pub fn dump_mir_for_phase_change<'tcx>(tcx: TyCtxt<'tcx>, body: &Body<'tcx>) {
     assert_eq!(body.pass_count, 0);
     mir::dump_mir(tcx, true, body.phase.name(), &"after", body, |_, _| Ok(()))
@@ -0,0 +1,59 @@
+//! Optimization remarks of the MIR passes, requested with `-Zmir-remark`.
+//!
+//! Like the LLVM remarks of `-Cremark`, a remark tells that a pass applied an optimization, or why
+//! it didn't. Remarks are emitted as notes, so they are part of the JSON output with
+//! `--error-format=json`.
+
+use rustc_middle::mir::{Body, Local, VarDebugInfoContents};
+use rustc_middle::ty::print::with_no_trimmed_paths;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::{Span, Symbol};
+
+use crate::errors;
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) enum RemarkKind {
+    /// The pass applied an optimization.
+    Applied,
+    /// The pass didn't apply an optimization, the message tells why.
+    Missed,
+}
+
+/// Whether `-Zmir-remark` requests the remarks of the pass `pass`.
+pub(crate) fn remarks_enabled(tcx: TyCtxt<'_>, pass: &str) -> bool {
+    tcx.sess.opts.unstable_opts.mir_remark.contains(pass)
+}
+
+/// Emits a remark of the pass `pass` if it was requested. The message is only built in that case.
+pub(crate) fn emit_remark(
+    tcx: TyCtxt<'_>,
+    pass: &'static str,
+    kind: RemarkKind,
+    span: Span,
+    message: impl FnOnce() -> String,
+) {
+    if !remarks_enabled(tcx, pass) {
+        return;
+    }
+    let kind = match kind {
+        RemarkKind::Applied => "applied",
+        RemarkKind::Missed => "missed",
+    };
+    // Paths aren't trimmed, as remarks aren't warnings or errors.
+    let message = with_no_trimmed_paths!(message());
+    tcx.sess.emit_note(errors::OptimizationRemark { span, pass, kind, message });
+}
+
+/// Returns the name of `local` if it is a variable of the source code. Remarks are only emitted
+/// about those, as temporaries would only be noise.
+pub(crate) fn user_variable_name(body: &Body<'_>, local: Local) -> Option<Symbol> {
+    if !body.local_decls[local].is_user_variable() {
+        return None;
+    }
+    body.var_debug_info.iter().find_map(|var_debug_info| match var_debug_info.value {
+        VarDebugInfoContents::Place(place) if place.as_local() == Some(local) => {
+            Some(var_debug_info.name)
+        }
+        _ => None,
+    })
+}
@@ -1,3 +1,4 @@
+use crate::remark::{emit_remark, remarks_enabled, user_variable_name, RemarkKind};
 use crate::MirPass;
 use rustc_index::bit_set::{BitSet, GrowableBitSet};
 use rustc_index::IndexVec;
@@ -26,12 +27,19 @@ fn run_pass(&self, tcx: TyCtxt<'tcx>, body: &mut Body<'tcx>) {
 
         let mut excluded = excluded_locals(body);
         let param_env = tcx.param_env_reveal_all_normalized(body.source.def_id());
+        let mut first_round = true;
         loop {
             debug!(?excluded);
             let escaping = escaping_locals(tcx, param_env, &excluded, body);
             debug!(?escaping);
-            let replacements = compute_flattening(tcx, param_env, body, escaping);
+            let replacements = compute_flattening(tcx, param_env, body, &escaping);
             debug!(?replacements);
+            // The later rounds replace the fields of the replaced locals, which aren't variables
+            // of the source code.
+            if first_round && remarks_enabled(tcx, self.name()) {
+                remark_user_variables(tcx, param_env, body, &excluded, &escaping, &replacements);
+            }
+            first_round = false;
             let all_dead_locals = replace_flattened_locals(tcx, body, replacements);
             if !all_dead_locals.is_empty() {
                 excluded.union(&all_dead_locals);
@@ -60,39 +68,10 @@ fn escaping_locals<'tcx>(
     excluded: &BitSet<Local>,
     body: &Body<'tcx>,
 ) -> BitSet<Local> {
-    let is_excluded_ty = |ty: Ty<'tcx>| {
-        if ty.is_union() || ty.is_enum() {
-            return true;
-        }
-        if let ty::Adt(def, _args) = ty.kind() {
-            if def.repr().flags.contains(ReprFlags::IS_SIMD) {
-                // Exclude #[repr(simd)] types so that they are not de-optimized into an array
-                return true;
-            }
-            // We already excluded unions and enums, so this ADT must have one variant
-            let variant = def.variant(FIRST_VARIANT);
-            if variant.fields.len() > 1 {
-                // If this has more than one field, it cannot be a wrapper that only provides a
-                // niche, so we do not want to automatically exclude it.
-                return false;
-            }
-            let Ok(layout) = tcx.layout_of(param_env.and(ty)) else {
-                // We can't get the layout
-                return true;
-            };
-            if layout.layout.largest_niche().is_some() {
-                // This type has a niche
-                return true;
-            }
-        }
-        // Default for non-ADTs
-        false
-    };
-
     let mut set = BitSet::new_empty(body.local_decls.len());
     set.insert_range(RETURN_PLACE..=Local::from_usize(body.arg_count));
     for (local, decl) in body.local_decls().iter_enumerated() {
-        if excluded.contains(local) || is_excluded_ty(decl.ty) {
+        if excluded.contains(local) || is_excluded_ty(tcx, param_env, decl.ty) {
             set.insert(local);
         }
     }
@@ -152,6 +131,74 @@ fn visit_var_debug_info(&mut self, _: &VarDebugInfo<'tcx>) {}
     }
 }
 
+/// Whether locals of type `ty` are never replaced, whatever their uses.
+fn is_excluded_ty<'tcx>(tcx: TyCtxt<'tcx>, param_env: ty::ParamEnv<'tcx>, ty: Ty<'tcx>) -> bool {
+    if ty.is_union() || ty.is_enum() {
+        return true;
+    }
+    if let ty::Adt(def, _args) = ty.kind() {
+        if def.repr().flags.contains(ReprFlags::IS_SIMD) {
+            // Exclude #[repr(simd)] types so that they are not de-optimized into an array
+            return true;
+        }
+        // We already excluded unions and enums, so this ADT must have one variant
+        let variant = def.variant(FIRST_VARIANT);
+        if variant.fields.len() > 1 {
+            // If this has more than one field, it cannot be a wrapper that only provides a
+            // niche, so we do not want to automatically exclude it.
+            return false;
+        }
+        let Ok(layout) = tcx.layout_of(param_env.and(ty)) else {
+            // We can't get the layout
+            return true;
+        };
+        if layout.layout.largest_niche().is_some() {
+            // This type has a niche
+            return true;
+        }
+    }
+    // Default for non-ADTs
+    false
+}
+
+/// Emits the `-Zmir-remark` remarks about the variables of the source code that have an
+/// aggregate type, telling whether they are replaced by their fields.
+fn remark_user_variables<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    param_env: ty::ParamEnv<'tcx>,
+    body: &Body<'tcx>,
+    excluded: &BitSet<Local>,
+    escaping: &BitSet<Local>,
+    replacements: &ReplacementMap<'tcx>,
+) {
+    let pass = ScalarReplacementOfAggregates.name();
+    for (local, decl) in body.local_decls.iter_enumerated() {
+        if !matches!(decl.ty.kind(), ty::Adt(..) | ty::Tuple(..) | ty::Closure(..)) {
+            continue;
+        }
+        let Some(name) = user_variable_name(body, local) else { continue };
+        let span = decl.source_info.span;
+        if replacements.fragments.get(local).is_some_and(Option::is_some) {
+            emit_remark(tcx, pass, RemarkKind::Applied, span, || {
+                format!("`{name}` was replaced by a local for each of its fields")
+            });
+        } else if escaping.contains(local) {
+            let reason = if local.as_usize() <= body.arg_count {
+                "it is an argument of the function"
+            } else if excluded.contains(local) {
+                "its address is taken"
+            } else if is_excluded_ty(tcx, param_env, decl.ty) {
+                "its type is a union, an enum, a SIMD type or a wrapper with a niche"
+            } else {
+                "it is used as a whole, not only through its fields"
+            };
+            emit_remark(tcx, pass, RemarkKind::Missed, span, || {
+                format!("`{name}` was not replaced by its fields: {reason}")
+            });
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 struct ReplacementMap<'tcx> {
     /// Pre-computed list of all "new" locals for each "old" local. This is used to expand storage
@@ -190,7 +237,7 @@ fn compute_flattening<'tcx>(
     tcx: TyCtxt<'tcx>,
     param_env: ty::ParamEnv<'tcx>,
     body: &mut Body<'tcx>,
-    escaping: BitSet<Local>,
+    escaping: &BitSet<Local>,
 ) -> ReplacementMap<'tcx> {
     let mut fragments = IndexVec::from_elem(None, &body.local_decls);
 
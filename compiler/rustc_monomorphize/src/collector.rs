@@ -207,13 +207,21 @@ pub struct UsageMap<'tcx> {
 
     // Maps every mono item to the mono items that use it.
     user_map: FxHashMap<MonoItem<'tcx>, Vec<MonoItem<'tcx>>>,
+
+    // Maps every mono item to the spans at which it is used, in the same order
+    // as `user_map`. Only recorded for `-Zdump-mono-bloat`.
+    user_spans: Option<FxHashMap<MonoItem<'tcx>, Vec<Span>>>,
 }
 
 type MonoItems<'tcx> = Vec<Spanned<MonoItem<'tcx>>>;
 
 impl<'tcx> UsageMap<'tcx> {
-    fn new() -> UsageMap<'tcx> {
-        UsageMap { used_map: FxHashMap::default(), user_map: FxHashMap::default() }
+    fn new(record_spans: bool) -> UsageMap<'tcx> {
+        UsageMap {
+            used_map: FxHashMap::default(),
+            user_map: FxHashMap::default(),
+            user_spans: record_spans.then(FxHashMap::default),
+        }
     }
 
     fn record_used<'a>(
@@ -223,6 +231,12 @@ fn record_used<'a>(
     ) where
         'tcx: 'a,
     {
+        if let Some(user_spans) = &mut self.user_spans {
+            for used_item in used_items {
+                user_spans.entry(used_item.node).or_default().push(used_item.span);
+            }
+        }
+
         let used_items: Vec<_> = used_items.iter().map(|item| item.node).collect();
         for &used_item in used_items.iter() {
             self.user_map.entry(used_item).or_default().push(user_item);
@@ -235,6 +249,13 @@ pub fn get_user_items(&self, item: MonoItem<'tcx>) -> &[MonoItem<'tcx>] {
         self.user_map.get(&item).map(|items| items.as_slice()).unwrap_or(&[])
     }
 
+    /// Returns the spans at which `item` is used, in the same order as `get_user_items`.
+    /// Returns `None` if the spans weren't recorded.
+    pub fn get_user_spans(&self, item: MonoItem<'tcx>) -> Option<&[Span]> {
+        let user_spans = self.user_spans.as_ref()?;
+        Some(user_spans.get(&item).map(|spans| spans.as_slice()).unwrap_or(&[]))
+    }
+
     /// Internally iterate over all inlined items used by `item`.
     pub fn for_each_inlined_used_item<F>(&self, tcx: TyCtxt<'tcx>, item: MonoItem<'tcx>, mut f: F)
     where
@@ -263,7 +284,8 @@ pub fn collect_crate_mono_items(
     debug!("building mono item graph, beginning at roots");
 
     let mut visited = MTLock::new(FxHashSet::default());
-    let mut usage_map = MTLock::new(UsageMap::new());
+    let record_spans = tcx.sess.opts.unstable_opts.dump_mono_bloat.enabled();
+    let mut usage_map = MTLock::new(UsageMap::new(record_spans));
     let recursion_limit = tcx.recursion_limit();
 
     {
@@ -119,7 +119,9 @@
 
 use crate::collector::UsageMap;
 use crate::collector::{self, MonoItemCollectionMode};
-use crate::errors::{CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode};
+use crate::errors::{
+    CouldntDumpMonoBloat, CouldntDumpMonoStats, SymbolAlreadyDefined, UnknownCguCollectionMode,
+};
 
 struct PartitioningCx<'a, 'tcx> {
     tcx: TyCtxt<'tcx>,
@@ -1147,6 +1149,14 @@ fn collect_and_partition_mono_items(tcx: TyCtxt<'_>, (): ()) -> (&DefIdSet, &[Co
         }
     }
 
+    if let SwitchWithOptPath::Enabled(ref path) = tcx.sess.opts.unstable_opts.dump_mono_bloat {
+        if let Err(err) =
+            dump_mono_bloat_report(tcx, &items, &usage_map, path, tcx.crate_name(LOCAL_CRATE))
+        {
+            tcx.sess.emit_fatal(CouldntDumpMonoBloat { error: err.to_string() });
+        }
+    }
+
     if tcx.sess.opts.unstable_opts.print_mono_items.is_some() {
         let mut item_to_cgus: FxHashMap<_, Vec<_>> = Default::default();
 
@@ -1279,6 +1289,156 @@ struct MonoItem {
     Ok(())
 }
 
+/// Outputs a report attributing the instantiations of generic items to their definition
+/// and to the chain of items that caused them, to a file in the given output directory.
+/// This shows which generic APIs are responsible for most of the generated code.
+fn dump_mono_bloat_report<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    items: &FxHashSet<MonoItem<'tcx>>,
+    usage_map: &UsageMap<'tcx>,
+    output_directory: &Option<PathBuf>,
+    crate_name: Symbol,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Bound the length of the chains, recursive generic code can make them very long.
+    const MAX_CHAIN_LEN: usize = 16;
+
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+
+    let format = tcx.sess.opts.unstable_opts.dump_mono_stats_format;
+    let ext = format.extension();
+    let filename = format!("{crate_name}.mono_bloat.{ext}");
+    let output_path = output_directory.join(&filename);
+    let file = File::create(&output_path)?;
+    let mut file = BufWriter::new(file);
+
+    // Closures are generic if the function they are defined in is.
+    let is_generic = |item: MonoItem<'tcx>| match item {
+        MonoItem::Fn(instance) => {
+            let def_id = tcx.typeck_root_def_id(instance.def_id());
+            tcx.generics_of(def_id).requires_monomorphization(tcx)
+        }
+        MonoItem::Static(_) | MonoItem::GlobalAsm(_) => false,
+    };
+
+    #[derive(serde::Serialize)]
+    struct Requester {
+        item: String,
+        /// Where `item` uses the previous item of the chain.
+        span: String,
+    }
+
+    #[derive(serde::Serialize)]
+    struct Instantiation {
+        name: String,
+        size_estimate: usize,
+        /// The users of the instantiation, up to the first non-generic one, which is
+        /// where the generic arguments come from.
+        requested_by: Vec<Requester>,
+    }
+
+    #[derive(serde::Serialize)]
+    struct GenericItem {
+        name: String,
+        instantiation_count: usize,
+        total_estimate: usize,
+        instantiations: Vec<Instantiation>,
+    }
+
+    let requester_chain = |item: MonoItem<'tcx>| {
+        let mut chain = Vec::new();
+        let mut seen = FxHashSet::default();
+        seen.insert(item);
+        let mut current = item;
+        while chain.len() < MAX_CHAIN_LEN {
+            let mut users = usage_map.get_user_items(current).iter().enumerate();
+            let Some((index, &user)) = users.find(|(_, user)| !seen.contains(*user)) else {
+                break;
+            };
+            seen.insert(user);
+            let span = usage_map
+                .get_user_spans(current)
+                .and_then(|spans| spans.get(index))
+                .map(|&span| tcx.sess.source_map().span_to_embeddable_string(span))
+                .unwrap_or_default();
+            chain.push(Requester { item: with_no_trimmed_paths!(user.to_string()), span });
+            if !is_generic(user) {
+                break;
+            }
+            current = user;
+        }
+        chain
+    };
+
+    // Gather the instantiations grouped by generic definition
+    let mut instantiations_per_def_id: FxHashMap<DefId, Vec<Instantiation>> = Default::default();
+    for &item in items {
+        // Avoid variable-sized compiler-generated shims
+        if !item.is_user_defined() || !is_generic(item) {
+            continue;
+        }
+        let instantiation = Instantiation {
+            name: with_no_trimmed_paths!(item.to_string()),
+            size_estimate: item.size_estimate(tcx),
+            requested_by: requester_chain(item),
+        };
+        let def_id = tcx.typeck_root_def_id(item.def_id());
+        instantiations_per_def_id.entry(def_id).or_default().push(instantiation);
+    }
+
+    // Output the definitions sorted by total instantiated size, from heaviest to lightest
+    let mut report: Vec<_> = instantiations_per_def_id
+        .into_iter()
+        .map(|(def_id, mut instantiations)| {
+            instantiations.sort_unstable_by(|a, b| {
+                b.size_estimate.cmp(&a.size_estimate).then_with(|| a.name.cmp(&b.name))
+            });
+            GenericItem {
+                name: with_no_trimmed_paths!(tcx.def_path_str(def_id)),
+                instantiation_count: instantiations.len(),
+                total_estimate: instantiations.iter().map(|i| i.size_estimate).sum(),
+                instantiations,
+            }
+        })
+        .collect();
+    report.sort_unstable_by(|a, b| {
+        b.total_estimate.cmp(&a.total_estimate).then_with(|| a.name.cmp(&b.name))
+    });
+
+    match format {
+        DumpMonoStatsFormat::Json => serde_json::to_writer(file, &report)?,
+        DumpMonoStatsFormat::Markdown => {
+            writeln!(file, "| Generic Item | Instantiation count | Total Estimated Cost |")?;
+            writeln!(file, "| --- | ---: | ---: |")?;
+            for GenericItem { name, instantiation_count, total_estimate, .. } in &report {
+                writeln!(file, "| `{name}` | {instantiation_count} | {total_estimate} |")?;
+            }
+
+            for GenericItem { name, instantiations, .. } in &report {
+                writeln!(file)?;
+                writeln!(file, "## `{name}`")?;
+                writeln!(file)?;
+                writeln!(file, "| Instantiation | Estimated Cost | Requested By |")?;
+                writeln!(file, "| --- | ---: | --- |")?;
+                for Instantiation { name, size_estimate, requested_by } in instantiations {
+                    let requested_by = requested_by
+                        .iter()
+                        .map(|Requester { item, span }| format!("`{item}` ({span})"))
+                        .collect::<Vec<_>>()
+                        .join(" ← ");
+                    writeln!(file, "| `{name}` | {size_estimate} | {requested_by} |")?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 fn codegened_and_inlined_items(tcx: TyCtxt<'_>, (): ()) -> &DefIdSet {
     let (items, cgus) = tcx.collect_and_partition_mono_items(());
     let mut visited = DefIdSet::default();
@@ -48,7 +48,7 @@
 pub use crate::plumbing::QueryCtxt;
 
 mod profiling_support;
-pub use self::profiling_support::alloc_self_profile_query_strings;
+pub use self::profiling_support::{alloc_self_profile_query_strings, print_item_times};
 
 struct DynamicConfig<
     'tcx,
@@ -672,6 +672,18 @@ pub fn alloc_self_profile_query_strings<'tcx>(tcx: TyCtxt<'tcx>, string_cache: &
                 )
             }
 
+            pub fn collect_item_times<'tcx>(
+                tcx: TyCtxt<'tcx>,
+                item_times: &mut $crate::profiling_support::ItemQueryTimes,
+            ) {
+                $crate::profiling_support::collect_item_times_for_query_cache(
+                    tcx,
+                    stringify!($name),
+                    &tcx.query_system.caches.$name,
+                    item_times,
+                )
+            }
+
             item_if_cached! { [$($modifiers)*] {
                 pub fn encode_query_results<'tcx>(
                     tcx: TyCtxt<'tcx>,
@@ -717,6 +729,10 @@ pub fn dynamic_queries<'tcx>() -> DynamicQueries<'tcx> {
             for<'tcx> fn(TyCtxt<'tcx>, &mut QueryKeyStringCache)
         ] = &[$(query_impl::$name::alloc_self_profile_query_strings),*];
 
+        const COLLECT_ITEM_TIMES: &[
+            for<'tcx> fn(TyCtxt<'tcx>, &mut $crate::profiling_support::ItemQueryTimes)
+        ] = &[$(query_impl::$name::collect_item_times),*];
+
         const ENCODE_QUERY_RESULTS: &[
             Option<for<'tcx> fn(
                 TyCtxt<'tcx>,
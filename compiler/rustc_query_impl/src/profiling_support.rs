@@ -1,12 +1,16 @@
 use measureme::{StringComponent, StringId};
-use rustc_data_structures::profiling::SelfProfiler;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_data_structures::profiling::{SelfProfiler, TimePassesFormat};
 use rustc_hir::def_id::{CrateNum, DefId, DefIndex, LocalDefId, LOCAL_CRATE};
 use rustc_hir::definitions::DefPathData;
 use rustc_middle::query::plumbing::QueryKeyStringCache;
+use rustc_middle::query::Key;
+use rustc_middle::ty::print::with_no_trimmed_paths;
 use rustc_middle::ty::TyCtxt;
 use rustc_query_system::query::QueryCache;
 use std::fmt::Debug;
 use std::io::Write;
+use std::time::Duration;
 
 struct QueryKeyStringBuilder<'p, 'tcx> {
     profiler: &'p SelfProfiler,
@@ -247,3 +251,110 @@ pub fn alloc_self_profile_query_strings(tcx: TyCtxt<'_>) {
         alloc(tcx, &mut string_cache)
     }
 }
+
+/// The time spent in the invocations of each query, by item.
+pub(crate) type ItemQueryTimes = FxHashMap<DefId, FxHashMap<&'static str, Duration>>;
+
+/// Attribute the time spent in the invocations of a single query to the items
+/// they were invoked for. This method is called from `print_item_times` which
+/// knows all the queries via macro magic.
+pub(crate) fn collect_item_times_for_query_cache<'tcx, C>(
+    tcx: TyCtxt<'tcx>,
+    query_name: &'static str,
+    query_cache: &C,
+    item_times: &mut ItemQueryTimes,
+) where
+    C: QueryCache,
+    C::Key: Key,
+{
+    let Some(times) = tcx.prof.item_times() else { return };
+
+    // Invocations whose key isn't an item, like `()` or a type, are left out.
+    query_cache.iter(&mut |key, _, dep_node_index| {
+        if let Some(def_id) = key.key_as_def_id() {
+            if let Some(time) = times.query_time(dep_node_index.into()) {
+                *item_times.entry(def_id).or_default().entry(query_name).or_default() += time;
+            }
+        }
+    });
+}
+
+/// The number of items printed by `-Z time-items` in the text format.
+const TEXT_ITEM_LIMIT: usize = 50;
+
+/// Prints the self time of the queries and other activities of each item for
+/// `-Z time-items`, starting with the items that took the longest.
+pub fn print_item_times(tcx: TyCtxt<'_>) {
+    let Some(times) = tcx.prof.item_times() else { return };
+
+    let mut query_times = ItemQueryTimes::default();
+    for collect in super::COLLECT_ITEM_TIMES.iter() {
+        collect(tcx, &mut query_times);
+    }
+
+    // Items are keyed by their path, so that the queries and the activities, which
+    // only know the path of their item, are grouped together.
+    let mut item_times: FxHashMap<String, FxHashMap<&'static str, Duration>> = Default::default();
+    for (def_id, queries) in query_times {
+        // The path of the crate root is empty.
+        let path = if def_id.is_crate_root() {
+            tcx.crate_name(def_id.krate).to_string()
+        } else {
+            with_no_trimmed_paths!(tcx.def_path_str(def_id))
+        };
+        let item = item_times.entry(path).or_default();
+        for (query, time) in queries {
+            *item.entry(query).or_default() += time;
+        }
+    }
+    for (activity, path, time) in times.activity_times() {
+        *item_times.entry(path).or_default().entry(activity).or_default() += time;
+    }
+
+    let mut items = item_times
+        .into_iter()
+        .map(|(path, times)| {
+            let mut times = times.into_iter().collect::<Vec<_>>();
+            times.sort_by(|(a, a_time), (b, b_time)| b_time.cmp(a_time).then(a.cmp(b)));
+            let total = times.iter().map(|&(_, time)| time).sum::<Duration>();
+            (path, total, times)
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|(a, a_total, _), (b, b_total, _)| b_total.cmp(a_total).then_with(|| a.cmp(b)));
+
+    match tcx.sess.opts.unstable_opts.time_items_format {
+        TimePassesFormat::Json => {
+            for (path, total, times) in &items {
+                let times = times
+                    .iter()
+                    .map(|(name, time)| (name.to_string(), time.as_secs_f64().into()))
+                    .collect::<serde_json::Map<_, _>>();
+                let entry = serde_json::json!({
+                    "item": path,
+                    "time": total.as_secs_f64(),
+                    "times": times,
+                });
+                eprintln!("time-items: {entry}");
+            }
+        }
+        TimePassesFormat::Text => {
+            let ms = |time: Duration| time.as_secs_f64() * 1000.0;
+            let total = items.iter().map(|&(_, total, _)| total).sum::<Duration>();
+            eprintln!("time-items: {:.3}ms spent on {} items", ms(total), items.len());
+            eprintln!("time-items: {:>12}  item / query or activity", "self time");
+            for (path, total, times) in items.iter().take(TEXT_ITEM_LIMIT) {
+                eprintln!("time-items: {:>10.3}ms  {path}", ms(*total));
+                for (name, time) in times {
+                    eprintln!("time-items: {:>10.3}ms      {name}", ms(*time));
+                }
+            }
+            if items.len() > TEXT_ITEM_LIMIT {
+                eprintln!(
+                    "time-items: ... and {} more items, \
+                     use `-Z time-items-format=json` to see all of them",
+                    items.len() - TEXT_ITEM_LIMIT
+                );
+            }
+        }
+    }
+}
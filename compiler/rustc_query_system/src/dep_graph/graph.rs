@@ -101,6 +101,10 @@ pub struct DepGraphData<K: DepKind> {
     /// a particular query result was decoded from disk
     /// (not just marked green)
     debug_loaded_from_disk: Lock<FxHashSet<DepNode<K>>>,
+
+    /// For each node that couldn't be marked green, the dependency that prevented it.
+    /// Only recorded with `-Zincremental-explain`.
+    invalidated_by: Option<Lock<FxHashMap<SerializedDepNodeIndex, SerializedDepNodeIndex>>>,
 }
 
 pub fn hash_result<R>(hcx: &mut StableHashingContext<'_>, result: &R) -> Fingerprint
@@ -120,6 +124,7 @@ pub fn new(
         encoder: FileEncoder,
         record_graph: bool,
         record_stats: bool,
+        record_invalidations: bool,
     ) -> DepGraph<K> {
         let prev_graph_node_count = prev_graph.node_count();
 
@@ -176,6 +181,7 @@ pub fn new(
                 previous: prev_graph,
                 colors,
                 debug_loaded_from_disk: Default::default(),
+                invalidated_by: record_invalidations.then(Default::default),
             })),
             virtual_dep_node_index: Lrc::new(AtomicU32::new(0)),
         }
@@ -873,7 +879,13 @@ fn try_mark_previous_green<Qcx: QueryContext<DepKind = K>>(
         let prev_deps = self.previous.edge_targets_from(prev_dep_node_index);
 
         for &dep_dep_node_index in prev_deps {
-            self.try_mark_parent_green(qcx, dep_dep_node_index, dep_node, Some(&frame))?;
+            if self.try_mark_parent_green(qcx, dep_dep_node_index, dep_node, Some(&frame)).is_none()
+            {
+                if let Some(invalidated_by) = &self.invalidated_by {
+                    invalidated_by.lock().insert(prev_dep_node_index, dep_dep_node_index);
+                }
+                return None;
+            }
         }
 
         // If we got here without hitting a `return` that means that all
@@ -994,6 +1006,74 @@ pub fn print_incremental_info(&self) {
         }
     }
 
+    /// Prints the chain of dependencies that prevented `dep_node` from being marked green,
+    /// starting with the node that changed. `what` describes `dep_node` to the user.
+    ///
+    /// This requires the invalidations to be recorded, see `DepGraph::new`.
+    pub fn print_invalidation(&self, what: &str, dep_node: &DepNode<K>) {
+        let Some(data) = &self.data else { return };
+        let Some(invalidated_by) = &data.invalidated_by else { return };
+        let Some(mut prev_index) = data.previous.node_to_index_opt(dep_node) else {
+            eprintln!("[incremental] {what} is new, it didn't exist in the previous session");
+            return;
+        };
+
+        let mut chain = vec![prev_index];
+        {
+            let invalidated_by = invalidated_by.lock();
+            while let Some(&dep_index) = invalidated_by.get(&prev_index) {
+                // Colors can be recomputed concurrently, so guard against cycles.
+                if chain.contains(&dep_index) {
+                    break;
+                }
+                chain.push(dep_index);
+                prev_index = dep_index;
+            }
+        }
+
+        eprintln!("[incremental] {what} was invalidated:");
+        let root = data.previous.index_to_node(prev_index);
+        if root.kind == K::RED {
+            eprintln!("[incremental]     state that isn't tracked by the dep-graph changed");
+        } else if data.colors.get(prev_index) == Some(DepNodeColor::Red) {
+            eprintln!("[incremental]     `{root:?}` changed");
+        } else {
+            // A node that couldn't be forced doesn't exist anymore, e.g. because
+            // the item it was computed for was removed.
+            eprintln!("[incremental]     `{root:?}` was removed");
+        }
+        for &prev_index in chain.iter().rev().skip(1) {
+            let node = data.previous.index_to_node(prev_index);
+            eprintln!("[incremental]     which invalidated `{node:?}`");
+        }
+    }
+
+    /// Prints the red nodes that weren't invalidated by one of their dependencies, which are
+    /// the inputs of the dep-graph that changed since the previous session.
+    pub fn print_changed_inputs(&self) {
+        let Some(data) = &self.data else { return };
+        let Some(invalidated_by) = &data.invalidated_by else { return };
+        let invalidated_by = invalidated_by.lock();
+
+        let mut changed = Vec::new();
+        for prev_index in data.colors.values.indices() {
+            if data.colors.get(prev_index) == Some(DepNodeColor::Red)
+                && !invalidated_by.contains_key(&prev_index)
+            {
+                let node = data.previous.index_to_node(prev_index);
+                if node.kind != K::RED {
+                    changed.push(format!("{node:?}"));
+                }
+            }
+        }
+        changed.sort();
+
+        eprintln!("[incremental] {} inputs changed since the previous session", changed.len());
+        for node in changed {
+            eprintln!("[incremental]     `{node}` changed");
+        }
+    }
+
     pub fn encode(&self, profiler: &SelfProfilerRef) -> FileEncodeResult {
         if let Some(data) = &self.data {
             data.current.encoder.steal().finish(profiler)
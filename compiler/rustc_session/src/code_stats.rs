@@ -1,8 +1,10 @@
+use crate::config::PrintTypeSizesFormat;
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::sync::Lock;
 use rustc_span::def_id::DefId;
 use rustc_span::Symbol;
 use rustc_target::abi::{Align, Size};
+use serde::Serialize;
 use std::cmp;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
@@ -14,7 +16,8 @@ pub struct VariantInfo {
     pub fields: Vec<FieldInfo>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SizeKind {
     Exact,
     Min,
@@ -46,7 +49,8 @@ pub struct FieldInfo {
     pub align: u64,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DataTypeKind {
     Struct,
     Union,
@@ -55,6 +59,16 @@ pub enum DataTypeKind {
     Generator,
 }
 
+/// The largest niche of a type: a range of invalid values of one of its fields, which
+/// enums containing the type can use to encode their discriminant.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize)]
+pub struct NicheInfo {
+    pub offset: u64,
+    pub size: u64,
+    /// Number of invalid values, i.e. of discriminant values that can be stored in the niche.
+    pub available: u128,
+}
+
 #[derive(PartialEq, Eq, Hash, Debug)]
 pub struct TypeSizeInfo {
     pub kind: DataTypeKind,
@@ -63,9 +77,48 @@ pub struct TypeSizeInfo {
     pub overall_size: u64,
     pub packed: bool,
     pub opt_discr_size: Option<u64>,
+    pub niche: Option<NicheInfo>,
     pub variants: Vec<VariantInfo>,
 }
 
+/// The JSON representation of a `TypeSizeInfo`, as printed by
+/// `-Zprint-type-sizes -Zprint-type-sizes-format=json`.
+#[derive(Serialize)]
+struct TypeSizeJson<'a> {
+    #[serde(rename = "type")]
+    type_description: &'a str,
+    kind: DataTypeKind,
+    size: u64,
+    align: u64,
+    packed: bool,
+    discriminant_size: Option<u64>,
+    niche: Option<NicheInfo>,
+    variants: Vec<VariantJson>,
+    end_padding: u64,
+}
+
+#[derive(Serialize)]
+struct VariantJson {
+    name: Option<String>,
+    size_kind: SizeKind,
+    /// Size of the variant, including the discriminant.
+    size: u64,
+    align: u64,
+    /// The fields, by increasing offset.
+    fields: Vec<FieldJson>,
+}
+
+#[derive(Serialize)]
+struct FieldJson {
+    kind: String,
+    name: String,
+    offset: u64,
+    size: u64,
+    align: u64,
+    /// Padding between the end of the previous field, or the discriminant, and this field.
+    padding_before: u64,
+}
+
 pub struct VTableSizeInfo {
     pub trait_name: String,
 
@@ -100,6 +153,7 @@ pub fn record_type_size<S: ToString>(
         overall_size: Size,
         packed: bool,
         opt_discr_size: Option<Size>,
+        niche: Option<NicheInfo>,
         mut variants: Vec<VariantInfo>,
     ) {
         // Sort variants so the largest ones are shown first. A stable sort is
@@ -117,6 +171,7 @@ pub fn record_type_size<S: ToString>(
             overall_size: overall_size.bytes(),
             packed,
             opt_discr_size: opt_discr_size.map(|s| s.bytes()),
+            niche,
             variants,
         };
         self.type_sizes.borrow_mut().insert(info);
@@ -130,7 +185,7 @@ pub fn record_vtable_size(&self, trait_did: DefId, trait_name: &str, info: VTabl
         );
     }
 
-    pub fn print_type_sizes(&self) {
+    pub fn print_type_sizes(&self, format: PrintTypeSizesFormat) {
         let type_sizes = self.type_sizes.borrow();
         let mut sorted: Vec<_> = type_sizes.iter().collect();
 
@@ -138,6 +193,10 @@ pub fn print_type_sizes(&self) {
         // Secondary sort: description (dictionary order)
         sorted.sort_by_key(|info| (cmp::Reverse(info.overall_size), &info.type_description));
 
+        if format == PrintTypeSizesFormat::Json {
+            return self.print_type_sizes_json(&sorted);
+        }
+
         for info in sorted {
             let TypeSizeInfo { type_description, overall_size, align, kind, variants, .. } = info;
             println!(
@@ -226,6 +285,64 @@ pub fn print_type_sizes(&self) {
         }
     }
 
+    /// Prints one JSON object per type and line, so that the output of two builds can be
+    /// compared with a line-based diff.
+    fn print_type_sizes_json(&self, sorted: &[&TypeSizeInfo]) {
+        for info in sorted {
+            // Padding is computed the same way as for the text output.
+            let discr_size = info.opt_discr_size.unwrap_or(0);
+            let mut max_variant_size = discr_size;
+            let variants = info
+                .variants
+                .iter()
+                .map(|variant| {
+                    max_variant_size = cmp::max(max_variant_size, variant.size);
+
+                    let mut min_offset = discr_size;
+                    let mut fields = variant.fields.clone();
+                    fields.sort_by_key(|f| (f.offset, f.size));
+                    let fields = fields
+                        .into_iter()
+                        .map(|FieldInfo { kind, name, offset, size, align }| {
+                            // Fields of unions overlap, and have no padding between them.
+                            let padding_before = offset.saturating_sub(min_offset);
+                            min_offset = offset + size;
+                            FieldJson {
+                                kind: kind.to_string(),
+                                name: name.to_string(),
+                                offset,
+                                size,
+                                align,
+                                padding_before,
+                            }
+                        })
+                        .collect();
+
+                    VariantJson {
+                        name: variant.name.map(|name| name.to_string()),
+                        size_kind: variant.kind,
+                        size: variant.size,
+                        align: variant.align,
+                        fields,
+                    }
+                })
+                .collect();
+
+            let json = TypeSizeJson {
+                type_description: &info.type_description,
+                kind: info.kind,
+                size: info.overall_size,
+                align: info.align,
+                packed: info.packed,
+                discriminant_size: info.opt_discr_size,
+                niche: info.niche,
+                variants,
+                end_padding: info.overall_size - max_variant_size,
+            };
+            println!("{}", serde_json::to_string(&json).unwrap());
+        }
+    }
+
     pub fn print_vtable_sizes(&self, crate_name: &str) {
         let mut infos =
             std::mem::take(&mut *self.vtable_sizes.lock()).into_values().collect::<Vec<_>>();
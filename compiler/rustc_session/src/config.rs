@@ -530,6 +530,8 @@ pub enum ErrorOutputType {
         /// human output.
         json_rendered: HumanReadableErrorType,
     },
+    /// A SARIF log, which is consumed by code scanning and code review tools.
+    Sarif,
 }
 
 impl Default for ErrorOutputType {
@@ -739,6 +741,7 @@ pub enum PrintKind {
     LinkArgs,
     SplitDebuginfo,
     DeploymentTarget,
+    LoadedCrates,
 }
 
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
@@ -1102,6 +1105,7 @@ pub fn diagnostic_handler_flags(&self, can_emit_warnings: bool) -> HandlerFlags
             macro_backtrace: self.macro_backtrace,
             deduplicate_diagnostics: self.deduplicate_diagnostics,
             track_diagnostics: self.track_diagnostics,
+            group_diagnostics: self.group_diagnostics,
         }
     }
 }
@@ -1161,6 +1165,13 @@ pub fn extend(&mut self, passes: impl IntoIterator<Item = String>) {
             Passes::All => {}
         }
     }
+
+    pub fn contains(&self, pass: &str) -> bool {
+        match *self {
+            Passes::Some(ref v) => v.iter().any(|p| p == pass),
+            Passes::All => true,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Hash, Debug, PartialEq)]
@@ -1715,7 +1726,7 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
             "",
             "explain",
             "Provide a detailed explanation of an error \
-             message",
+             message or a lint",
             "OPT",
         ),
         opt::flag_s("", "test", "Build a test harness"),
@@ -1930,6 +1941,7 @@ pub fn parse_error_format(
             }
             Some("json") => ErrorOutputType::Json { pretty: false, json_rendered },
             Some("pretty-json") => ErrorOutputType::Json { pretty: true, json_rendered },
+            Some("sarif") => ErrorOutputType::Sarif,
             Some("short") => ErrorOutputType::HumanReadable(HumanReadableErrorType::Short(color)),
 
             Some(arg) => {
@@ -1937,7 +1949,7 @@ pub fn parse_error_format(
                     HumanReadableErrorType::Default(color),
                 ));
                 handler.early_error(format!(
-                    "argument for `--error-format` must be `human`, `json` or \
+                    "argument for `--error-format` must be `human`, `json`, `sarif` or \
                      `short` (instead was `{arg}`)"
                 ))
             }
@@ -2011,6 +2023,13 @@ fn check_error_format_stability(
             });
             handler.early_error("`--error-format=human-annotate-rs` is unstable");
         }
+        if let ErrorOutputType::Sarif = error_format {
+            handler.abort_if_error_and_set_error_format(ErrorOutputType::Json {
+                pretty: false,
+                json_rendered,
+            });
+            handler.early_error("`--error-format=sarif` is unstable");
+        }
     }
 }
 
@@ -2139,6 +2158,7 @@ fn collect_print_requests(
         ("link-args", PrintKind::LinkArgs),
         ("split-debuginfo", PrintKind::SplitDebuginfo),
         ("deployment-target", PrintKind::DeploymentTarget),
+        ("loaded-crates", PrintKind::LoadedCrates),
     ];
 
     // We disallow reusing the same path in multiple prints, such as `--print
@@ -2177,6 +2197,16 @@ fn collect_print_requests(
                     );
                 }
             }
+            Some((_, PrintKind::LoadedCrates)) => {
+                if unstable_opts.unstable_options {
+                    PrintKind::LoadedCrates
+                } else {
+                    handler.early_error(
+                        "the `-Z unstable-options` flag must also be passed to \
+                         enable the loaded-crates print option",
+                    );
+                }
+            }
             Some(&(_, print_kind)) => print_kind,
             None => {
                 let prints =
@@ -2201,6 +2231,20 @@ fn collect_print_requests(
         PrintRequest { kind, out }
     }));
 
+    // The loaded crates are only known after name resolution, but the other print requests
+    // (except the ones printed during linking) stop the compilation before that.
+    let is_printed_late = |kind: PrintKind| {
+        matches!(kind, PrintKind::LoadedCrates | PrintKind::NativeStaticLibs | PrintKind::LinkArgs)
+    };
+    if prints.iter().any(|p| p.kind == PrintKind::LoadedCrates)
+        && !prints.iter().all(|p| is_printed_late(p.kind))
+    {
+        handler.early_error(
+            "`--print loaded-crates` cannot be combined with other print requests, \
+             except `native-static-libs` and `link-args`",
+        );
+    }
+
     prints
 }
 
@@ -2877,8 +2921,14 @@ fn parse_pretty(handler: &EarlyErrorHandler, unstable_opts: &UnstableOptions) ->
         "hir-tree" => HirTree,
         "thir-tree" => ThirTree,
         "thir-flat" => ThirFlat,
-        "mir" => Mir,
-        "mir-cfg" => MirCFG,
+        "mir" => Mir(PpMirFilter::default()),
+        "mir-cfg" => MirCFG(PpMirFilter::default()),
+        name if let Some(filter) = name.strip_prefix("mir=") => {
+            Mir(parse_pretty_mir_filter(handler, filter))
+        }
+        name if let Some(filter) = name.strip_prefix("mir-cfg=") => {
+            MirCFG(parse_pretty_mir_filter(handler, filter))
+        }
         name => handler.early_error(format!(
             "argument to `unpretty` must be one of `normal`, `identified`, \
                             `expanded`, `expanded,identified`, `expanded,hygiene`, \
@@ -2891,6 +2941,23 @@ fn parse_pretty(handler: &EarlyErrorHandler, unstable_opts: &UnstableOptions) ->
     Some(first)
 }
 
+/// Parses the `ITEM@after:PASS` part of `-Zunpretty=mir=ITEM@after:PASS`, where both the item
+/// and the pass are optional.
+fn parse_pretty_mir_filter(handler: &EarlyErrorHandler, filter: &str) -> PpMirFilter {
+    let (item, pass) = match filter.split_once('@') {
+        Some((item, pass)) => (item, Some(pass)),
+        None => (filter, None),
+    };
+    let after_pass = pass.map(|pass| match pass.strip_prefix("after:") {
+        Some(pass) if !pass.is_empty() => pass.to_string(),
+        _ => handler.early_error(format!(
+            "the pass of `-Zunpretty=mir` must be given as `@after:PASS`; got `@{pass}`"
+        )),
+    });
+    let item = if item.is_empty() { None } else { Some(item.to_string()) };
+    PpMirFilter { item, after_pass }
+}
+
 pub fn make_crate_type_option() -> RustcOptGroup {
     opt::multi_s(
         "",
@@ -3037,7 +3104,30 @@ pub enum PpHirMode {
     Typed,
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+/// Restricts what `-Zunpretty=mir` and `-Zunpretty=mir-cfg` print, e.g.
+/// `-Zunpretty=mir=my_mod::hot_fn@after:ConstProp`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct PpMirFilter {
+    /// Only print the bodies of the items whose path is or ends with this path.
+    pub item: Option<String>,
+    /// Print the MIR as it is right after this pass, instead of the final MIR.
+    pub after_pass: Option<String>,
+}
+
+impl PpMirFilter {
+    /// Whether the item with the path `def_path` is printed.
+    pub fn matches_item(&self, def_path: &str) -> bool {
+        match &self.item {
+            Some(item) => {
+                def_path == item
+                    || def_path.strip_suffix(item.as_str()).is_some_and(|p| p.ends_with("::"))
+            }
+            None => true,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 pub enum PpMode {
     /// Options that print the source code, i.e.
     /// `-Zunpretty=normal` and `-Zunpretty=expanded`
@@ -3052,9 +3142,9 @@ pub enum PpMode {
     /// `-Zunpretty=thir-flat`
     ThirFlat,
     /// `-Zunpretty=mir`
-    Mir,
+    Mir(PpMirFilter),
     /// `-Zunpretty=mir-cfg`
-    MirCFG,
+    MirCFG(PpMirFilter),
 }
 
 impl PpMode {
@@ -3070,8 +3160,8 @@ pub fn needs_ast_map(&self) -> bool {
             | HirTree
             | ThirTree
             | ThirFlat
-            | Mir
-            | MirCFG => true,
+            | Mir(_)
+            | MirCFG(_) => true,
         }
     }
     pub fn needs_hir(&self) -> bool {
@@ -3079,13 +3169,13 @@ pub fn needs_hir(&self) -> bool {
         match *self {
             Source(_) | AstTree(_) => false,
 
-            Hir(_) | HirTree | ThirTree | ThirFlat | Mir | MirCFG => true,
+            Hir(_) | HirTree | ThirTree | ThirFlat | Mir(_) | MirCFG(_) => true,
         }
     }
 
     pub fn needs_analysis(&self) -> bool {
         use PpMode::*;
-        matches!(*self, Mir | MirCFG | ThirTree | ThirFlat)
+        matches!(*self, Mir(_) | MirCFG(_) | ThirTree | ThirFlat)
     }
 }
 
@@ -3338,6 +3428,15 @@ pub enum ProcMacroExecutionStrategy {
     CrossThread,
 }
 
+/// Which format to use for `-Z print-type-sizes`
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum PrintTypeSizesFormat {
+    /// Print human-readable `print-type-size` lines
+    Text,
+    /// Emit one JSON object per type
+    Json,
+}
+
 /// Which format to use for `-Z dump-mono-stats`
 #[derive(Clone, Copy, PartialEq, Hash, Debug)]
 pub enum DumpMonoStatsFormat {
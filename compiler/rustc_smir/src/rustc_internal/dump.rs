@@ -0,0 +1,113 @@
+//! Serialization of the Stable MIR of the local crate.
+//!
+//! This allows external tools to analyze a crate offline, without linking against rustc.
+
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::def_id::LOCAL_CRATE;
+use rustc_span::{Pos, Span};
+use serde::Serialize;
+
+use super::{run, with_tables};
+use crate::rustc_smir::{Stable, Tables};
+use crate::stable_mir::mir::{Body, CodeRegion};
+use crate::stable_mir::ty::TyKind;
+use crate::stable_mir::CrateItem;
+
+/// The version of the snapshot format.
+/// This must be bumped whenever the structure of the serialized data changes.
+pub const SMIR_SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct Snapshot {
+    version: u32,
+    crate_name: String,
+    items: Vec<Item>,
+    /// The kind of every type, indexed by `Ty`.
+    types: Vec<TyKind>,
+    /// The path of every definition, indexed by `DefId`.
+    defs: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Item {
+    item: CrateItem,
+    name: String,
+    kind: String,
+    span: CodeRegion,
+    body: Body,
+}
+
+/// Writes the Stable MIR of all local items that have a body to `<crate>.smir.json`
+/// in the given directory, or in the current directory if there is none.
+pub fn dump_smir(
+    tcx: TyCtxt<'_>,
+    output_directory: &Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_directory = if let Some(ref directory) = output_directory {
+        fs::create_dir_all(directory)?;
+        directory
+    } else {
+        Path::new(".")
+    };
+    let crate_name = tcx.crate_name(LOCAL_CRATE);
+    let output_path = output_directory.join(format!("{crate_name}.smir.json"));
+
+    let mut snapshot = None;
+    run(tcx, || snapshot = Some(with_tables(|tables| build_snapshot(tables))));
+
+    let mut file = BufWriter::new(File::create(&output_path)?);
+    serde_json::to_writer(&mut file, &snapshot.unwrap())?;
+    file.flush()?;
+    Ok(())
+}
+
+fn build_snapshot(tables: &mut Tables<'_>) -> Snapshot {
+    let tcx = tables.tcx;
+    let items = tcx
+        .mir_keys(())
+        .iter()
+        .map(|def_id| {
+            let def_id = def_id.to_def_id();
+            // Constants only have MIR for CTFE, which `instance_mir` takes care of.
+            let body = tcx.instance_mir(ty::InstanceDef::Item(def_id)).stable(tables);
+            Item {
+                item: tables.crate_item(def_id),
+                name: tcx.def_path_str(def_id),
+                kind: tcx.def_descr(def_id).to_string(),
+                span: code_region(tcx, tcx.def_span(def_id)),
+                body,
+            }
+        })
+        .collect();
+
+    // Converting a type may refer to new types, so keep going until all of them are known.
+    let mut types = Vec::new();
+    while let Some(&ty) = tables.types.get(types.len()) {
+        types.push(ty.stable(tables));
+    }
+
+    Snapshot {
+        version: SMIR_SNAPSHOT_VERSION,
+        crate_name: tcx.crate_name(LOCAL_CRATE).to_string(),
+        items,
+        types,
+        defs: tables.def_ids.iter().map(|&def_id| tcx.def_path_str(def_id)).collect(),
+    }
+}
+
+fn code_region(tcx: TyCtxt<'_>, span: Span) -> CodeRegion {
+    let source_map = tcx.sess.source_map();
+    let lo = source_map.lookup_char_pos(span.lo());
+    let hi = source_map.lookup_char_pos(span.hi());
+    CodeRegion {
+        file_name: source_map.span_to_filename(span).prefer_local().to_string(),
+        start_line: lo.line,
+        start_col: lo.col.to_usize() + 1,
+        end_line: hi.line,
+        end_col: hi.col.to_usize() + 1,
+    }
+}
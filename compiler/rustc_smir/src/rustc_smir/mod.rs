@@ -12,11 +12,14 @@
 use crate::stable_mir::ty::{FloatTy, IntTy, Movability, RigidTy, TyKind, UintTy};
 use crate::stable_mir::{self, Context};
 use rustc_hir as hir;
+use rustc_hir::def::DefKind;
 use rustc_middle::mir::coverage::CodeRegion;
+use rustc_middle::mir::mono::MonoItem;
 use rustc_middle::mir::{self};
-use rustc_middle::ty::{self, Ty, TyCtxt, Variance};
+use rustc_middle::ty::{self, Instance, Ty, TyCtxt, Variance};
 use rustc_span::def_id::{CrateNum, DefId, LOCAL_CRATE};
-use rustc_target::abi::FieldIdx;
+use rustc_target::abi::{self, FieldIdx};
+use std::iter;
 use tracing::debug;
 
 impl<'tcx> Context for Tables<'tcx> {
@@ -43,22 +46,7 @@ fn entry_fn(&mut self) -> Option<stable_mir::CrateItem> {
     }
     fn mir_body(&mut self, item: &stable_mir::CrateItem) -> stable_mir::mir::Body {
         let def_id = self.item_def_id(item);
-        let mir = self.tcx.optimized_mir(def_id);
-        stable_mir::mir::Body {
-            blocks: mir
-                .basic_blocks
-                .iter()
-                .map(|block| stable_mir::mir::BasicBlock {
-                    terminator: block.terminator().stable(self),
-                    statements: block
-                        .statements
-                        .iter()
-                        .map(|statement| statement.stable(self))
-                        .collect(),
-                })
-                .collect(),
-            locals: mir.local_decls.iter().map(|decl| self.intern_ty(decl.ty)).collect(),
-        }
+        self.tcx.optimized_mir(def_id).stable(self)
     }
 
     fn rustc_tables(&mut self, f: &mut dyn FnMut(&mut Tables<'_>)) {
@@ -69,12 +57,150 @@ fn ty_kind(&mut self, ty: crate::stable_mir::ty::Ty) -> TyKind {
         let ty = self.types[ty.0];
         ty.stable(self)
     }
+
+    fn ty_layout(&mut self, ty: stable_mir::ty::Ty) -> Option<stable_mir::ty::Layout> {
+        let ty = self.types[ty.0];
+        let layout = self.tcx.layout_of(ty::ParamEnv::reveal_all().and(ty)).ok()?;
+        Some(layout.layout.0.0.stable(self))
+    }
+
+    fn adt_kind(&mut self, def: &stable_mir::ty::AdtDef) -> stable_mir::ty::AdtKind {
+        let def_id = self.def_ids[def.0];
+        self.tcx.adt_def(def_id).adt_kind().stable(self)
+    }
+
+    fn adt_variants(&mut self, def: &stable_mir::ty::AdtDef) -> Vec<stable_mir::ty::VariantDef> {
+        let adt = self.tcx.adt_def(self.def_ids[def.0]);
+        adt.variants()
+            .iter_enumerated()
+            .map(|(idx, variant)| stable_mir::ty::VariantDef {
+                name: variant.name.to_string(),
+                discr: adt.is_enum().then(|| adt.discriminant_for_variant(self.tcx, idx).val),
+                fields: variant.fields.iter().map(|field| field.stable(self)).collect(),
+            })
+            .collect()
+    }
+
+    fn generics_of(&mut self, def_id: stable_mir::DefId) -> stable_mir::ty::Generics {
+        let def_id = self.def_ids[def_id];
+        let generics = self.tcx.generics_of(def_id);
+        generics.stable(self)
+    }
+
+    fn all_trait_impls(&mut self) -> stable_mir::ImplTraitDecls {
+        let tcx = self.tcx;
+        iter::once(LOCAL_CRATE)
+            .chain(tcx.crates(()).iter().copied())
+            .flat_map(|crate_num| tcx.trait_impls_in_crate(crate_num).iter())
+            .map(|impl_def_id| self.impl_def(*impl_def_id))
+            .collect()
+    }
+
+    fn trait_impl(&mut self, impl_def: &stable_mir::ty::ImplDef) -> stable_mir::ty::ImplTrait {
+        let def_id = self.def_ids[impl_def.0];
+        let impl_trait = self.tcx.impl_trait_ref(def_id).unwrap();
+        impl_trait.stable(self)
+    }
+
+    fn mono_items(&mut self) -> Vec<stable_mir::mir::mono::MonoItem> {
+        let (_, units) = self.tcx.collect_and_partition_mono_items(());
+        let mut items: Vec<_> = units.iter().flat_map(|unit| unit.items().keys()).collect();
+        // The same item may be shared between codegen units.
+        items.sort_by_cached_key(|item| item.to_string());
+        items.dedup();
+        items.into_iter().map(|item| item.stable(self)).collect()
+    }
+
+    fn instance_body(
+        &mut self,
+        def: stable_mir::mir::mono::InstanceDef,
+    ) -> Option<stable_mir::mir::Body> {
+        let instance = self.instances[def.0];
+        let has_body = match instance.def {
+            ty::InstanceDef::Intrinsic(..) | ty::InstanceDef::Virtual(..) => false,
+            ty::InstanceDef::Item(def_id) => self.tcx.is_mir_available(def_id),
+            _ => true,
+        };
+        if !has_body {
+            return None;
+        }
+        let mir = instance.subst_mir_and_normalize_erasing_regions(
+            self.tcx,
+            ty::ParamEnv::reveal_all(),
+            ty::EarlyBinder::bind(self.tcx.instance_mir(instance.def).clone()),
+        );
+        Some(mir.stable(self))
+    }
+
+    fn instance_ty(&mut self, def: stable_mir::mir::mono::InstanceDef) -> stable_mir::ty::Ty {
+        let instance = self.instances[def.0];
+        let ty = instance.ty(self.tcx, ty::ParamEnv::reveal_all());
+        self.intern_ty(ty)
+    }
+
+    fn instance_name(&mut self, def: stable_mir::mir::mono::InstanceDef) -> String {
+        self.instances[def.0].to_string()
+    }
+
+    fn instance_mangled_name(&mut self, def: stable_mir::mir::mono::InstanceDef) -> String {
+        let instance = self.instances[def.0];
+        self.tcx.symbol_name(instance).name.to_string()
+    }
+
+    fn mono_instance(
+        &mut self,
+        item: &stable_mir::CrateItem,
+    ) -> Option<stable_mir::mir::mono::Instance> {
+        let def_id = self.item_def_id(item);
+        // Constants and statics are evaluated, not called, so they have no instance.
+        if !matches!(self.tcx.def_kind(def_id), DefKind::Fn | DefKind::AssocFn)
+            || self.tcx.generics_of(def_id).requires_monomorphization(self.tcx)
+        {
+            return None;
+        }
+        Some(Instance::mono(self.tcx, def_id).stable(self))
+    }
+
+    fn resolve_instance(
+        &mut self,
+        fn_ty: stable_mir::ty::Ty,
+    ) -> Option<stable_mir::mir::mono::Instance> {
+        let ty::FnDef(def_id, args) = *self.types[fn_ty.0].kind() else {
+            return None;
+        };
+        let instance =
+            Instance::resolve(self.tcx, ty::ParamEnv::reveal_all(), def_id, args).ok()??;
+        Some(instance.stable(self))
+    }
+
+    fn resolve_drop_in_place(&mut self, ty: stable_mir::ty::Ty) -> stable_mir::mir::mono::Instance {
+        let ty = self.types[ty.0];
+        Instance::resolve_drop_in_place(self.tcx, ty).stable(self)
+    }
+
+    fn resolve_closure(
+        &mut self,
+        closure_ty: stable_mir::ty::Ty,
+        kind: stable_mir::ty::ClosureKind,
+    ) -> Option<stable_mir::mir::mono::Instance> {
+        let ty::Closure(def_id, args) = *self.types[closure_ty.0].kind() else {
+            return None;
+        };
+        let kind = match kind {
+            stable_mir::ty::ClosureKind::Fn => ty::ClosureKind::Fn,
+            stable_mir::ty::ClosureKind::FnMut => ty::ClosureKind::FnMut,
+            stable_mir::ty::ClosureKind::FnOnce => ty::ClosureKind::FnOnce,
+        };
+        let instance = Instance::resolve_closure(self.tcx, def_id, args, kind)?;
+        Some(instance.stable(self))
+    }
 }
 
 pub struct Tables<'tcx> {
     pub tcx: TyCtxt<'tcx>,
     pub def_ids: Vec<DefId>,
     pub types: Vec<Ty<'tcx>>,
+    pub instances: Vec<Instance<'tcx>>,
 }
 
 impl<'tcx> Tables<'tcx> {
@@ -86,6 +212,15 @@ fn intern_ty(&mut self, ty: Ty<'tcx>) -> stable_mir::ty::Ty {
         self.types.push(ty);
         stable_mir::ty::Ty(id)
     }
+
+    fn intern_instance(&mut self, instance: Instance<'tcx>) -> stable_mir::mir::mono::InstanceDef {
+        if let Some(id) = self.instances.iter().position(|&i| i == instance) {
+            return stable_mir::mir::mono::InstanceDef(id);
+        }
+        let id = self.instances.len();
+        self.instances.push(instance);
+        stable_mir::mir::mono::InstanceDef(id)
+    }
 }
 
 /// Build a stable mir crate from a given crate number.
@@ -104,6 +239,60 @@ pub(crate) trait Stable<'tcx> {
     fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T;
 }
 
+impl<'tcx> Stable<'tcx> for mir::Body<'tcx> {
+    type T = stable_mir::mir::Body;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        stable_mir::mir::Body {
+            blocks: self
+                .basic_blocks
+                .iter()
+                .map(|block| stable_mir::mir::BasicBlock {
+                    terminator: block.terminator().stable(tables),
+                    statements: block
+                        .statements
+                        .iter()
+                        .map(|statement| statement.stable(tables))
+                        .collect(),
+                })
+                .collect(),
+            locals: self.local_decls.iter().map(|decl| tables.intern_ty(decl.ty)).collect(),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for Instance<'tcx> {
+    type T = stable_mir::mir::mono::Instance;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::mir::mono::InstanceKind;
+        let kind = match self.def {
+            ty::InstanceDef::Item(..) => InstanceKind::Item,
+            ty::InstanceDef::Intrinsic(..) => InstanceKind::Intrinsic,
+            ty::InstanceDef::Virtual(_, idx) => InstanceKind::Virtual { idx },
+            ty::InstanceDef::VTableShim(..)
+            | ty::InstanceDef::ReifyShim(..)
+            | ty::InstanceDef::FnPtrShim(..)
+            | ty::InstanceDef::ClosureOnceShim { .. }
+            | ty::InstanceDef::ThreadLocalShim(..)
+            | ty::InstanceDef::DropGlue(..)
+            | ty::InstanceDef::CloneShim(..)
+            | ty::InstanceDef::FnPtrAddrShim(..) => InstanceKind::Shim,
+        };
+        stable_mir::mir::mono::Instance { kind, def: tables.intern_instance(*self) }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for MonoItem<'tcx> {
+    type T = stable_mir::mir::mono::MonoItem;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::mir::mono::MonoItem as StableMonoItem;
+        match self {
+            MonoItem::Fn(instance) => StableMonoItem::Fn(instance.stable(tables)),
+            MonoItem::Static(def_id) => StableMonoItem::Static(tables.crate_item(*def_id)),
+            MonoItem::GlobalAsm(item_id) => StableMonoItem::GlobalAsm(opaque(item_id)),
+        }
+    }
+}
+
 impl<'tcx> Stable<'tcx> for mir::Statement<'tcx> {
     type T = stable_mir::mir::Statement;
     fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
@@ -417,7 +606,10 @@ fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
         match self {
             Copy(place) => stable_mir::mir::Operand::Copy(place.stable(tables)),
             Move(place) => stable_mir::mir::Operand::Move(place.stable(tables)),
-            Constant(c) => stable_mir::mir::Operand::Constant(c.to_string()),
+            Constant(c) => stable_mir::mir::Operand::Constant(stable_mir::mir::Constant {
+                literal: c.to_string(),
+                ty: tables.intern_ty(c.ty()),
+            }),
         }
     }
 }
@@ -1063,3 +1255,251 @@ fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
         BoundTy { var: self.var.as_usize(), kind: self.kind.stable(tables) }
     }
 }
+
+impl<'tcx> Stable<'tcx> for ty::AdtKind {
+    type T = stable_mir::ty::AdtKind;
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        match self {
+            ty::AdtKind::Struct => stable_mir::ty::AdtKind::Struct,
+            ty::AdtKind::Union => stable_mir::ty::AdtKind::Union,
+            ty::AdtKind::Enum => stable_mir::ty::AdtKind::Enum,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::FieldDef {
+    type T = stable_mir::ty::FieldDef;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        stable_mir::ty::FieldDef {
+            def: tables.field_def(self.did),
+            name: self.name.to_string(),
+            ty: tables.intern_ty(tables.tcx.type_of(self.did).instantiate_identity()),
+        }
+    }
+}
+
+impl<'tcx, S, V> Stable<'tcx> for ty::EarlyBinder<S>
+where
+    S: Stable<'tcx, T = V>,
+{
+    type T = stable_mir::ty::EarlyBinder<V>;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::EarlyBinder;
+
+        EarlyBinder { value: self.as_ref().skip_binder().stable(tables) }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::TraitRef<'tcx> {
+    type T = stable_mir::ty::TraitRef;
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::TraitRef;
+
+        TraitRef { def_id: tables.trait_def(self.def_id), args: self.args.stable(tables) }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::Generics {
+    type T = stable_mir::ty::Generics;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::Generics;
+
+        Generics {
+            parent: self.parent.map(|did| tables.generic_def(did)),
+            parent_count: self.parent_count,
+            params: self.params.iter().map(|param| param.stable(tables)).collect(),
+            has_self: self.has_self,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::GenericParamDefKind {
+    type T = stable_mir::ty::GenericParamDefKind;
+
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::GenericParamDefKind;
+        match *self {
+            ty::GenericParamDefKind::Lifetime => GenericParamDefKind::Lifetime,
+            ty::GenericParamDefKind::Type { has_default, synthetic } => {
+                GenericParamDefKind::Type { has_default, synthetic }
+            }
+            ty::GenericParamDefKind::Const { has_default } => {
+                GenericParamDefKind::Const { has_default }
+            }
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for ty::GenericParamDef {
+    type T = stable_mir::ty::GenericParamDef;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        stable_mir::ty::GenericParamDef {
+            name: self.name.to_string(),
+            def_id: tables.generic_def(self.def_id),
+            index: self.index,
+            pure_wrt_drop: self.pure_wrt_drop,
+            kind: self.kind.stable(tables),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::LayoutS {
+    type T = stable_mir::ty::Layout;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        stable_mir::ty::Layout {
+            size: self.size.bytes(),
+            align: self.align.abi.bytes(),
+            fields: self.fields.stable(tables),
+            variants: self.variants.stable(tables),
+            abi: self.abi.stable(tables),
+            largest_niche: self.largest_niche.map(|niche| niche.stable(tables)),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::FieldsShape {
+    type T = stable_mir::ty::FieldsShape;
+
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::FieldsShape;
+        match self {
+            abi::FieldsShape::Primitive => FieldsShape::Primitive,
+            abi::FieldsShape::Union(count) => FieldsShape::Union(count.get()),
+            abi::FieldsShape::Array { stride, count } => {
+                FieldsShape::Array { stride: stride.bytes(), count: *count }
+            }
+            abi::FieldsShape::Arbitrary { offsets, .. } => FieldsShape::Arbitrary {
+                offsets: offsets.iter().map(|offset| offset.bytes()).collect(),
+            },
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::Variants {
+    type T = stable_mir::ty::VariantsShape;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::VariantsShape;
+        match self {
+            abi::Variants::Single { index } => {
+                VariantsShape::Single { index: index.stable(tables) }
+            }
+            abi::Variants::Multiple { tag, tag_encoding, tag_field, variants } => {
+                VariantsShape::Multiple {
+                    tag: tag.stable(tables),
+                    tag_encoding: tag_encoding.stable(tables),
+                    tag_field: *tag_field,
+                    variants: variants.iter().map(|variant| variant.stable(tables)).collect(),
+                }
+            }
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::TagEncoding {
+    type T = stable_mir::ty::TagEncoding;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::TagEncoding;
+        match self {
+            abi::TagEncoding::Direct => TagEncoding::Direct,
+            abi::TagEncoding::Niche { untagged_variant, niche_variants, niche_start } => {
+                TagEncoding::Niche {
+                    untagged_variant: untagged_variant.stable(tables),
+                    niche_variants: niche_variants.start().stable(tables)
+                        ..=niche_variants.end().stable(tables),
+                    niche_start: *niche_start,
+                }
+            }
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::Abi {
+    type T = stable_mir::ty::ValueAbi;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::ValueAbi;
+        match *self {
+            abi::Abi::Uninhabited => ValueAbi::Uninhabited,
+            abi::Abi::Scalar(scalar) => ValueAbi::Scalar(scalar.stable(tables)),
+            abi::Abi::ScalarPair(first, second) => {
+                ValueAbi::ScalarPair(first.stable(tables), second.stable(tables))
+            }
+            abi::Abi::Vector { element, count } => {
+                ValueAbi::Vector { element: element.stable(tables), count }
+            }
+            abi::Abi::Aggregate { sized } => ValueAbi::Aggregate { sized },
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::Scalar {
+    type T = stable_mir::ty::Scalar;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::Scalar;
+        match *self {
+            abi::Scalar::Initialized { value, valid_range } => Scalar::Initialized {
+                value: value.stable(tables),
+                valid_range: valid_range.stable(tables),
+            },
+            abi::Scalar::Union { value } => Scalar::Union { value: value.stable(tables) },
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::Primitive {
+    type T = stable_mir::ty::Primitive;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::Primitive;
+        match *self {
+            abi::Primitive::Int(length, signed) => {
+                Primitive::Int { length: length.stable(tables), signed }
+            }
+            abi::Primitive::F32 => Primitive::F32,
+            abi::Primitive::F64 => Primitive::F64,
+            abi::Primitive::Pointer(address_space) => Primitive::Pointer(address_space.0),
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::Integer {
+    type T = stable_mir::ty::IntegerLength;
+
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        use stable_mir::ty::IntegerLength;
+        match self {
+            abi::Integer::I8 => IntegerLength::I8,
+            abi::Integer::I16 => IntegerLength::I16,
+            abi::Integer::I32 => IntegerLength::I32,
+            abi::Integer::I64 => IntegerLength::I64,
+            abi::Integer::I128 => IntegerLength::I128,
+        }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::WrappingRange {
+    type T = stable_mir::ty::WrappingRange;
+
+    fn stable(&self, _: &mut Tables<'tcx>) -> Self::T {
+        stable_mir::ty::WrappingRange { start: self.start, end: self.end }
+    }
+}
+
+impl<'tcx> Stable<'tcx> for abi::Niche {
+    type T = stable_mir::ty::Niche;
+
+    fn stable(&self, tables: &mut Tables<'tcx>) -> Self::T {
+        stable_mir::ty::Niche {
+            offset: self.offset.bytes(),
+            value: self.value.stable(tables),
+            valid_range: self.valid_range.stable(tables),
+        }
+    }
+}
@@ -1,3 +1,6 @@
 mod body;
+pub mod builder;
+pub mod mono;
+pub mod visit;
 
 pub use body::*;
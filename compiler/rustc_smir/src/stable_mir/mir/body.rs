@@ -3,20 +3,21 @@
     AdtDef, ClosureDef, Const, GeneratorDef, GenericArgs, Movability, Region,
 };
 use crate::stable_mir::{self, ty::Ty};
+use serde::Serialize;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Body {
     pub blocks: Vec<BasicBlock>,
     pub locals: Vec<Ty>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct BasicBlock {
     pub statements: Vec<Statement>,
     pub terminator: Terminator,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Terminator {
     Goto {
         target: usize,
@@ -60,7 +61,30 @@ pub enum Terminator {
     },
 }
 
-#[derive(Clone, Debug)]
+impl Terminator {
+    /// The blocks this terminator may jump to, including cleanup blocks.
+    pub fn successors(&self) -> Vec<usize> {
+        use self::Terminator::*;
+        match self {
+            Goto { target } => vec![*target],
+            SwitchInt { targets, otherwise, .. } => {
+                targets.iter().map(|target| target.target).chain(Some(*otherwise)).collect()
+            }
+            Resume | Abort | Return | Unreachable | GeneratorDrop => vec![],
+            Drop { target, unwind, .. } | Assert { target, unwind, .. } => {
+                Some(*target).into_iter().chain(unwind.cleanup()).collect()
+            }
+            Call { target, unwind, .. } => {
+                target.into_iter().copied().chain(unwind.cleanup()).collect()
+            }
+            InlineAsm { destination, unwind, .. } => {
+                destination.into_iter().copied().chain(unwind.cleanup()).collect()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct InlineAsmOperand {
     pub in_value: Option<Operand>,
     pub out_place: Option<Place>,
@@ -69,7 +93,7 @@ pub struct InlineAsmOperand {
     pub raw_rpr: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum UnwindAction {
     Continue,
     Unreachable,
@@ -77,7 +101,17 @@ pub enum UnwindAction {
     Cleanup(usize),
 }
 
-#[derive(Clone, Debug)]
+impl UnwindAction {
+    /// The block to jump to when unwinding, if any.
+    pub fn cleanup(&self) -> Option<usize> {
+        match self {
+            UnwindAction::Cleanup(block) => Some(*block),
+            UnwindAction::Continue | UnwindAction::Unreachable | UnwindAction::Terminate => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub enum AssertMessage {
     BoundsCheck { len: Operand, index: Operand },
     Overflow(BinOp, Operand, Operand),
@@ -89,7 +123,7 @@ pub enum AssertMessage {
     MisalignedPointerDereference { required: Operand, found: Operand },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum BinOp {
     Add,
     AddUnchecked,
@@ -115,19 +149,19 @@ pub enum BinOp {
     Offset,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum UnOp {
     Not,
     Neg,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum GeneratorKind {
     Async(AsyncGeneratorKind),
     Gen,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum AsyncGeneratorKind {
     Block,
     Closure,
@@ -140,7 +174,7 @@ pub enum AsyncGeneratorKind {
 pub(crate) type ExpressionOperandId = Opaque;
 
 /// The FakeReadCause describes the type of pattern why a FakeRead statement exists.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum FakeReadCause {
     ForMatchGuard,
     ForMatchedPlace(LocalDefId),
@@ -150,7 +184,7 @@ pub enum FakeReadCause {
 }
 
 /// Describes what kind of retag is to be performed
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum RetagKind {
     FnEntry,
     TwoPhase,
@@ -158,7 +192,7 @@ pub enum RetagKind {
     Default,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Variance {
     Covariant,
     Invariant,
@@ -166,13 +200,13 @@ pub enum Variance {
     Bivariant,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Op {
     Subtract,
     Add,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum CoverageKind {
     Counter {
         function_source_hash: usize,
@@ -187,7 +221,7 @@ pub enum CoverageKind {
     Unreachable,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct CodeRegion {
     pub file_name: String,
     pub start_line: usize,
@@ -196,26 +230,26 @@ pub struct CodeRegion {
     pub end_col: usize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Coverage {
     pub kind: CoverageKind,
     pub code_region: Option<CodeRegion>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct CopyNonOverlapping {
     pub src: Operand,
     pub dst: Operand,
     pub count: Operand,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum NonDivergingIntrinsic {
     Assume(Operand),
     CopyNonOverlapping(CopyNonOverlapping),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Statement {
     Assign(Place, Rvalue),
     FakeRead(FakeReadCause, Place),
@@ -232,7 +266,7 @@ pub enum Statement {
     Nop,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Rvalue {
     /// Creates a pointer with the indicated mutability to the place.
     ///
@@ -346,7 +380,7 @@ pub enum Rvalue {
     Use(Operand),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum AggregateKind {
     Array(Ty),
     Tuple,
@@ -355,20 +389,27 @@ pub enum AggregateKind {
     Generator(GeneratorDef, GenericArgs, Movability),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Operand {
     Copy(Place),
     Move(Place),
-    Constant(String),
+    Constant(Constant),
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Constant {
+    /// A textual representation of the constant value.
+    pub literal: String,
+    pub ty: Ty,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Place {
     pub local: Local,
     pub projection: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct UserTypeProjection {
     pub base: UserTypeAnnotationIndex,
     pub projection: String,
@@ -383,13 +424,13 @@ pub struct UserTypeProjection {
 
 type UserTypeAnnotationIndex = usize;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SwitchTarget {
     pub value: u128,
     pub target: usize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum BorrowKind {
     /// Data must be immutable and is aliasable.
     Shared,
@@ -406,26 +447,26 @@ pub enum BorrowKind {
     },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum MutBorrowKind {
     Default,
     TwoPhaseBorrow,
     ClosureCapture,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Mutability {
     Not,
     Mut,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Safety {
     Unsafe,
     Normal,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum PointerCoercion {
     /// Go from a fn-item type to a fn-pointer type.
     ReifyFnPointer,
@@ -452,7 +493,7 @@ pub enum PointerCoercion {
     Unsize,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum CastKind {
     PointerExposeAddress,
     PointerFromExposedAddress,
@@ -467,7 +508,7 @@ pub enum CastKind {
     Transmute,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum NullOp {
     /// Returns the size of a value of that type.
     SizeOf,
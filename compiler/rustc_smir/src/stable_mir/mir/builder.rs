@@ -0,0 +1,101 @@
+//! A builder to construct new Stable MIR bodies or to modify existing ones.
+//!
+//! Blocks and locals are only ever appended, so indices handed out by the builder stay valid
+//! for the whole lifetime of the builder. This allows instrumentation tools to record
+//! positions in the original body and to insert code at them afterwards.
+
+use crate::stable_mir::mir::visit::Location;
+use crate::stable_mir::mir::{BasicBlock, Body, Local, Statement, Terminator};
+use crate::stable_mir::ty::Ty;
+
+#[derive(Clone, Debug)]
+pub struct BodyBuilder {
+    blocks: Vec<BasicBlock>,
+    locals: Vec<Ty>,
+}
+
+impl BodyBuilder {
+    /// Start a new body. The return place is the local `0`, and has type `return_ty`.
+    /// The arguments are the locals `1..=arg_tys.len()`.
+    pub fn new(return_ty: Ty, arg_tys: &[Ty]) -> BodyBuilder {
+        let mut locals = vec![return_ty];
+        locals.extend_from_slice(arg_tys);
+        BodyBuilder { blocks: vec![], locals }
+    }
+
+    /// Start from an existing body, keeping all of its blocks and locals.
+    pub fn from_body(body: Body) -> BodyBuilder {
+        let Body { blocks, locals } = body;
+        BodyBuilder { blocks, locals }
+    }
+
+    pub fn local_ty(&self, local: Local) -> Ty {
+        self.locals[local]
+    }
+
+    /// Declare a new local of type `ty`.
+    pub fn new_local(&mut self, ty: Ty) -> Local {
+        self.locals.push(ty);
+        self.locals.len() - 1
+    }
+
+    /// Append a new, empty block ending in `terminator` and return its index.
+    pub fn new_block(&mut self, terminator: Terminator) -> usize {
+        self.blocks.push(BasicBlock { statements: vec![], terminator });
+        self.blocks.len() - 1
+    }
+
+    pub fn block(&self, block: usize) -> &BasicBlock {
+        &self.blocks[block]
+    }
+
+    /// Append a statement at the end of `block`, right before its terminator.
+    pub fn push_statement(&mut self, block: usize, stmt: Statement) {
+        self.blocks[block].statements.push(stmt);
+    }
+
+    /// Insert a statement at `location`, shifting the statements after it.
+    pub fn insert_statement(&mut self, location: Location, stmt: Statement) {
+        self.blocks[location.block].statements.insert(location.statement_index, stmt);
+    }
+
+    /// Replace the terminator of `block`, returning the previous one.
+    pub fn set_terminator(&mut self, block: usize, terminator: Terminator) -> Terminator {
+        std::mem::replace(&mut self.blocks[block].terminator, terminator)
+    }
+
+    /// Split `location.block` in two at `location`.
+    ///
+    /// The statements from `location.statement_index` onwards and the terminator are moved to a
+    /// new block, whose index is returned. The original block then jumps to the new block,
+    /// so it can be redirected, e.g. to call an instrumentation function, by replacing its
+    /// terminator.
+    pub fn split_block(&mut self, location: Location) -> usize {
+        let block = &mut self.blocks[location.block];
+        let statements = block.statements.split_off(location.statement_index);
+        let new_block = self.blocks.len();
+        let terminator = std::mem::replace(
+            &mut self.blocks[location.block].terminator,
+            Terminator::Goto { target: new_block },
+        );
+        self.blocks.push(BasicBlock { statements, terminator });
+        new_block
+    }
+
+    /// Finish building the body.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a terminator refers to a block that does not exist.
+    pub fn build(self) -> Body {
+        for (index, block) in self.blocks.iter().enumerate() {
+            for target in block.terminator.successors() {
+                assert!(
+                    target < self.blocks.len(),
+                    "block {index} jumps to non-existent block {target}"
+                );
+            }
+        }
+        Body { blocks: self.blocks, locals: self.locals }
+    }
+}
@@ -0,0 +1,86 @@
+use crate::rustc_internal::Opaque;
+use crate::stable_mir::mir::Body;
+use crate::stable_mir::ty::{ClosureKind, Ty};
+use crate::stable_mir::{with, CrateItem};
+
+/// A monomorphized function or static, i.e., an item with all its generic arguments known.
+#[derive(Clone, Debug)]
+pub struct Instance {
+    /// The kind of this instance.
+    pub kind: InstanceKind,
+    /// Index used to look up the instance in the compiler.
+    pub(crate) def: InstanceDef,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InstanceKind {
+    /// A user defined item.
+    Item,
+    /// A compiler intrinsic.
+    Intrinsic,
+    /// A dynamically dispatched trait method. `idx` is the method's position in the vtable.
+    Virtual { idx: usize },
+    /// A compiler generated shim, such as drop glue, a vtable shim or a closure shim.
+    Shim,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InstanceDef(pub(crate) usize);
+
+impl Instance {
+    /// Get the body of this instance, with all generic arguments substituted.
+    ///
+    /// Returns `None` for intrinsics and virtual calls, which have no body,
+    /// and for items whose MIR is not available to the local crate.
+    pub fn body(&self) -> Option<Body> {
+        with(|cx| cx.instance_body(self.def))
+    }
+
+    /// Get the type of this instance. For functions, this is a `FnDef`.
+    pub fn ty(&self) -> Ty {
+        with(|cx| cx.instance_ty(self.def))
+    }
+
+    /// A human readable name of this instance, including its generic arguments.
+    pub fn name(&self) -> String {
+        with(|cx| cx.instance_name(self.def))
+    }
+
+    /// The symbol name this instance is emitted with.
+    pub fn mangled_name(&self) -> String {
+        with(|cx| cx.instance_mangled_name(self.def))
+    }
+
+    /// Build the instance of a non-generic function.
+    /// Returns `None` if the item is not a function or has type or const parameters.
+    pub fn mono(item: &CrateItem) -> Option<Instance> {
+        with(|cx| cx.mono_instance(item))
+    }
+
+    /// Resolve the function being called given the type of the callee, e.g. the type of the
+    /// `func` operand of a `Call` terminator. The type must be a `FnDef`.
+    ///
+    /// Returns `None` if the callee cannot be resolved yet, e.g. because the arguments are
+    /// still generic.
+    pub fn resolve(fn_ty: Ty) -> Option<Instance> {
+        with(|cx| cx.resolve_instance(fn_ty))
+    }
+
+    /// Resolve the drop glue of the given type.
+    pub fn resolve_drop_in_place(ty: Ty) -> Instance {
+        with(|cx| cx.resolve_drop_in_place(ty))
+    }
+
+    /// Resolve the instance used to call a closure of type `closure_ty` through the `kind` trait.
+    pub fn resolve_closure(closure_ty: Ty, kind: ClosureKind) -> Option<Instance> {
+        with(|cx| cx.resolve_closure(closure_ty, kind))
+    }
+}
+
+/// An item that is collected for code generation.
+#[derive(Clone, Debug)]
+pub enum MonoItem {
+    Fn(Instance),
+    Static(CrateItem),
+    GlobalAsm(Opaque),
+}
@@ -0,0 +1,319 @@
+//! # The Stable MIR Visitor
+//!
+//! ## Overview
+//!
+//! The `MirVisitor` trait walks a Stable MIR [`Body`] in a fixed order, calling a `visit_*`
+//! method for each construct it finds. Each `visit_*` method has a default implementation that
+//! calls the corresponding `super_*` method, which in turn visits the sub-components of the
+//! construct. To write an analysis, override the `visit_*` methods you are interested in and
+//! call the `super_*` method from them if you want to keep walking into the sub-components.
+//!
+//! For example, counting the function calls of a body looks like:
+//!
+//! ```ignore (needs a running compiler)
+//! struct CallCounter(usize);
+//!
+//! impl MirVisitor for CallCounter {
+//!     fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+//!         if let Terminator::Call { .. } = term {
+//!             self.0 += 1;
+//!         }
+//!         self.super_terminator(term, location);
+//!     }
+//! }
+//! ```
+//!
+//! The `super_*` methods destructure every construct exhaustively, so that adding a field to
+//! the Stable MIR data types forces the visitor to be updated.
+
+use crate::stable_mir::mir::*;
+use crate::stable_mir::ty::{Const, GenericArgKind, GenericArgs, Region, Ty};
+
+/// A position in a body: the statement at `statement_index` in `block`.
+/// The terminator of a block is located at the index one past its last statement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub block: usize,
+    pub statement_index: usize,
+}
+
+/// Whether a place is written to or only read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PlaceContext {
+    pub is_mut: bool,
+}
+
+impl PlaceContext {
+    pub const MUTATING: PlaceContext = PlaceContext { is_mut: true };
+    pub const NON_MUTATING: PlaceContext = PlaceContext { is_mut: false };
+}
+
+pub trait MirVisitor {
+    fn visit_body(&mut self, body: &Body) {
+        self.super_body(body)
+    }
+
+    fn visit_basic_block(&mut self, block: &BasicBlock, index: usize) {
+        self.super_basic_block(block, index)
+    }
+
+    fn visit_statement(&mut self, stmt: &Statement, location: Location) {
+        self.super_statement(stmt, location)
+    }
+
+    fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+        self.super_terminator(term, location)
+    }
+
+    fn visit_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
+        self.super_rvalue(rvalue, location)
+    }
+
+    fn visit_operand(&mut self, operand: &Operand, location: Location) {
+        self.super_operand(operand, location)
+    }
+
+    fn visit_constant(&mut self, constant: &Constant, location: Location) {
+        self.super_constant(constant, location)
+    }
+
+    fn visit_place(&mut self, place: &Place, ptx: PlaceContext, location: Location) {
+        self.super_place(place, ptx, location)
+    }
+
+    fn visit_assert_msg(&mut self, msg: &AssertMessage, location: Location) {
+        self.super_assert_msg(msg, location)
+    }
+
+    /// Called for each local of the body, with the body's declared type.
+    fn visit_local_decl(&mut self, local: Local, ty: Ty) {
+        self.super_local_decl(local, ty)
+    }
+
+    /// Called for each use of a local, e.g. as the base of a place.
+    fn visit_local(&mut self, _local: &Local, _ptx: PlaceContext, _location: Location) {}
+
+    /// Called for each type mentioned in a statement or terminator.
+    fn visit_ty(&mut self, _ty: &Ty, _location: Location) {}
+
+    fn visit_const(&mut self, _constant: &Const, _location: Location) {}
+
+    fn visit_region(&mut self, _region: &Region, _location: Location) {}
+
+    fn visit_generic_args(&mut self, args: &GenericArgs, location: Location) {
+        self.super_generic_args(args, location)
+    }
+
+    fn super_body(&mut self, body: &Body) {
+        let Body { blocks, locals } = body;
+
+        for (local, ty) in locals.iter().enumerate() {
+            self.visit_local_decl(local, *ty);
+        }
+
+        for (index, block) in blocks.iter().enumerate() {
+            self.visit_basic_block(block, index);
+        }
+    }
+
+    fn super_basic_block(&mut self, block: &BasicBlock, index: usize) {
+        let BasicBlock { statements, terminator } = block;
+        for (statement_index, stmt) in statements.iter().enumerate() {
+            self.visit_statement(stmt, Location { block: index, statement_index });
+        }
+        let location = Location { block: index, statement_index: statements.len() };
+        self.visit_terminator(terminator, location);
+    }
+
+    fn super_local_decl(&mut self, _local: Local, _ty: Ty) {}
+
+    fn super_statement(&mut self, stmt: &Statement, location: Location) {
+        match stmt {
+            Statement::Assign(place, rvalue) => {
+                self.visit_place(place, PlaceContext::MUTATING, location);
+                self.visit_rvalue(rvalue, location);
+            }
+            Statement::FakeRead(_, place) | Statement::PlaceMention(place) => {
+                self.visit_place(place, PlaceContext::NON_MUTATING, location);
+            }
+            Statement::SetDiscriminant { place, variant_index: _ }
+            | Statement::Deinit(place)
+            | Statement::Retag(_, place) => {
+                self.visit_place(place, PlaceContext::MUTATING, location);
+            }
+            Statement::StorageLive(local) | Statement::StorageDead(local) => {
+                self.visit_local(local, PlaceContext::NON_MUTATING, location);
+            }
+            Statement::AscribeUserType { place, projections: _, variance: _ } => {
+                self.visit_place(place, PlaceContext::NON_MUTATING, location);
+            }
+            Statement::Intrinsic(intrinsic) => match intrinsic {
+                NonDivergingIntrinsic::Assume(operand) => {
+                    self.visit_operand(operand, location);
+                }
+                NonDivergingIntrinsic::CopyNonOverlapping(CopyNonOverlapping {
+                    src,
+                    dst,
+                    count,
+                }) => {
+                    self.visit_operand(src, location);
+                    self.visit_operand(dst, location);
+                    self.visit_operand(count, location);
+                }
+            },
+            Statement::Coverage(_) | Statement::ConstEvalCounter | Statement::Nop => {}
+        }
+    }
+
+    fn super_terminator(&mut self, term: &Terminator, location: Location) {
+        match term {
+            Terminator::Goto { target: _ }
+            | Terminator::Resume
+            | Terminator::Abort
+            | Terminator::Return
+            | Terminator::Unreachable
+            | Terminator::GeneratorDrop => {}
+            Terminator::SwitchInt { discr, targets: _, otherwise: _ } => {
+                self.visit_operand(discr, location);
+            }
+            Terminator::Drop { place, target: _, unwind: _ } => {
+                self.visit_place(place, PlaceContext::MUTATING, location);
+            }
+            Terminator::Call { func, args, destination, target: _, unwind: _ } => {
+                self.visit_operand(func, location);
+                for arg in args {
+                    self.visit_operand(arg, location);
+                }
+                self.visit_place(destination, PlaceContext::MUTATING, location);
+            }
+            Terminator::Assert { cond, expected: _, msg, target: _, unwind: _ } => {
+                self.visit_operand(cond, location);
+                self.visit_assert_msg(msg, location);
+            }
+            Terminator::InlineAsm {
+                template: _,
+                operands,
+                options: _,
+                line_spans: _,
+                destination: _,
+                unwind: _,
+            } => {
+                for InlineAsmOperand { in_value, out_place, raw_rpr: _ } in operands {
+                    if let Some(in_value) = in_value {
+                        self.visit_operand(in_value, location);
+                    }
+                    if let Some(out_place) = out_place {
+                        self.visit_place(out_place, PlaceContext::MUTATING, location);
+                    }
+                }
+            }
+        }
+    }
+
+    fn super_rvalue(&mut self, rvalue: &Rvalue, location: Location) {
+        match rvalue {
+            Rvalue::AddressOf(mutability, place) => {
+                let ptx = match mutability {
+                    Mutability::Mut => PlaceContext::MUTATING,
+                    Mutability::Not => PlaceContext::NON_MUTATING,
+                };
+                self.visit_place(place, ptx, location);
+            }
+            Rvalue::Aggregate(kind, operands) => {
+                match kind {
+                    AggregateKind::Array(ty) => self.visit_ty(ty, location),
+                    AggregateKind::Tuple => {}
+                    AggregateKind::Adt(_, _, args, _, _)
+                    | AggregateKind::Closure(_, args)
+                    | AggregateKind::Generator(_, args, _) => {
+                        self.visit_generic_args(args, location)
+                    }
+                }
+                for operand in operands {
+                    self.visit_operand(operand, location);
+                }
+            }
+            Rvalue::BinaryOp(_, lhs, rhs) | Rvalue::CheckedBinaryOp(_, lhs, rhs) => {
+                self.visit_operand(lhs, location);
+                self.visit_operand(rhs, location);
+            }
+            Rvalue::Cast(_, operand, ty) | Rvalue::ShallowInitBox(operand, ty) => {
+                self.visit_operand(operand, location);
+                self.visit_ty(ty, location);
+            }
+            Rvalue::CopyForDeref(place) | Rvalue::Discriminant(place) | Rvalue::Len(place) => {
+                self.visit_place(place, PlaceContext::NON_MUTATING, location);
+            }
+            Rvalue::Ref(region, kind, place) => {
+                self.visit_region(region, location);
+                let ptx = match kind {
+                    BorrowKind::Mut { .. } => PlaceContext::MUTATING,
+                    BorrowKind::Shared | BorrowKind::Shallow => PlaceContext::NON_MUTATING,
+                };
+                self.visit_place(place, ptx, location);
+            }
+            Rvalue::Repeat(operand, count) => {
+                self.visit_operand(operand, location);
+                self.visit_const(count, location);
+            }
+            Rvalue::ThreadLocalRef(_) => {}
+            Rvalue::NullaryOp(_, ty) => self.visit_ty(ty, location),
+            Rvalue::UnaryOp(_, operand) | Rvalue::Use(operand) => {
+                self.visit_operand(operand, location);
+            }
+        }
+    }
+
+    fn super_operand(&mut self, operand: &Operand, location: Location) {
+        match operand {
+            Operand::Copy(place) | Operand::Move(place) => {
+                self.visit_place(place, PlaceContext::NON_MUTATING, location)
+            }
+            Operand::Constant(constant) => self.visit_constant(constant, location),
+        }
+    }
+
+    fn super_constant(&mut self, constant: &Constant, location: Location) {
+        let Constant { literal: _, ty } = constant;
+        self.visit_ty(ty, location);
+    }
+
+    fn super_place(&mut self, place: &Place, ptx: PlaceContext, location: Location) {
+        let Place { local, projection: _ } = place;
+        self.visit_local(local, ptx, location);
+    }
+
+    fn super_assert_msg(&mut self, msg: &AssertMessage, location: Location) {
+        match msg {
+            AssertMessage::BoundsCheck { len, index } => {
+                self.visit_operand(len, location);
+                self.visit_operand(index, location);
+            }
+            AssertMessage::Overflow(_, lhs, rhs) => {
+                self.visit_operand(lhs, location);
+                self.visit_operand(rhs, location);
+            }
+            AssertMessage::OverflowNeg(operand)
+            | AssertMessage::DivisionByZero(operand)
+            | AssertMessage::RemainderByZero(operand) => {
+                self.visit_operand(operand, location);
+            }
+            AssertMessage::ResumedAfterReturn(_) | AssertMessage::ResumedAfterPanic(_) => {}
+            AssertMessage::MisalignedPointerDereference { required, found } => {
+                self.visit_operand(required, location);
+                self.visit_operand(found, location);
+            }
+        }
+    }
+
+    fn super_generic_args(&mut self, args: &GenericArgs, location: Location) {
+        let GenericArgs(args) = args;
+        for arg in args {
+            match arg {
+                GenericArgKind::Lifetime(region) => self.visit_region(region, location),
+                GenericArgKind::Type(ty) => self.visit_ty(ty, location),
+                GenericArgKind::Const(constant) => self.visit_const(constant, location),
+            }
+        }
+    }
+}
@@ -13,9 +13,11 @@
 
 use std::cell::Cell;
 
+use serde::Serialize;
+
 use crate::rustc_smir::Tables;
 
-use self::ty::{Ty, TyKind};
+use self::ty::{AdtDef, AdtKind, Generics, ImplDef, ImplTrait, Layout, Ty, TyKind, VariantDef};
 
 pub mod mir;
 pub mod ty;
@@ -32,8 +34,11 @@
 /// A list of crate items.
 pub type CrateItems = Vec<CrateItem>;
 
+/// A list of trait impls.
+pub type ImplTraitDecls = Vec<ImplDef>;
+
 /// Holds information about a crate.
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct Crate {
     pub(crate) id: CrateNum,
     pub name: Symbol,
@@ -43,7 +48,7 @@ pub struct Crate {
 /// Holds information about an item in the crate.
 /// For now, it only stores the item DefId. Use functions inside `rustc_internal` module to
 /// use this item.
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct CrateItem(pub(crate) DefId);
 
 impl CrateItem {
@@ -79,6 +84,22 @@ pub fn all_local_items() -> CrateItems {
     with(|cx| cx.all_local_items())
 }
 
+/// Retrieve all trait impls of the local crate and of all its dependencies.
+pub fn all_trait_impls() -> ImplTraitDecls {
+    with(|cx| cx.all_trait_impls())
+}
+
+/// Retrieve the trait reference implemented by the given impl.
+pub fn trait_impl(trait_impl: &ImplDef) -> ImplTrait {
+    with(|cx| cx.trait_impl(trait_impl))
+}
+
+/// Retrieve all items that will be code generated for the local crate,
+/// including generic items instantiated by it and compiler generated shims.
+pub fn mono_items() -> Vec<MonoItem> {
+    with(|cx| cx.mono_items())
+}
+
 pub trait Context {
     fn entry_fn(&mut self) -> Option<CrateItem>;
     /// Retrieve all items of the local crate that have a MIR associated with them.
@@ -95,6 +116,51 @@ pub trait Context {
     /// Obtain the representation of a type.
     fn ty_kind(&mut self, ty: Ty) -> TyKind;
 
+    /// Compute the layout of a type, if it is fully known.
+    fn ty_layout(&mut self, ty: Ty) -> Option<Layout>;
+
+    /// Whether an ADT is a struct, a union or an enum.
+    fn adt_kind(&mut self, def: &AdtDef) -> AdtKind;
+
+    /// Retrieve the variants of an ADT, together with their fields.
+    fn adt_variants(&mut self, def: &AdtDef) -> Vec<VariantDef>;
+
+    /// Retrieve the generic parameters of an item.
+    fn generics_of(&mut self, def_id: DefId) -> Generics;
+
+    /// Retrieve all trait impls visible to the local crate.
+    fn all_trait_impls(&mut self) -> ImplTraitDecls;
+
+    /// Retrieve the trait reference implemented by an impl.
+    fn trait_impl(&mut self, trait_impl: &ImplDef) -> ImplTrait;
+
+    /// Collect all items that need to be code generated for the local crate.
+    fn mono_items(&mut self) -> Vec<MonoItem>;
+
+    /// Get the monomorphized body of an instance, if it has one.
+    fn instance_body(&mut self, def: InstanceDef) -> Option<mir::Body>;
+
+    /// Get the type of an instance.
+    fn instance_ty(&mut self, def: InstanceDef) -> Ty;
+
+    /// Get a human readable name of an instance.
+    fn instance_name(&mut self, def: InstanceDef) -> String;
+
+    /// Get the symbol name of an instance.
+    fn instance_mangled_name(&mut self, def: InstanceDef) -> String;
+
+    /// Build the instance of a function without type or const parameters.
+    fn mono_instance(&mut self, item: &CrateItem) -> Option<Instance>;
+
+    /// Resolve the instance called through a `FnDef` type.
+    fn resolve_instance(&mut self, fn_ty: Ty) -> Option<Instance>;
+
+    /// Resolve the drop glue of a type.
+    fn resolve_drop_in_place(&mut self, ty: Ty) -> Instance;
+
+    /// Resolve the instance for calling a closure through the given closure trait.
+    fn resolve_closure(&mut self, closure_ty: Ty, kind: ClosureKind) -> Option<Instance>;
+
     /// HACK: Until we have fully stable consumers, we need an escape hatch
     /// to get `DefId`s out of `CrateItem`s.
     fn rustc_tables(&mut self, f: &mut dyn FnMut(&mut Tables<'_>));
@@ -1,20 +1,30 @@
-use super::{mir::Mutability, with, DefId};
+use serde::Serialize;
+use std::ops::RangeInclusive;
+
+use super::mir::{Mutability, VariantIdx};
+use super::{with, DefId, Symbol};
 use crate::rustc_internal::Opaque;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize)]
 pub struct Ty(pub usize);
 
 impl Ty {
     pub fn kind(&self) -> TyKind {
         with(|context| context.ty_kind(*self))
     }
+
+    /// Compute the memory layout of this type for the current target.
+    /// Returns `None` if the layout cannot be computed, e.g. because the type is generic.
+    pub fn layout(&self) -> Option<Layout> {
+        with(|context| context.ty_layout(*self))
+    }
 }
 
 pub(crate) type Const = Opaque;
 pub(crate) type Region = Opaque;
 type Span = Opaque;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum TyKind {
     RigidTy(RigidTy),
     Alias(AliasKind, AliasTy),
@@ -22,7 +32,7 @@ pub enum TyKind {
     Bound(usize, BoundTy),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum RigidTy {
     Bool,
     Char,
@@ -45,7 +55,7 @@ pub enum RigidTy {
     Tuple(Vec<Ty>),
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub enum IntTy {
     Isize,
     I8,
@@ -55,7 +65,7 @@ pub enum IntTy {
     I128,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub enum UintTy {
     Usize,
     U8,
@@ -65,62 +75,110 @@ pub enum UintTy {
     U128,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub enum FloatTy {
     F32,
     F64,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum ClosureKind {
+    Fn,
+    FnMut,
+    FnOnce,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
 pub enum Movability {
     Static,
     Movable,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct ForeignDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct FnDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct ClosureDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct GeneratorDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct ParamDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct BrNamedDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct AdtDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+impl AdtDef {
+    pub fn kind(&self) -> AdtKind {
+        with(|cx| cx.adt_kind(self))
+    }
+
+    /// Retrieve the variants of this ADT. Structs and unions have exactly one variant.
+    pub fn variants(&self) -> Vec<VariantDef> {
+        with(|cx| cx.adt_variants(self))
+    }
+
+    pub fn generics(&self) -> Generics {
+        with(|cx| cx.generics_of(self.0))
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct AliasDef(pub(crate) DefId);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub struct TraitDef(pub(crate) DefId);
 
-#[derive(Clone, Debug)]
+impl TraitDef {
+    pub fn generics(&self) -> Generics {
+        with(|cx| cx.generics_of(self.0))
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct ImplDef(pub(crate) DefId);
+
+impl ImplDef {
+    /// Retrieve the trait reference implemented by this impl.
+    pub fn trait_impl(&self) -> ImplTrait {
+        with(|cx| cx.trait_impl(self))
+    }
+
+    pub fn generics(&self) -> Generics {
+        with(|cx| cx.generics_of(self.0))
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct GenericDef(pub(crate) DefId);
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
+pub struct FieldDefId(pub(crate) DefId);
+
+#[derive(Clone, Debug, Serialize)]
 pub struct GenericArgs(pub Vec<GenericArgKind>);
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum GenericArgKind {
     Lifetime(Region),
     Type(Ty),
     Const(Const),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum TermKind {
     Type(Ty),
     Const(Const),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum AliasKind {
     Projection,
     Inherent,
@@ -128,7 +186,7 @@ pub enum AliasKind {
     Weak,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct AliasTy {
     pub def_id: AliasDef,
     pub args: GenericArgs,
@@ -136,7 +194,7 @@ pub struct AliasTy {
 
 pub type PolyFnSig = Binder<FnSig>;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct FnSig {
     pub inputs_and_output: Vec<Ty>,
     pub c_variadic: bool,
@@ -144,13 +202,13 @@ pub struct FnSig {
     pub abi: Abi,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub enum Unsafety {
     Unsafe,
     Normal,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub enum Abi {
     Rust,
     C { unwind: bool },
@@ -179,66 +237,236 @@ pub enum Abi {
     RustCold,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Binder<T> {
     pub value: T,
     pub bound_vars: Vec<BoundVariableKind>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum BoundVariableKind {
     Ty(BoundTyKind),
     Region(BoundRegionKind),
     Const,
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize)]
 pub enum BoundTyKind {
     Anon,
     Param(ParamDef, String),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum BoundRegionKind {
     BrAnon(Option<Span>),
     BrNamed(BrNamedDef, String),
     BrEnv,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum DynKind {
     Dyn,
     DynStar,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum ExistentialPredicate {
     Trait(ExistentialTraitRef),
     Projection(ExistentialProjection),
     AutoTrait(TraitDef),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ExistentialTraitRef {
     pub def_id: TraitDef,
     pub generic_args: GenericArgs,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ExistentialProjection {
     pub def_id: TraitDef,
     pub generic_args: GenericArgs,
     pub term: TermKind,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct ParamTy {
     pub index: u32,
     pub name: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct BoundTy {
     pub var: usize,
     pub kind: BoundTyKind,
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum AdtKind {
+    Struct,
+    Union,
+    Enum,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VariantDef {
+    pub name: Symbol,
+    /// The discriminant value of this variant. Only enum variants have one.
+    pub discr: Option<u128>,
+    pub fields: Vec<FieldDef>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FieldDef {
+    pub def: FieldDefId,
+    pub name: Symbol,
+    /// The declared type of the field, which may refer to the generic parameters of its ADT.
+    pub ty: Ty,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EarlyBinder<T> {
+    pub value: T,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TraitRef {
+    pub def_id: TraitDef,
+    pub args: GenericArgs,
+}
+
+pub type ImplTrait = EarlyBinder<TraitRef>;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Generics {
+    pub parent: Option<GenericDef>,
+    pub parent_count: usize,
+    pub params: Vec<GenericParamDef>,
+    pub has_self: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub enum GenericParamDefKind {
+    Lifetime,
+    Type { has_default: bool, synthetic: bool },
+    Const { has_default: bool },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct GenericParamDef {
+    pub name: Symbol,
+    pub def_id: GenericDef,
+    pub index: u32,
+    pub pure_wrt_drop: bool,
+    pub kind: GenericParamDefKind,
+}
+
+/// The memory layout of a type for the current target.
+///
+/// All sizes, offsets and alignments are in bytes.
+#[derive(Clone, Debug, Serialize)]
+pub struct Layout {
+    pub size: u64,
+    /// The alignment required by the ABI.
+    pub align: u64,
+    pub fields: FieldsShape,
+    pub variants: VariantsShape,
+    pub abi: ValueAbi,
+    /// The leaf scalar with the largest number of invalid values, if any.
+    pub largest_niche: Option<Niche>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub enum FieldsShape {
+    /// Scalar primitives and `!`, which never have fields.
+    Primitive,
+    /// All fields start at offset zero. The `usize` is the field count.
+    Union(usize),
+    /// Array/vector-like placement, with all fields of identical types.
+    Array { stride: u64, count: u64 },
+    /// Struct-like placement. Offsets are ordered to match the source definition order.
+    Arbitrary { offsets: Vec<u64> },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub enum VariantsShape {
+    /// Single enum variants, structs/tuples, unions, and all non-ADTs.
+    Single { index: VariantIdx },
+    /// Enum-likes with more than one inhabited variant, distinguished by a tag.
+    Multiple { tag: Scalar, tag_encoding: TagEncoding, tag_field: usize, variants: Vec<Layout> },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub enum TagEncoding {
+    /// The tag directly stores the discriminant.
+    Direct,
+    /// The discriminant is encoded in the niche of the `untagged_variant`.
+    Niche {
+        untagged_variant: VariantIdx,
+        niche_variants: RangeInclusive<VariantIdx>,
+        niche_start: u128,
+    },
+}
+
+/// Describes how values of a type are passed by value.
+#[derive(Clone, Debug, Serialize)]
+pub enum ValueAbi {
+    Uninhabited,
+    Scalar(Scalar),
+    ScalarPair(Scalar, Scalar),
+    Vector {
+        element: Scalar,
+        count: u64,
+    },
+    Aggregate {
+        /// If true, the size is exact, otherwise it's only a lower bound.
+        sized: bool,
+    },
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub enum Scalar {
+    Initialized {
+        value: Primitive,
+        valid_range: WrappingRange,
+    },
+    /// Unions never have niches, so there is no `valid_range`.
+    Union {
+        value: Primitive,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Primitive {
+    Int {
+        length: IntegerLength,
+        signed: bool,
+    },
+    F32,
+    F64,
+    /// A pointer in the given address space.
+    Pointer(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum IntegerLength {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+}
+
+/// An inclusive range of valid values, which may wrap around (i.e. `start > end`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct WrappingRange {
+    pub start: u128,
+    pub end: u128,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Niche {
+    pub offset: u64,
+    pub value: Primitive,
+    pub valid_range: WrappingRange,
+}
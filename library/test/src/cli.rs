@@ -2,6 +2,7 @@
 
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use super::options::{ColorConfig, Options, OutputFormat, RunIgnored};
 use super::time::TestTimeOptions;
@@ -19,6 +20,9 @@ pub struct TestOpts {
     pub bench_benchmarks: bool,
     pub logfile: Option<PathBuf>,
     pub nocapture: bool,
+    /// Run every test in its own subprocess, so that all output written to the standard
+    /// output and error file descriptors is captured, not only the one of `print!` and friends.
+    pub capture_fd: bool,
     pub color: ColorConfig,
     pub format: OutputFormat,
     pub shuffle: bool,
@@ -26,6 +30,17 @@ pub struct TestOpts {
     pub test_threads: Option<usize>,
     pub skip: Vec<String>,
     pub time_options: Option<TestTimeOptions>,
+    /// Kill tests that run for longer than this and report them as failed.
+    /// Enforcing the timeout requires running every test in its own subprocess.
+    pub test_timeout: Option<Duration>,
+    /// Number of times a failing test is run again before it is reported as failed.
+    pub retries: usize,
+    /// Only run the tests of a shard, given as `(index, count)`.
+    pub shard: Option<(usize, usize)>,
+    /// Save the samples of the benchmarks as a baseline with this name.
+    pub save_baseline: Option<String>,
+    /// Compare the benchmarks against the baseline with this name.
+    pub baseline: Option<String>,
     /// Stop at first failing test.
     /// May run a few more tests due to threading, but will
     /// abort as soon as possible.
@@ -65,6 +80,13 @@ fn optgroups() -> getopts::Options {
             "don't capture stdout/stderr of each \
              task, allow printing directly",
         )
+        .optflag(
+            "",
+            "capture-fd",
+            "Capture stdout/stderr of each test at the file descriptor level, by
+            running it in its own subprocess. This also captures the output of
+            child processes and of threads not spawned by the test.",
+        )
         .optopt(
             "",
             "test-threads",
@@ -102,8 +124,10 @@ fn optgroups() -> getopts::Options {
             pretty = Print verbose output;
             terse  = Display one character per test;
             json   = Output a json document;
-            junit  = Output a JUnit document",
-            "pretty|terse|json|junit",
+            junit  = Output a JUnit document;
+            tap    = Output a TAP (Test Anything Protocol) version 13 stream;
+            github = Print verbose output with GitHub Actions annotations for failed tests",
+            "pretty|terse|json|junit|tap|github",
         )
         .optflag("", "show-output", "Show captured stdout of successful tests")
         .optopt(
@@ -142,6 +166,55 @@ fn optgroups() -> getopts::Options {
             `CRITICAL_TIME` here means the limit that should not be exceeded by test.
             ",
         )
+        .optopt(
+            "",
+            "test-timeout",
+            "Kill tests that run for longer than SECONDS and report them as failed.
+
+            Every test is run in its own subprocess, so that a hung test can be
+            terminated without stopping the rest of the suite. Tests that can't be
+            run in a subprocess, such as dynamically created ones, are run in-process
+            and are not subject to the timeout.",
+            "SECONDS",
+        )
+        .optopt(
+            "",
+            "retries",
+            "Run failing tests up to N more times. Tests that pass on a retry
+            are reported as flaky.
+
+            Tests that can't be run again, such as dynamically created ones,
+            are not retried.",
+            "N",
+        )
+        .optopt(
+            "",
+            "shard-index",
+            "Only run the tests of the given shard, between 0 and the shard count.
+            Must be used together with --shard-count.",
+            "INDEX",
+        )
+        .optopt(
+            "",
+            "shard-count",
+            "Partition the tests into COUNT shards, based on their names.
+            Must be used together with --shard-index.",
+            "COUNT",
+        )
+        .optopt(
+            "",
+            "save-baseline",
+            "Save the results of the benchmarks as the baseline NAME.
+            Results of benchmarks that were not run are kept.",
+            "NAME",
+        )
+        .optopt(
+            "",
+            "baseline",
+            "Compare the results of the benchmarks against the baseline NAME
+            saved by a previous run with --save-baseline.",
+            "NAME",
+        )
         .optflag("", "shuffle", "Run tests in random order")
         .optopt(
             "",
@@ -258,7 +331,13 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
     // Unstable flags
     let force_run_in_process = unstable_optflag!(matches, allow_unstable, "force-run-in-process");
     let exclude_should_panic = unstable_optflag!(matches, allow_unstable, "exclude-should-panic");
+    let capture_fd = unstable_optflag!(matches, allow_unstable, "capture-fd");
     let time_options = get_time_options(&matches, allow_unstable)?;
+    let test_timeout = get_test_timeout(&matches, allow_unstable)?;
+    let retries = get_retries(&matches, allow_unstable)?;
+    let shard = get_shard(&matches, allow_unstable)?;
+    let save_baseline = unstable_optopt!(matches, allow_unstable, "save-baseline");
+    let baseline = unstable_optopt!(matches, allow_unstable, "baseline");
     let shuffle = get_shuffle(&matches, allow_unstable)?;
     let shuffle_seed = get_shuffle_seed(&matches, allow_unstable)?;
 
@@ -292,6 +371,7 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         bench_benchmarks,
         logfile,
         nocapture,
+        capture_fd,
         color,
         format,
         shuffle,
@@ -299,6 +379,11 @@ fn parse_opts_impl(matches: getopts::Matches) -> OptRes {
         test_threads,
         skip,
         time_options,
+        test_timeout,
+        retries,
+        shard,
+        save_baseline,
+        baseline,
         options,
         fail_fast: false,
     };
@@ -336,6 +421,76 @@ fn get_time_options(
     Ok(options)
 }
 
+fn get_test_timeout(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<Duration>> {
+    let test_timeout = match unstable_optopt!(matches, allow_unstable, "test-timeout") {
+        Some(n_str) => match n_str.parse::<u64>() {
+            Ok(0) => return Err("argument for --test-timeout must not be 0".to_string()),
+            Ok(n) => Some(Duration::from_secs(n)),
+            Err(e) => {
+                return Err(format!(
+                    "argument for --test-timeout must be a number of seconds > 0 \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    Ok(test_timeout)
+}
+
+fn get_retries(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<usize> {
+    let retries = match unstable_optopt!(matches, allow_unstable, "retries") {
+        Some(n_str) => match n_str.parse::<usize>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(format!(
+                    "argument for --retries must be a number \
+                     (error: {e})"
+                ));
+            }
+        },
+        None => 0,
+    };
+
+    Ok(retries)
+}
+
+fn get_shard(
+    matches: &getopts::Matches,
+    allow_unstable: bool,
+) -> OptPartRes<Option<(usize, usize)>> {
+    let index = unstable_optopt!(matches, allow_unstable, "shard-index");
+    let count = unstable_optopt!(matches, allow_unstable, "shard-count");
+    let (index, count) = match (index, count) {
+        (Some(index), Some(count)) => (index, count),
+        (None, None) => return Ok(None),
+        _ => return Err("the options --shard-index and --shard-count must be used together".into()),
+    };
+
+    let index = match index.parse::<usize>() {
+        Ok(n) => n,
+        Err(e) => return Err(format!("argument for --shard-index must be a number (error: {e})")),
+    };
+    let count = match count.parse::<usize>() {
+        Ok(0) => return Err("argument for --shard-count must not be 0".to_string()),
+        Ok(n) => n,
+        Err(e) => {
+            return Err(format!("argument for --shard-count must be a number > 0 (error: {e})"));
+        }
+    };
+    if index >= count {
+        return Err(format!(
+            "argument for --shard-index must be less than the shard count {count} (was {index})"
+        ));
+    }
+
+    Ok(Some((index, count)))
+}
+
 fn get_shuffle(matches: &getopts::Matches, allow_unstable: bool) -> OptPartRes<bool> {
     let mut shuffle = unstable_optflag!(matches, allow_unstable, "shuffle");
     if !shuffle && allow_unstable {
@@ -414,9 +569,11 @@ fn get_format(
             }
             OutputFormat::Junit
         }
+        Some("tap") => OutputFormat::Tap,
+        Some("github") => OutputFormat::Github,
         Some(v) => {
             return Err(format!(
-                "argument for --format must be pretty, terse, json or junit (was \
+                "argument for --format must be pretty, terse, json, junit, tap or github (was \
                  {v})"
             ));
         }
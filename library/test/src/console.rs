@@ -6,12 +6,19 @@
 use std::time::Instant;
 
 use super::{
-    bench::fmt_bench_samples,
+    bench::{fmt_bench_samples, BenchSamples},
     cli::TestOpts,
     event::{CompletedTest, TestEvent},
     filter_tests,
-    formatters::{JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter, TerseFormatter},
-    helpers::{concurrency::get_concurrency, metrics::MetricMap},
+    formatters::{
+        GithubFormatter, JsonFormatter, JunitFormatter, OutputFormatter, PrettyFormatter,
+        TapFormatter, TerseFormatter,
+    },
+    helpers::{
+        baseline::{Baseline, Comparison},
+        concurrency::get_concurrency,
+        metrics::MetricMap,
+    },
     options::{Options, OutputFormat},
     run_tests, term,
     test_result::TestResult,
@@ -87,10 +94,16 @@ pub struct ConsoleTestState {
     pub passed: usize,
     pub failed: usize,
     pub ignored: usize,
+    /// Tests that passed only after being retried. They are also counted as passed.
+    pub flaky: usize,
     pub filtered_out: usize,
     pub measured: usize,
     pub exec_time: Option<TestSuiteExecTime>,
     pub metrics: MetricMap,
+    /// Baseline to compare the benchmarks against.
+    pub baseline: Option<Baseline>,
+    /// Samples of the benchmarks run so far.
+    pub bench_samples: Baseline,
     pub failures: Vec<(TestDesc, Vec<u8>)>,
     pub not_failures: Vec<(TestDesc, Vec<u8>)>,
     pub ignores: Vec<(TestDesc, Vec<u8>)>,
@@ -104,6 +117,10 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             Some(ref path) => Some(File::create(path)?),
             None => None,
         };
+        let baseline = match opts.baseline {
+            Some(ref name) => Some(Baseline::load(name)?),
+            None => None,
+        };
 
         Ok(ConsoleTestState {
             log_out,
@@ -111,10 +128,13 @@ pub fn new(opts: &TestOpts) -> io::Result<ConsoleTestState> {
             passed: 0,
             failed: 0,
             ignored: 0,
+            flaky: 0,
             filtered_out: 0,
             measured: 0,
             exec_time: None,
             metrics: MetricMap::new(),
+            baseline,
+            bench_samples: Baseline::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
             ignores: Vec::new(),
@@ -171,6 +191,12 @@ pub fn write_log_result(
         self.write_log(|| "\n")
     }
 
+    /// Compares the results of a benchmark against the baseline, if there is one
+    /// and it contains the benchmark.
+    pub fn compare_to_baseline(&self, desc: &TestDesc, bs: &BenchSamples) -> Option<Comparison> {
+        self.baseline.as_ref()?.compare(desc.name.as_slice(), &bs.samples)
+    }
+
     fn current_test_count(&self) -> usize {
         self.passed + self.failed + self.ignored + self.measured
     }
@@ -184,7 +210,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
     };
 
     let mut out: Box<dyn OutputFormatter> = match opts.format {
-        OutputFormat::Pretty | OutputFormat::Junit => {
+        OutputFormat::Pretty | OutputFormat::Junit | OutputFormat::Tap | OutputFormat::Github => {
             Box::new(PrettyFormatter::new(output, false, 0, false, None))
         }
         OutputFormat::Terse => Box::new(TerseFormatter::new(output, false, 0, false)),
@@ -225,6 +251,9 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
     match completed_test.result {
         TestResult::TrOk => {
             st.passed += 1;
+            if completed_test.attempt > 1 {
+                st.flaky += 1;
+            }
             st.not_failures.push((test, stdout));
         }
         TestResult::TrIgnored => {
@@ -237,6 +266,7 @@ fn handle_test_result(st: &mut ConsoleTestState, completed_test: CompletedTest)
                 bs.ns_iter_summ.median,
                 bs.ns_iter_summ.max - bs.ns_iter_summ.min,
             );
+            st.bench_samples.insert(test.name.as_slice(), bs.samples);
             st.measured += 1
         }
         TestResult::TrFailed => {
@@ -279,10 +309,21 @@ fn on_test_event(
             let exec_time = &completed_test.exec_time;
             let stdout = &completed_test.stdout;
 
+            let attempt = completed_test.attempt;
+
             st.write_log_result(test, result, exec_time.as_ref())?;
-            out.write_result(test, result, exec_time.as_ref(), stdout, st)?;
+            out.write_result(test, result, exec_time.as_ref(), stdout, attempt, st)?;
             handle_test_result(st, completed_test);
         }
+        TestEvent::TeRetry(completed_test) => {
+            let test = &completed_test.desc;
+            let result = &completed_test.result;
+            let exec_time = &completed_test.exec_time;
+            let stdout = &completed_test.stdout;
+            let attempt = completed_test.attempt;
+
+            out.write_retry(test, result, exec_time.as_ref(), stdout, attempt)?;
+        }
     }
 
     Ok(())
@@ -317,6 +358,14 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
         }
         OutputFormat::Json => Box::new(JsonFormatter::new(output)),
         OutputFormat::Junit => Box::new(JunitFormatter::new(output)),
+        OutputFormat::Tap => Box::new(TapFormatter::new(output)),
+        OutputFormat::Github => Box::new(GithubFormatter::new(PrettyFormatter::new(
+            output,
+            opts.use_color(),
+            max_name_len,
+            is_multithreaded,
+            opts.time_options,
+        ))),
     };
     let mut st = ConsoleTestState::new(opts)?;
 
@@ -329,6 +378,10 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
     run_tests(opts, tests, |x| on_test_event(&x, &mut st, &mut *out))?;
     st.exec_time = start_time.map(|t| TestSuiteExecTime(t.elapsed()));
 
+    if let Some(ref name) = opts.save_baseline {
+        st.bench_samples.save(name)?;
+    }
+
     assert!(opts.fail_fast || st.current_test_count() == st.total);
 
     out.write_run_finish(&st)
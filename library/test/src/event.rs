@@ -12,6 +12,9 @@ pub struct CompletedTest {
     pub result: TestResult,
     pub exec_time: Option<TestExecTime>,
     pub stdout: Vec<u8>,
+    /// The number of the attempt that produced this result, starting at 1.
+    /// Failing tests are attempted again when `--retries` is given.
+    pub attempt: usize,
 }
 
 impl CompletedTest {
@@ -22,7 +25,7 @@ pub fn new(
         exec_time: Option<TestExecTime>,
         stdout: Vec<u8>,
     ) -> Self {
-        Self { id, desc, result, exec_time, stdout }
+        Self { id, desc, result, exec_time, stdout, attempt: 1 }
     }
 }
 
@@ -31,6 +34,8 @@ pub enum TestEvent {
     TeFiltered(usize, Option<u64>),
     TeWait(TestDesc),
     TeResult(CompletedTest),
+    /// A failed attempt of a test that is going to be run again.
+    TeRetry(CompletedTest),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
 }
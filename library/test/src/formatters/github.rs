@@ -0,0 +1,154 @@
+use std::{io, io::prelude::Write};
+
+use super::{OutputFormatter, PrettyFormatter};
+use crate::{
+    console::{ConsoleTestDiscoveryState, ConsoleTestState},
+    test_result::TestResult,
+    time,
+    types::TestDesc,
+};
+
+/// Formatter for GitHub Actions.
+///
+/// The output is the same as for the pretty formatter, with an `::error` workflow command
+/// after every failed test, so that the failure is annotated at the location of the panic,
+/// or of the test if the location of the panic isn't known.
+pub(crate) struct GithubFormatter<T> {
+    pretty: PrettyFormatter<T>,
+}
+
+impl<T: Write> GithubFormatter<T> {
+    pub fn new(pretty: PrettyFormatter<T>) -> Self {
+        Self { pretty }
+    }
+
+    fn write_annotation(
+        &mut self,
+        desc: &TestDesc,
+        message: Option<&str>,
+        stdout: &[u8],
+    ) -> io::Result<()> {
+        let stdout = String::from_utf8_lossy(stdout);
+        let panic = find_panic(&stdout);
+
+        let location = match panic {
+            Some(PanicLocation { file, line, col, .. }) => Some((file, line, col)),
+            None if !desc.source_file.is_empty() => {
+                Some((desc.source_file, desc.start_line, desc.start_col))
+            }
+            None => None,
+        };
+        let mut properties = String::new();
+        if let Some((file, line, col)) = location {
+            properties.push_str(&format!("file={},line={line},col={col},", escape_property(file)));
+        }
+        let title = format!("test {} failed", desc.name);
+        properties.push_str(&format!("title={}", escape_property(&title)));
+
+        let message = match (message, panic) {
+            (Some(message), _) => message,
+            (None, Some(PanicLocation { message, .. })) => message,
+            (None, None) => "test failed",
+        };
+        self.pretty.write_plain(format!("::error {properties}::{}\n", escape_data(message)))
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PanicLocation<'a> {
+    file: &'a str,
+    line: usize,
+    col: usize,
+    message: &'a str,
+}
+
+/// Finds the first panic in the output of a test, which the default panic hook prints as
+/// `thread '<name>' panicked at <file>:<line>:<col>:` followed by the panic message.
+fn find_panic(output: &str) -> Option<PanicLocation<'_>> {
+    let mut lines = output.lines();
+    let location = lines.find_map(|line| {
+        let (_, location) = line.strip_prefix("thread '")?.split_once("' panicked at ")?;
+        location.strip_suffix(':')
+    })?;
+    // The file name may contain colons itself, so split from the end.
+    let mut parts = location.rsplitn(3, ':');
+    let col = parts.next()?.parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+
+    // The message is on the next line. Messages that span several lines are shortened to
+    // their first line.
+    let message = lines.next().unwrap_or("");
+    Some(PanicLocation { file, line, col, message })
+}
+
+/// Escapes the message of a workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+/// Escapes a property value of a workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+impl<T: Write> OutputFormatter for GithubFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        self.pretty.write_discovery_start()
+    }
+
+    fn write_test_discovered(&mut self, desc: &TestDesc, test_type: &str) -> io::Result<()> {
+        self.pretty.write_test_discovered(desc, test_type)
+    }
+
+    fn write_discovery_finish(&mut self, state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        self.pretty.write_discovery_finish(state)
+    }
+
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        self.pretty.write_run_start(test_count, shuffle_seed)
+    }
+
+    fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.pretty.write_test_start(desc)
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.pretty.write_timeout(desc)
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        attempt: usize,
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        self.pretty.write_result(desc, result, exec_time, stdout, attempt, state)?;
+        match *result {
+            TestResult::TrFailed => self.write_annotation(desc, None, stdout),
+            TestResult::TrFailedMsg(ref msg) => self.write_annotation(desc, Some(msg), stdout),
+            TestResult::TrTimedFail => {
+                self.write_annotation(desc, Some("time limit exceeded"), stdout)
+            }
+            TestResult::TrOk | TestResult::TrIgnored | TestResult::TrBench(_) => Ok(()),
+        }
+    }
+
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        attempt: usize,
+    ) -> io::Result<()> {
+        self.pretty.write_retry(desc, result, exec_time, stdout, attempt)
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        self.pretty.write_run_finish(state)
+    }
+}
@@ -120,6 +120,7 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        attempt: usize,
         state: &ConsoleTestState,
     ) -> io::Result<()> {
         let display_stdout = state.options.display_output || *result != TestResult::TrOk;
@@ -128,11 +129,32 @@ fn write_result(
         } else {
             None
         };
+        // Tests that were retried also report how many attempts they took.
+        let attempts_json =
+            if attempt > 1 { format!(r#", "attempts": {attempt}"#) } else { String::new() };
         match *result {
+            TestResult::TrOk if attempt > 1 => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "ok",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""flaky": true{attempts_json}"#)),
+            ),
+
             TestResult::TrOk => {
                 self.write_event("test", desc.name.as_slice(), "ok", exec_time, stdout, None)
             }
 
+            TestResult::TrFailed if attempt > 1 => self.write_event(
+                "test",
+                desc.name.as_slice(),
+                "failed",
+                exec_time,
+                stdout,
+                Some(&*format!(r#""attempts": {attempt}"#)),
+            ),
+
             TestResult::TrFailed => {
                 self.write_event("test", desc.name.as_slice(), "failed", exec_time, stdout, None)
             }
@@ -143,7 +165,7 @@ fn write_result(
                 "failed",
                 exec_time,
                 stdout,
-                Some(r#""reason": "time limit exceeded""#),
+                Some(&*format!(r#""reason": "time limit exceeded"{attempts_json}"#)),
             ),
 
             TestResult::TrFailedMsg(ref m) => self.write_event(
@@ -152,7 +174,7 @@ fn write_result(
                 "failed",
                 exec_time,
                 stdout,
-                Some(&*format!(r#""message": "{}""#, EscapedString(m))),
+                Some(&*format!(r#""message": "{}"{attempts_json}"#, EscapedString(m))),
             ),
 
             TestResult::TrIgnored => self.write_event(
@@ -175,18 +197,51 @@ fn write_result(
                 } else {
                     format!(r#", "mib_per_second": {}"#, bs.mb_s)
                 };
+                let baseline = match state.compare_to_baseline(desc, bs) {
+                    Some(comparison) => format!(
+                        ", \"baseline\": {{ \
+                         \"mean\": {}, \
+                         \"change_percent\": {:.2}, \
+                         \"change\": \"{}\" }}",
+                        comparison.baseline_mean as usize,
+                        comparison.change_pct,
+                        comparison.change.as_str()
+                    ),
+                    None => String::new(),
+                };
                 let name = EscapedString(desc.name.as_slice());
 
                 self.writeln_message(&format!(
                     "{{ \"type\": \"bench\", \
                      \"name\": \"{name}\", \
                      \"median\": {median}, \
-                     \"deviation\": {deviation}{mbps} }}\n",
+                     \"deviation\": {deviation}{mbps}{baseline} }}\n",
                 ))
             }
         }
     }
 
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        attempt: usize,
+    ) -> io::Result<()> {
+        let stdout = if !stdout.is_empty() { Some(String::from_utf8_lossy(stdout)) } else { None };
+        let extra = match *result {
+            TestResult::TrFailedMsg(ref m) => {
+                format!(r#""attempt": {attempt}, "message": "{}""#, EscapedString(m))
+            }
+            TestResult::TrTimedFail => {
+                format!(r#""attempt": {attempt}, "reason": "time limit exceeded""#)
+            }
+            _ => format!(r#""attempt": {attempt}"#),
+        };
+        self.write_event("test", desc.name.as_slice(), "retry", exec_time, stdout, Some(&extra))
+    }
+
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         let name = EscapedString(desc.name.as_slice());
         let newline = "\n";
@@ -202,6 +257,8 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let ignored = state.ignored;
         let measured = state.measured;
         let filtered_out = state.filtered_out;
+        let flaky_json =
+            if state.flaky > 0 { format!(r#", "flaky": {}"#, state.flaky) } else { String::new() };
         let exec_time_json = if let Some(ref exec_time) = state.exec_time {
             format!(r#", "exec_time": {}"#, exec_time.0.as_secs_f64())
         } else {
@@ -210,7 +267,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
         let newline = "\n";
 
         self.writeln_message(&format!(
-            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{exec_time_json} }}{newline}"#
+            r#"{{ "type": "suite", "event": "{event}", "passed": {passed}, "failed": {failed}, "ignored": {ignored}, "measured": {measured}, "filtered_out": {filtered_out}{flaky_json}{exec_time_json} }}{newline}"#
         ))?;
 
         Ok(state.failed == 0)
@@ -219,7 +276,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
 /// A formatting utility used to print strings with characters in need of escaping.
 /// Base code taken form `libserialize::json::escape_str`
-struct EscapedString<S: AsRef<str>>(S);
+pub(crate) struct EscapedString<S: AsRef<str>>(pub(crate) S);
 
 impl<S: AsRef<str>> std::fmt::Display for EscapedString<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> ::std::fmt::Result {
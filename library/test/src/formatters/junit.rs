@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::io::{self, prelude::Write};
 use std::time::Duration;
 
@@ -9,14 +10,22 @@
     types::{TestDesc, TestType},
 };
 
+/// A failed attempt of a test that was retried.
+struct FailedAttempt {
+    result: TestResult,
+    stdout: Vec<u8>,
+}
+
 pub struct JunitFormatter<T> {
     out: OutputLocation<T>,
-    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
+    results: Vec<(TestDesc, TestResult, Duration, Vec<u8>, Vec<FailedAttempt>)>,
+    /// Failed attempts of tests whose final result isn't known yet, by test name.
+    retried: HashMap<String, Vec<FailedAttempt>>,
 }
 
 impl<T: Write> JunitFormatter<T> {
     pub fn new(out: OutputLocation<T>) -> Self {
-        Self { out, results: Vec::new() }
+        Self { out, results: Vec::new(), retried: HashMap::new() }
     }
 
     fn write_message(&mut self, s: &str) -> io::Result<()> {
@@ -24,6 +33,29 @@ fn write_message(&mut self, s: &str) -> io::Result<()> {
 
         self.out.write_all(s.as_ref())
     }
+
+    /// Writes the failed attempts of a retried test, using the `flakyFailure` element if the
+    /// test eventually passed and `rerunFailure` otherwise, like Maven Surefire does.
+    fn write_failed_attempts(&mut self, attempts: &[FailedAttempt], flaky: bool) -> io::Result<()> {
+        let element = if flaky { "flakyFailure" } else { "rerunFailure" };
+        for FailedAttempt { result, stdout } in attempts {
+            let attributes = match result {
+                TestResult::TrFailedMsg(m) => format!("message=\"{m}\" type=\"assert\""),
+                TestResult::TrTimedFail => "type=\"timeout\"".to_string(),
+                _ => "type=\"assert\"".to_string(),
+            };
+            if stdout.is_empty() {
+                self.write_message(&format!("<{element} {attributes}/>"))?;
+            } else {
+                self.write_message(&format!("<{element} {attributes}>"))?;
+                self.write_message("<system-out>")?;
+                self.write_message(&str_to_cdata(&String::from_utf8_lossy(stdout)))?;
+                self.write_message("</system-out>")?;
+                self.write_message(&format!("</{element}>"))?;
+            }
+        }
+        Ok(())
+    }
 }
 
 fn str_to_cdata(s: &str) -> String {
@@ -76,13 +108,28 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        _attempt: usize,
         _state: &ConsoleTestState,
     ) -> io::Result<()> {
         // Because the testsuite node holds some of the information as attributes, we can't write it
         // until all of the tests have finished. Instead of writing every result as they come in, we add
         // them to a Vec and write them all at once when run is complete.
         let duration = exec_time.map(|t| t.0).unwrap_or_default();
-        self.results.push((desc.clone(), result.clone(), duration, stdout.to_vec()));
+        let attempts = self.retried.remove(desc.name.as_slice()).unwrap_or_default();
+        self.results.push((desc.clone(), result.clone(), duration, stdout.to_vec(), attempts));
+        Ok(())
+    }
+
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        _exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        _attempt: usize,
+    ) -> io::Result<()> {
+        let attempt = FailedAttempt { result: result.clone(), stdout: stdout.to_vec() };
+        self.retried.entry(desc.name.as_slice().to_owned()).or_default().push(attempt);
         Ok(())
     }
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
@@ -97,7 +144,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
              >",
             state.failed, state.total, state.ignored
         ))?;
-        for (desc, result, duration, stdout) in std::mem::take(&mut self.results) {
+        for (desc, result, duration, stdout, attempts) in std::mem::take(&mut self.results) {
             let (class_name, test_name) = parse_class_name(&desc);
             match result {
                 TestResult::TrIgnored => { /* no-op */ }
@@ -110,6 +157,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         duration.as_secs_f64()
                     ))?;
                     self.write_message("<failure type=\"assert\"/>")?;
+                    self.write_failed_attempts(&attempts, false)?;
                     if !stdout.is_empty() {
                         self.write_message("<system-out>")?;
                         self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
@@ -127,6 +175,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         duration.as_secs_f64()
                     ))?;
                     self.write_message(&format!("<failure message=\"{m}\" type=\"assert\"/>"))?;
+                    self.write_failed_attempts(&attempts, false)?;
                     if !stdout.is_empty() {
                         self.write_message("<system-out>")?;
                         self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
@@ -144,6 +193,7 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         duration.as_secs_f64()
                     ))?;
                     self.write_message("<failure type=\"timeout\"/>")?;
+                    self.write_failed_attempts(&attempts, false)?;
                     self.write_message("</testcase>")?;
                 }
 
@@ -163,12 +213,17 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
                         test_name,
                         duration.as_secs_f64()
                     ))?;
-                    if stdout.is_empty() || !state.options.display_output {
+                    let display_stdout = !stdout.is_empty() && state.options.display_output;
+                    if !display_stdout && attempts.is_empty() {
                         self.write_message("/>")?;
                     } else {
-                        self.write_message("><system-out>")?;
-                        self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
-                        self.write_message("</system-out>")?;
+                        self.write_message(">")?;
+                        self.write_failed_attempts(&attempts, true)?;
+                        if display_stdout {
+                            self.write_message("<system-out>")?;
+                            self.write_message(&str_to_cdata(&String::from_utf8_lossy(&stdout)))?;
+                            self.write_message("</system-out>")?;
+                        }
                         self.write_message("</testcase>")?;
                     }
                 }
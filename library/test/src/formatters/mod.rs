@@ -7,14 +7,18 @@
     types::{TestDesc, TestName},
 };
 
+mod github;
 mod json;
 mod junit;
 mod pretty;
+mod tap;
 mod terse;
 
+pub(crate) use self::github::GithubFormatter;
 pub(crate) use self::json::JsonFormatter;
 pub(crate) use self::junit::JunitFormatter;
 pub(crate) use self::pretty::PrettyFormatter;
+pub(crate) use self::tap::TapFormatter;
 pub(crate) use self::terse::TerseFormatter;
 
 pub(crate) trait OutputFormatter {
@@ -25,14 +29,26 @@ pub(crate) trait OutputFormatter {
     fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()>;
     fn write_test_start(&mut self, desc: &TestDesc) -> io::Result<()>;
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()>;
+    /// Writes the final result of a test. `attempt` is greater than 1 if the test was
+    /// retried after failing.
     fn write_result(
         &mut self,
         desc: &TestDesc,
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         stdout: &[u8],
+        attempt: usize,
         state: &ConsoleTestState,
     ) -> io::Result<()>;
+    /// Writes the result of a failed attempt of a test that is going to be run again.
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        attempt: usize,
+    ) -> io::Result<()>;
     fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool>;
 }
 
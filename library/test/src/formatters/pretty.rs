@@ -4,6 +4,7 @@
 use crate::{
     bench::fmt_bench_samples,
     console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation},
+    helpers::baseline::{Change, Comparison},
     term,
     test_result::TestResult,
     time,
@@ -45,6 +46,10 @@ pub fn write_failed(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED", term::color::RED)
     }
 
+    pub fn write_flaky(&mut self) -> io::Result<()> {
+        self.write_short_result("ok (flaky)", term::color::YELLOW)
+    }
+
     pub fn write_ignored(&mut self, message: Option<&'static str>) -> io::Result<()> {
         if let Some(message) = message {
             self.write_short_result(&format!("ignored, {message}"), term::color::YELLOW)
@@ -61,6 +66,16 @@ pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
 
+    pub fn write_comparison(&mut self, comparison: &Comparison) -> io::Result<()> {
+        self.write_plain(format!(" (change: {:+.2}%, ", comparison.change_pct))?;
+        match comparison.change {
+            Change::Regressed => self.write_pretty("regressed", term::color::RED)?,
+            Change::Improved => self.write_pretty("improved", term::color::GREEN)?,
+            Change::Unchanged => self.write_plain("unchanged")?,
+        }
+        self.write_plain(")")
+    }
+
     pub fn write_short_result(
         &mut self,
         result: &str,
@@ -236,19 +251,24 @@ fn write_result(
         result: &TestResult,
         exec_time: Option<&time::TestExecTime>,
         _: &[u8],
-        _: &ConsoleTestState,
+        attempt: usize,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         if self.is_multithreaded {
             self.write_test_name(desc)?;
         }
 
         match *result {
+            TestResult::TrOk if attempt > 1 => self.write_flaky()?,
             TestResult::TrOk => self.write_ok()?,
             TestResult::TrFailed | TestResult::TrFailedMsg(_) => self.write_failed()?,
             TestResult::TrIgnored => self.write_ignored(desc.ignore_message)?,
             TestResult::TrBench(ref bs) => {
                 self.write_bench()?;
                 self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(comparison) = state.compare_to_baseline(desc, bs) {
+                    self.write_comparison(&comparison)?;
+                }
             }
             TestResult::TrTimedFail => self.write_time_failed()?,
         }
@@ -257,6 +277,24 @@ fn write_result(
         self.write_plain("\n")
     }
 
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        _: &TestResult,
+        exec_time: Option<&time::TestExecTime>,
+        _: &[u8],
+        attempt: usize,
+    ) -> io::Result<()> {
+        if self.is_multithreaded {
+            self.write_test_name(desc)?;
+        }
+
+        self.write_failed()?;
+        self.write_plain(format!(" (attempt {attempt}, retrying)"))?;
+        self.write_time(desc, exec_time)?;
+        self.write_plain("\n")
+    }
+
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         self.write_plain(format!(
             "test {} has been running for over {} seconds\n",
@@ -296,6 +334,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.write_plain(s)?;
 
+        if state.flaky > 0 {
+            self.write_plain(format!("; {} flaky", state.flaky))?;
+        }
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!("; finished in {exec_time}");
             self.write_plain(time_str)?;
@@ -0,0 +1,178 @@
+use std::{io, io::prelude::Write};
+
+use super::json::EscapedString;
+use super::OutputFormatter;
+use crate::{
+    bench::fmt_bench_samples,
+    console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation},
+    test_result::TestResult,
+    time,
+    types::TestDesc,
+};
+
+/// Formatter for the Test Anything Protocol, version 13.
+///
+/// Every test result is a test point. Additional information, such as the output of failed
+/// tests, is written as a YAML diagnostic block after the test point. The plan is written at
+/// the end, once the number of test points is known.
+pub(crate) struct TapFormatter<T> {
+    out: OutputLocation<T>,
+    /// Number of test points written so far.
+    test_count: usize,
+}
+
+impl<T: Write> TapFormatter<T> {
+    pub fn new(out: OutputLocation<T>) -> Self {
+        Self { out, test_count: 0 }
+    }
+
+    fn write_plain<S: AsRef<str>>(&mut self, s: S) -> io::Result<()> {
+        let s = s.as_ref();
+        self.out.write_all(s.as_bytes())?;
+        self.out.flush()
+    }
+
+    fn write_test_point(
+        &mut self,
+        ok: bool,
+        desc: &TestDesc,
+        directive: Option<String>,
+    ) -> io::Result<()> {
+        self.test_count += 1;
+        let status = if ok { "ok" } else { "not ok" };
+        // `#` starts a directive, so it must be escaped in the description.
+        let name = desc.name.as_slice().replace('\\', "\\\\").replace('#', "\\#");
+        let directive = directive.map(|d| format!(" # {d}")).unwrap_or_default();
+        self.write_plain(format!("{status} {} - {name}{directive}\n", self.test_count))
+    }
+
+    /// Writes a YAML diagnostic block. The values are written as double-quoted strings,
+    /// whose escaping rules are compatible with the ones of JSON.
+    fn write_diagnostics(&mut self, fields: &[(&str, &str)]) -> io::Result<()> {
+        if fields.is_empty() {
+            return Ok(());
+        }
+        self.write_plain("  ---\n")?;
+        for (key, value) in fields {
+            self.write_plain(format!("  {key}: \"{}\"\n", EscapedString(value)))?;
+        }
+        self.write_plain("  ...\n")
+    }
+}
+
+impl<T: Write> OutputFormatter for TapFormatter<T> {
+    fn write_discovery_start(&mut self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_test_discovered(&mut self, _desc: &TestDesc, _test_type: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_discovery_finish(&mut self, _state: &ConsoleTestDiscoveryState) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::NotFound, "Not yet implemented!"))
+    }
+
+    fn write_run_start(&mut self, test_count: usize, shuffle_seed: Option<u64>) -> io::Result<()> {
+        self.write_plain("TAP version 13\n")?;
+        self.write_plain(format!("# running {test_count} tests\n"))?;
+        if let Some(shuffle_seed) = shuffle_seed {
+            self.write_plain(format!("# shuffle seed: {shuffle_seed}\n"))?;
+        }
+        Ok(())
+    }
+
+    fn write_test_start(&mut self, _desc: &TestDesc) -> io::Result<()> {
+        // We do not output anything on test start.
+        Ok(())
+    }
+
+    fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        self.write_plain(format!(
+            "# test {} has been running for over {} seconds\n",
+            desc.name,
+            time::TEST_WARN_TIMEOUT_S
+        ))
+    }
+
+    fn write_result(
+        &mut self,
+        desc: &TestDesc,
+        result: &TestResult,
+        _exec_time: Option<&time::TestExecTime>,
+        stdout: &[u8],
+        attempt: usize,
+        state: &ConsoleTestState,
+    ) -> io::Result<()> {
+        let stdout = String::from_utf8_lossy(stdout);
+        let attempts = attempt.to_string();
+        let mut fields = Vec::new();
+        match *result {
+            TestResult::TrOk => {
+                self.write_test_point(true, desc, None)?;
+                if attempt > 1 {
+                    fields.push(("flaky", "true"));
+                    fields.push(("attempts", &*attempts));
+                }
+                if state.options.display_output && !stdout.is_empty() {
+                    fields.push(("stdout", &*stdout));
+                }
+            }
+            TestResult::TrIgnored => {
+                let directive = match desc.ignore_message {
+                    Some(msg) => format!("SKIP {msg}"),
+                    None => "SKIP".to_string(),
+                };
+                self.write_test_point(true, desc, Some(directive))?;
+            }
+            TestResult::TrBench(ref bs) => {
+                self.write_test_point(true, desc, None)?;
+                let samples = fmt_bench_samples(bs);
+                let mut fields = vec![("bench", samples)];
+                if let Some(comparison) = state.compare_to_baseline(desc, bs) {
+                    fields.push(("change", format!("{:+.2}%", comparison.change_pct)));
+                    fields.push(("verdict", comparison.change.as_str().to_string()));
+                }
+                let fields = fields.iter().map(|(k, v)| (*k, &**v)).collect::<Vec<_>>();
+                return self.write_diagnostics(&fields);
+            }
+            TestResult::TrFailed | TestResult::TrFailedMsg(_) | TestResult::TrTimedFail => {
+                self.write_test_point(false, desc, None)?;
+                match *result {
+                    TestResult::TrFailedMsg(ref msg) => fields.push(("message", &**msg)),
+                    TestResult::TrTimedFail => fields.push(("message", "time limit exceeded")),
+                    _ => {}
+                }
+                if attempt > 1 {
+                    fields.push(("attempts", &*attempts));
+                }
+                if !stdout.is_empty() {
+                    fields.push(("stdout", &*stdout));
+                }
+            }
+        }
+        self.write_diagnostics(&fields)
+    }
+
+    fn write_retry(
+        &mut self,
+        desc: &TestDesc,
+        _result: &TestResult,
+        _exec_time: Option<&time::TestExecTime>,
+        _stdout: &[u8],
+        attempt: usize,
+    ) -> io::Result<()> {
+        // Only the final attempt of a test is a test point.
+        self.write_plain(format!("# test {} failed on attempt {attempt}, retrying\n", desc.name))
+    }
+
+    fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
+        self.write_plain(format!("1..{}\n", self.test_count))?;
+        self.write_plain(format!(
+            "# {} passed; {} failed; {} ignored; {} measured; {} filtered out\n",
+            state.passed, state.failed, state.ignored, state.measured, state.filtered_out
+        ))?;
+
+        Ok(state.failed == 0)
+    }
+}
@@ -4,6 +4,7 @@
 use crate::{
     bench::fmt_bench_samples,
     console::{ConsoleTestDiscoveryState, ConsoleTestState, OutputLocation},
+    helpers::baseline::{Change, Comparison},
     term,
     test_result::TestResult,
     time,
@@ -59,6 +60,16 @@ pub fn write_bench(&mut self) -> io::Result<()> {
         self.write_pretty("bench", term::color::CYAN)
     }
 
+    pub fn write_comparison(&mut self, comparison: &Comparison) -> io::Result<()> {
+        self.write_plain(format!(" (change: {:+.2}%, ", comparison.change_pct))?;
+        match comparison.change {
+            Change::Regressed => self.write_pretty("regressed", term::color::RED)?,
+            Change::Improved => self.write_pretty("improved", term::color::GREEN)?,
+            Change::Unchanged => self.write_plain("unchanged")?,
+        }
+        self.write_plain(")")
+    }
+
     pub fn write_short_result(
         &mut self,
         result: &str,
@@ -208,7 +219,8 @@ fn write_result(
         result: &TestResult,
         _: Option<&time::TestExecTime>,
         _: &[u8],
-        _: &ConsoleTestState,
+        _: usize,
+        state: &ConsoleTestState,
     ) -> io::Result<()> {
         match *result {
             TestResult::TrOk => self.write_ok(),
@@ -221,11 +233,27 @@ fn write_result(
                     self.write_test_name(desc)?;
                 }
                 self.write_bench()?;
-                self.write_plain(format!(": {}\n", fmt_bench_samples(bs)))
+                self.write_plain(format!(": {}", fmt_bench_samples(bs)))?;
+                if let Some(comparison) = state.compare_to_baseline(desc, bs) {
+                    self.write_comparison(&comparison)?;
+                }
+                self.write_plain("\n")
             }
         }
     }
 
+    fn write_retry(
+        &mut self,
+        _: &TestDesc,
+        _: &TestResult,
+        _: Option<&time::TestExecTime>,
+        _: &[u8],
+        _: usize,
+    ) -> io::Result<()> {
+        // Only the final attempt of a test is shown.
+        Ok(())
+    }
+
     fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
         self.write_plain(format!(
             "test {} has been running for over {} seconds\n",
@@ -259,6 +287,10 @@ fn write_run_finish(&mut self, state: &ConsoleTestState) -> io::Result<bool> {
 
         self.write_plain(s)?;
 
+        if state.flaky > 0 {
+            self.write_plain(format!("; {} flaky", state.flaky))?;
+        }
+
         if let Some(ref exec_time) = state.exec_time {
             let time_str = format!("; finished in {exec_time}");
             self.write_plain(time_str)?;
@@ -0,0 +1,205 @@
+//! Benchmark baselines.
+//!
+//! A baseline is a named snapshot of the samples of every benchmark of a test binary.
+//! Later runs can be compared against it to tell whether a benchmark got faster or slower.
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::stats::Stats;
+
+/// Relative changes below this, in percent, are reported as unchanged, even if they are
+/// statistically significant.
+const NOISE_THRESHOLD_PCT: f64 = 2.0;
+
+const HEADER: &str = "# libtest benchmark baseline, version 1";
+
+/// Samples of the benchmarks, in ns/iter, by benchmark name.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Baseline(BTreeMap<String, Vec<f64>>);
+
+impl Baseline {
+    pub fn new() -> Baseline {
+        Baseline(BTreeMap::new())
+    }
+
+    /// Loads the baseline `name` of the current test binary.
+    pub fn load(name: &str) -> io::Result<Baseline> {
+        let path = baseline_path(name)?;
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            if e.kind() == io::ErrorKind::NotFound {
+                io::Error::new(
+                    e.kind(),
+                    format!("no benchmark baseline named `{name}` at {}", path.display()),
+                )
+            } else {
+                e
+            }
+        })?;
+        Baseline::parse(&contents).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("malformed benchmark baseline at {}", path.display()),
+            )
+        })
+    }
+
+    /// Saves the benchmarks to the baseline `name` of the current test binary.
+    /// Benchmarks that are already in the baseline but not in `self` are kept.
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let mut baseline = match Baseline::load(name) {
+            Ok(baseline) => baseline,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Baseline::new(),
+            Err(e) => return Err(e),
+        };
+        baseline.0.extend(self.0.iter().map(|(name, samples)| (name.clone(), samples.clone())));
+
+        let path = baseline_path(name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, baseline.serialize())
+    }
+
+    pub fn insert(&mut self, name: &str, samples: Vec<f64>) {
+        // Names are stored one per line, and benchmarks without samples can't be compared.
+        if !name.contains('\n') && !samples.is_empty() {
+            self.0.insert(name.to_owned(), samples);
+        }
+    }
+
+    /// Compares the samples of the benchmark `name` against the ones in the baseline.
+    /// Returns `None` if the benchmark isn't part of the baseline.
+    pub fn compare(&self, name: &str, samples: &[f64]) -> Option<Comparison> {
+        compare(self.0.get(name)?, samples)
+    }
+
+    /// Every benchmark is a line with its samples, separated by spaces, followed by
+    /// a tab and the name of the benchmark.
+    fn serialize(&self) -> String {
+        let mut output = format!("{HEADER}\n");
+        for (name, samples) in &self.0 {
+            let samples = samples.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            output.push_str(&format!("{}\t{name}\n", samples.join(" ")));
+        }
+        output
+    }
+
+    fn parse(contents: &str) -> Option<Baseline> {
+        let mut lines = contents.lines();
+        if lines.next()? != HEADER {
+            return None;
+        }
+        let mut baseline = Baseline::new();
+        for line in lines {
+            let (samples, name) = line.split_once('\t')?;
+            let samples = samples.split(' ').map(|s| s.parse().ok()).collect::<Option<_>>()?;
+            baseline.0.insert(name.to_owned(), samples);
+        }
+        Some(baseline)
+    }
+}
+
+/// Returns the path of the baseline `name` of the current test binary.
+///
+/// Baselines are stored in `RUST_TEST_BASELINE_DIR` if it is set, and in a `bench-baselines`
+/// directory next to the test binary otherwise.
+fn baseline_path(name: &str) -> io::Result<PathBuf> {
+    let exe = env::current_exe()?;
+    let dir = match env::var_os("RUST_TEST_BASELINE_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => exe.parent().unwrap_or(Path::new(".")).join("bench-baselines"),
+    };
+    Ok(dir.join(format!("{}-{name}.txt", binary_name(&exe))))
+}
+
+/// The name of the test binary, without the hash Cargo appends to it. The hash changes
+/// whenever e.g. the dependencies change, which must not make the baselines unreachable.
+fn binary_name(exe: &Path) -> String {
+    let stem = exe.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    match stem.rsplit_once('-') {
+        Some((name, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            name.to_owned()
+        }
+        _ => stem,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Change {
+    Improved,
+    Regressed,
+    Unchanged,
+}
+
+impl Change {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Change::Improved => "improved",
+            Change::Regressed => "regressed",
+            Change::Unchanged => "unchanged",
+        }
+    }
+}
+
+/// Result of comparing a benchmark against its baseline.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Comparison {
+    /// Mean of the baseline samples, in ns/iter.
+    pub baseline_mean: f64,
+    /// Relative change of the mean, in percent. Positive values mean that
+    /// the benchmark got slower.
+    pub change_pct: f64,
+    pub change: Change,
+}
+
+/// Compares two sets of benchmark samples. A change is only reported if the means differ
+/// significantly according to Welch's t-test, and by more than the noise threshold.
+pub fn compare(baseline: &[f64], samples: &[f64]) -> Option<Comparison> {
+    if baseline.len() < 2 || samples.len() < 2 {
+        return None;
+    }
+    let baseline_mean = baseline.mean();
+    if baseline_mean <= 0.0 {
+        return None;
+    }
+    let change_pct = (samples.mean() - baseline_mean) / baseline_mean * 100.0;
+    let significant =
+        change_pct.abs() >= NOISE_THRESHOLD_PCT && differ_significantly(baseline, samples);
+    let change = if !significant {
+        Change::Unchanged
+    } else if change_pct > 0.0 {
+        Change::Regressed
+    } else {
+        Change::Improved
+    };
+    Some(Comparison { baseline_mean, change_pct, change })
+}
+
+/// Welch's t-test, which doesn't assume that both samples have the same variance.
+/// Returns whether the means differ at the 95% confidence level.
+fn differ_significantly(a: &[f64], b: &[f64]) -> bool {
+    let (na, nb) = (a.len() as f64, b.len() as f64);
+    let (va, vb) = (a.var() / na, b.var() / nb);
+    let se2 = va + vb;
+    if se2 == 0.0 {
+        return a.mean() != b.mean();
+    }
+    let t = (a.mean() - b.mean()).abs() / se2.sqrt();
+    // Welch–Satterthwaite approximation of the degrees of freedom.
+    let df = se2 * se2 / (va * va / (na - 1.0) + vb * vb / (nb - 1.0));
+    t > t_critical_95(df)
+}
+
+/// Approximates the two-sided 95% critical value of Student's t-distribution with `df`
+/// degrees of freedom, using the Cornish-Fisher expansion around the normal distribution.
+/// It is accurate to within 1% from 5 degrees of freedom on, and benchmarks have far more.
+fn t_critical_95(df: f64) -> f64 {
+    const Z: f64 = 1.959963984540054;
+    let (z3, z5, z7) = (Z.powi(3), Z.powi(5), Z.powi(7));
+    Z + (z3 + Z) / (4.0 * df)
+        + (5.0 * z5 + 16.0 * z3 + 3.0 * Z) / (96.0 * df.powi(2))
+        + (3.0 * z7 + 19.0 * z5 + 17.0 * z3 - 15.0 * Z) / (384.0 * df.powi(3))
+}
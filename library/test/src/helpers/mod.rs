@@ -1,6 +1,7 @@
 //! Module with common helpers not directly related to tests
 //! but used in `libtest`.
 
+pub mod baseline;
 pub mod concurrency;
 pub mod exit_code;
 pub mod metrics;
@@ -155,6 +155,10 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Option<Opt
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=unwind.
 pub fn test_main_static(tests: &[&TestDescAndFn]) {
+    // With `--test-timeout` or `--capture-fd`, tests are run in subprocesses even when
+    // panic=unwind.
+    run_spawned_test_if_requested(tests, false);
+
     let args = env::args().collect::<Vec<_>>();
     let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
     test_main(&args, owned_tests, None)
@@ -169,8 +173,16 @@ pub fn test_main_static(tests: &[&TestDescAndFn]) {
 /// This is the entry point for the main function generated by `rustc --test`
 /// when panic=abort.
 pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
-    // If we're being run in SpawnedSecondary mode, run the test here. run_test
-    // will then exit the process.
+    run_spawned_test_if_requested(tests, true);
+
+    let args = env::args().collect::<Vec<_>>();
+    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
+    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
+}
+
+/// If we're being run in SpawnedSecondary mode, run the test here. run_test
+/// will then exit the process.
+fn run_spawned_test_if_requested(tests: &[&TestDescAndFn], panic_abort: bool) {
     if let Ok(name) = env::var(SECONDARY_TEST_INVOKER_VAR) {
         env::remove_var(SECONDARY_TEST_INVOKER_VAR);
 
@@ -192,17 +204,13 @@ pub fn test_main_static_abort(tests: &[&TestDescAndFn]) {
                 if runnable_test.is_dynamic() {
                     panic!("only static tests are supported");
                 }
-                run_test_in_spawned_subprocess(desc, runnable_test);
+                run_test_in_spawned_subprocess(desc, runnable_test, panic_abort);
             }
             Runnable::Bench(_) => {
                 panic!("benchmarks should not be executed into child processes")
             }
         }
     }
-
-    let args = env::args().collect::<Vec<_>>();
-    let owned_tests: Vec<_> = tests.iter().map(make_owned_test).collect();
-    test_main(&args, owned_tests, Some(Options::new().panic_abort(true)))
 }
 
 /// Clones static values for putting into a dynamic vector, which test_main()
@@ -293,6 +301,48 @@ struct TimeoutEntry {
         timeout: Instant,
     }
 
+    type RetryMap<V> = HashMap<TestId, V, BuildHasherDefault<collections::hash_map::DefaultHasher>>;
+
+    struct Retries {
+        max_retries: usize,
+        /// The number of the running attempt of tests that have been retried before.
+        attempts: RetryMap<usize>,
+        /// Copies of the running tests that can be run again if they fail.
+        copies: RetryMap<TestDescAndFn>,
+    }
+
+    impl Retries {
+        /// Keeps a copy of `test` if it would have to be run again after failing.
+        fn prepare(&mut self, id: TestId, test: &TestDescAndFn) {
+            if self.max_retries == 0 {
+                return;
+            }
+            if let Some(testfn) = test.testfn.try_clone() {
+                self.copies.insert(id, TestDescAndFn { desc: test.desc.clone(), testfn });
+            }
+        }
+
+        /// Records the attempt number in `completed_test`, and returns the test to run again
+        /// if it failed and has retries left.
+        fn on_completed(&mut self, completed_test: &mut CompletedTest) -> Option<TestDescAndFn> {
+            let id = completed_test.id;
+            let copy = self.copies.remove(&id);
+            let attempt = self.attempts.get(&id).copied().unwrap_or(1);
+            completed_test.attempt = attempt;
+
+            let failed = match completed_test.result {
+                TrIgnored | TrOk | TrBench(_) => false,
+                TrFailed | TrFailedMsg(_) | TrTimedFail => true,
+            };
+            if !failed || attempt > self.max_retries {
+                return None;
+            }
+            let test = copy?;
+            self.attempts.insert(id, attempt + 1);
+            Some(test)
+        }
+    }
+
     let tests_len = tests.len();
 
     let mut filtered = FilteredTests { tests: Vec::new(), benches: Vec::new(), next_id: 0 };
@@ -343,7 +393,10 @@ struct TimeoutEntry {
     let mut pending = 0;
 
     let (tx, rx) = channel::<CompletedTest>();
-    let run_strategy = if opts.options.panic_abort && !opts.force_run_in_process {
+    let spawn_tests = opts.options.panic_abort
+        || opts.test_timeout.is_some()
+        || (opts.capture_fd && !opts.nocapture);
+    let run_strategy = if spawn_tests && !opts.force_run_in_process {
         RunStrategy::SpawnPrimary
     } else {
         RunStrategy::InProcess
@@ -351,6 +404,11 @@ struct TimeoutEntry {
 
     let mut running_tests: TestMap = HashMap::default();
     let mut timeout_queue: VecDeque<TimeoutEntry> = VecDeque::new();
+    let mut retries = Retries {
+        max_retries: opts.retries,
+        attempts: HashMap::default(),
+        copies: HashMap::default(),
+    };
 
     fn get_timed_out_tests(
         running_tests: &TestMap,
@@ -382,11 +440,19 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let (id, test) = remaining.pop_front().unwrap();
             let event = TestEvent::TeWait(test.desc.clone());
             notify_about_test_event(event)?;
+            retries.prepare(id, &test);
             let join_handle = run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
             // Wait for the test to complete.
             let mut completed_test = rx.recv().unwrap();
             RunningTest { join_handle }.join(&mut completed_test);
 
+            if let Some(test) = retries.on_completed(&mut completed_test) {
+                remaining.push_front((id, test));
+                let event = TestEvent::TeRetry(completed_test);
+                notify_about_test_event(event)?;
+                continue;
+            }
+
             let fail_fast = match completed_test.result {
                 TrIgnored | TrOk | TrBench(_) => false,
                 TrFailed | TrFailedMsg(_) | TrTimedFail => opts.fail_fast,
@@ -408,6 +474,7 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
 
                 let event = TestEvent::TeWait(desc.clone());
                 notify_about_test_event(event)?; //here no pad
+                retries.prepare(id, &test);
                 let join_handle =
                     run_test(opts, !opts.run_tests, id, test, run_strategy, tx.clone());
                 running_tests.insert(id, RunningTest { join_handle });
@@ -442,6 +509,17 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
             let mut completed_test = res.unwrap();
             let running_test = running_tests.remove(&completed_test.id).unwrap();
             running_test.join(&mut completed_test);
+            pending -= 1;
+
+            if let Some(test) = retries.on_completed(&mut completed_test) {
+                let id = completed_test.id;
+                // The next attempt gets a new timeout.
+                timeout_queue.retain(|entry| entry.id != id);
+                remaining.push_front((id, test));
+                let event = TestEvent::TeRetry(completed_test);
+                notify_about_test_event(event)?;
+                continue;
+            }
 
             let fail_fast = match completed_test.result {
                 TrIgnored | TrOk | TrBench(_) => false,
@@ -450,7 +528,6 @@ fn calc_timeout(timeout_queue: &VecDeque<TimeoutEntry>) -> Option<Duration> {
 
             let event = TestEvent::TeResult(completed_test);
             notify_about_test_event(event)?;
-            pending -= 1;
 
             if fail_fast {
                 // Prevent remaining test threads from panicking
@@ -515,6 +592,19 @@ pub fn filter_tests(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> Vec<TestDescA
         RunIgnored::No => {}
     }
 
+    // Only keep the tests of the requested shard. Tests are assigned to shards in the order
+    // of their names, so that the shards don't depend on the order of `tests`.
+    if let Some((index, count)) = opts.shard {
+        let mut by_name: Vec<usize> = (0..filtered.len()).collect();
+        by_name.sort_by_key(|&i| filtered[i].desc.name.as_slice());
+        let mut in_shard = vec![false; filtered.len()];
+        for (position, &i) in by_name.iter().enumerate() {
+            in_shard[i] = position % count == index;
+        }
+        let mut in_shard = in_shard.into_iter();
+        filtered.retain(|_| in_shard.next().unwrap());
+    }
+
     filtered
 }
 
@@ -556,9 +646,16 @@ pub fn run_test(
 
     match testfn.into_runnable() {
         Runnable::Test(runnable_test) => {
+            let mut strategy = strategy;
             if runnable_test.is_dynamic() {
                 match strategy {
                     RunStrategy::InProcess => (),
+                    // A subprocess can't find dynamic tests, so `--test-timeout` and
+                    // `--capture-fd` can't be applied to them. Without panic=abort they can
+                    // still run in-process.
+                    RunStrategy::SpawnPrimary if !opts.options.panic_abort => {
+                        strategy = RunStrategy::InProcess;
+                    }
                     _ => panic!("Cannot run dynamic test fn out-of-process"),
                 };
             }
@@ -567,6 +664,7 @@ pub fn run_test(
             let nocapture = opts.nocapture;
             let time_options = opts.time_options;
             let bench_benchmarks = opts.bench_benchmarks;
+            let test_timeout = opts.test_timeout;
 
             let runtest = move || match strategy {
                 RunStrategy::InProcess => run_test_in_process(
@@ -586,6 +684,7 @@ pub fn run_test(
                     monitor_ch,
                     time_options,
                     bench_benchmarks,
+                    test_timeout,
                 ),
             };
 
@@ -684,6 +783,7 @@ fn spawn_test_subprocess(
     monitor_ch: Sender<CompletedTest>,
     time_opts: Option<time::TestTimeOptions>,
     bench_benchmarks: bool,
+    test_timeout: Option<Duration>,
 ) {
     let (result, test_output, exec_time) = (|| {
         let args = env::args().collect::<Vec<_>>();
@@ -700,7 +800,11 @@ fn spawn_test_subprocess(
         }
 
         let start = report_time.then(Instant::now);
-        let output = match command.output() {
+        let output = match test_timeout {
+            Some(timeout) => output_with_timeout(&mut command, nocapture, timeout),
+            None => command.output().map(|output| (output, false)),
+        };
+        let (output, timed_out) = match output {
             Ok(out) => out,
             Err(e) => {
                 let err = format!("Failed to spawn {} as child for test: {:?}", args[0], e);
@@ -717,6 +821,10 @@ fn spawn_test_subprocess(
         formatters::write_stderr_delimiter(&mut test_output, &desc.name);
         test_output.extend_from_slice(&stderr);
 
+        if timed_out {
+            return (TrFailedMsg("timed out".to_string()), test_output, exec_time);
+        }
+
         let result = match (|| -> Result<TestResult, String> {
             let exit_code = get_exit_code(status)?;
             Ok(get_result_from_exit_code(&desc, exit_code, &time_opts, &exec_time))
@@ -735,7 +843,88 @@ fn spawn_test_subprocess(
     monitor_ch.send(message).unwrap();
 }
 
-fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -> ! {
+/// Like `Command::output`, but kills the child once it has been running for longer
+/// than `timeout`. Also returns whether the child had to be killed.
+fn output_with_timeout(
+    command: &mut Command,
+    nocapture: bool,
+    timeout: Duration,
+) -> io::Result<(process::Output, bool)> {
+    if !nocapture {
+        command.stdout(process::Stdio::piped());
+        command.stderr(process::Stdio::piped());
+    }
+    let mut child = command.stdin(process::Stdio::null()).spawn()?;
+
+    // Drain the pipes while waiting, so that the child can't block on a full pipe. The output is
+    // collected as it arrives: after a timeout, processes spawned by the child may keep the pipes
+    // open, so we report what was written so far instead of waiting for the end.
+    type Reader = (Arc<Mutex<Vec<u8>>>, thread::JoinHandle<()>);
+    fn read_to_end(mut pipe: impl io::Read + Send + 'static) -> Reader {
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let reader_buf = buf.clone();
+        let handle = thread::spawn(move || {
+            let mut chunk = [0; 4096];
+            loop {
+                match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(n) => reader_buf.lock().unwrap().extend_from_slice(&chunk[..n]),
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => break,
+                }
+            }
+        });
+        (buf, handle)
+    }
+    let stdout = child.stdout.take().map(read_to_end);
+    let stderr = child.stderr.take().map(read_to_end);
+
+    let deadline = Instant::now() + timeout;
+    let mut timed_out = false;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            timed_out = true;
+            // This only fails if the child has exited in the meantime.
+            let _ = child.kill();
+            break child.wait()?;
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(10)));
+    };
+
+    let join = |reader: Option<Reader>| {
+        let Some((buf, handle)) = reader else { return Vec::new() };
+        if !timed_out {
+            let _ = handle.join();
+        }
+        let mut buf = buf.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut *buf)
+    };
+    let output = process::Output { status, stdout: join(stdout), stderr: join(stderr) };
+    Ok((output, timed_out))
+}
+
+fn run_test_in_spawned_subprocess(
+    desc: TestDesc,
+    runnable_test: RunnableTest,
+    panic_abort: bool,
+) -> ! {
+    if !panic_abort {
+        // The test may catch its own panics, so only a panic that escapes it makes it fail.
+        let result = fold_err(catch_unwind(AssertUnwindSafe(|| runnable_test.run())));
+        let test_result = match result {
+            Ok(()) => calc_result(&desc, Ok(()), &None, &None),
+            Err(e) => calc_result(&desc, Err(e.as_ref()), &None, &None),
+        };
+        if let TrFailedMsg(msg) = &test_result {
+            eprintln!("{msg}");
+        }
+        exit_with_test_result(&test_result)
+    }
+
     let builtin_panic_hook = panic::take_hook();
     let record_result = Arc::new(move |panic_info: Option<&'_ PanicInfo<'_>>| {
         let test_result = match panic_info {
@@ -753,11 +942,7 @@ fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -
             builtin_panic_hook(info);
         }
 
-        if let TrOk = test_result {
-            process::exit(test_result::TR_OK);
-        } else {
-            process::exit(test_result::TR_FAILED);
-        }
+        exit_with_test_result(&test_result)
     });
     let record_result2 = record_result.clone();
     panic::set_hook(Box::new(move |info| record_result2(Some(info))));
@@ -767,3 +952,12 @@ fn run_test_in_spawned_subprocess(desc: TestDesc, runnable_test: RunnableTest) -
     record_result(None);
     unreachable!("panic=abort callback should have exited the process")
 }
+
+/// Exits the test subprocess with the exit code that reports `test_result` to the parent.
+fn exit_with_test_result(test_result: &TestResult) -> ! {
+    if let TrOk = test_result {
+        process::exit(test_result::TR_OK);
+    } else {
+        process::exit(test_result::TR_FAILED);
+    }
+}
@@ -36,6 +36,10 @@ pub enum OutputFormat {
     Json,
     /// JUnit output
     Junit,
+    /// Test Anything Protocol output
+    Tap,
+    /// Verbose output with GitHub Actions annotations for failed tests
+    Github,
 }
 
 /// Whether ignored test should be run or not
@@ -4,6 +4,7 @@
     bench::Bencher,
     console::OutputLocation,
     formatters::PrettyFormatter,
+    helpers::baseline::{self, Baseline, Change},
     options::OutputFormat,
     test::{
         filter_tests,
@@ -43,6 +44,7 @@ fn new() -> TestOpts {
             bench_benchmarks: false,
             logfile: None,
             nocapture: false,
+            capture_fd: false,
             color: AutoColor,
             format: OutputFormat::Pretty,
             shuffle: false,
@@ -50,6 +52,11 @@ fn new() -> TestOpts {
             test_threads: None,
             skip: vec![],
             time_options: None,
+            test_timeout: None,
+            retries: 0,
+            shard: None,
+            save_baseline: None,
+            baseline: None,
             options: Options::new(),
             fail_fast: false,
         }
@@ -495,6 +502,21 @@ fn parse_include_ignored_flag() {
     assert_eq!(opts.run_ignored, RunIgnored::Yes);
 }
 
+#[test]
+fn parse_test_timeout_option() {
+    let args = vec![
+        "progname".to_string(),
+        "-Zunstable-options".to_string(),
+        "--test-timeout".to_string(),
+        "30".to_string(),
+    ];
+    let opts = parse_opts(&args).unwrap().unwrap();
+    assert_eq!(opts.test_timeout, Some(Duration::from_secs(30)));
+
+    let args = vec!["progname".to_string(), "--test-timeout".to_string(), "30".to_string()];
+    assert!(parse_opts(&args).unwrap().is_err());
+}
+
 #[test]
 pub fn filter_for_ignored_option() {
     // When we run ignored tests the test filter should filter out all the
@@ -683,6 +705,54 @@ fn testfn() -> Result<(), String> {
     tests
 }
 
+#[test]
+pub fn shard_tests() {
+    let names = |tests: Vec<TestDescAndFn>| {
+        tests.into_iter().map(|test| test.desc.name.to_string()).collect::<Vec<_>>()
+    };
+    let shard = |index, tests| {
+        names(filter_tests(&TestOpts { shard: Some((index, 3)), ..TestOpts::new() }, tests))
+    };
+
+    let mut all = Vec::new();
+    for index in 0..3 {
+        let tests = shard(index, sample_tests());
+        // Every shard gets a third of the tests.
+        assert!(tests.len() == 3 || tests.len() == 4);
+
+        // The shards don't depend on the order of the tests.
+        let mut reversed = sample_tests();
+        reversed.reverse();
+        let mut reversed = shard(index, reversed);
+        reversed.reverse();
+        assert_eq!(tests, reversed);
+
+        all.extend(tests);
+    }
+
+    // The shards are disjoint and cover all tests.
+    all.sort();
+    let mut expected = names(sample_tests());
+    expected.sort();
+    assert_eq!(all, expected);
+}
+
+#[test]
+fn parse_shard_options() {
+    let args = |extra: &[&str]| {
+        let mut args = vec!["progname".to_string(), "-Zunstable-options".to_string()];
+        args.extend(extra.iter().map(|arg| arg.to_string()));
+        args
+    };
+
+    let opts = parse_opts(&args(&["--shard-index", "1", "--shard-count", "4"])).unwrap().unwrap();
+    assert_eq!(opts.shard, Some((1, 4)));
+
+    assert!(parse_opts(&args(&["--shard-index", "1"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index", "4", "--shard-count", "4"])).unwrap().is_err());
+    assert!(parse_opts(&args(&["--shard-index", "0", "--shard-count", "0"])).unwrap().is_err());
+}
+
 #[test]
 pub fn shuffle_tests() {
     let mut opts = TestOpts::new();
@@ -843,6 +913,33 @@ fn f(b: &mut Bencher) -> Result<(), String> {
     rx.recv().unwrap();
 }
 
+#[test]
+fn compare_bench_to_baseline() {
+    let base = [100.0, 101.0, 99.0, 100.5, 99.5, 100.0, 100.2, 99.8];
+    let shifted = |offset: f64| base.iter().map(|s| s + offset).collect::<Vec<_>>();
+
+    let unchanged = baseline::compare(&base, &base).unwrap();
+    assert_eq!(unchanged.change, Change::Unchanged);
+    assert_eq!(unchanged.change_pct, 0.0);
+    assert_eq!(unchanged.baseline_mean, 100.0);
+
+    assert_eq!(baseline::compare(&base, &shifted(10.0)).unwrap().change, Change::Regressed);
+    assert_eq!(baseline::compare(&base, &shifted(-10.0)).unwrap().change, Change::Improved);
+
+    // Significant, but within the noise threshold.
+    assert_eq!(baseline::compare(&base, &shifted(1.0)).unwrap().change, Change::Unchanged);
+
+    // Larger than the noise threshold, but not significant given the variance.
+    let noisy = [50.0, 150.0, 60.0, 140.0, 70.0, 130.0, 80.0, 145.0];
+    assert_eq!(baseline::compare(&base, &noisy).unwrap().change, Change::Unchanged);
+
+    // Benchmarks that aren't part of the baseline can't be compared.
+    let mut samples = Baseline::new();
+    samples.insert("a", base.to_vec());
+    assert!(samples.compare("a", &shifted(10.0)).is_some());
+    assert!(samples.compare("b", &base).is_none());
+}
+
 #[test]
 fn should_sort_failures_before_printing_them() {
     let test_a = TestDesc {
@@ -883,10 +980,13 @@ fn should_sort_failures_before_printing_them() {
         passed: 0,
         failed: 0,
         ignored: 0,
+        flaky: 0,
         filtered_out: 0,
         measured: 0,
         exec_time: None,
         metrics: MetricMap::new(),
+        baseline: None,
+        bench_samples: Baseline::new(),
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
         options: Options::new(),
         not_failures: Vec::new(),
@@ -939,3 +1039,62 @@ fn f(_: &mut Bencher) -> Result<(), String> {
     let result = rx.recv().unwrap().result;
     assert_eq!(result, TrFailed);
 }
+
+#[test]
+fn test_retries() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static FLAKY_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+    fn flaky() -> Result<(), String> {
+        if FLAKY_RUNS.fetch_add(1, Ordering::SeqCst) == 0 { Err("flaked".into()) } else { Ok(()) }
+    }
+    fn failing() -> Result<(), String> {
+        Err("failed".into())
+    }
+    fn desc(name: &'static str) -> TestDesc {
+        TestDesc {
+            name: StaticTestName(name),
+            ignore: false,
+            ignore_message: None,
+            source_file: "",
+            start_line: 0,
+            start_col: 0,
+            end_line: 0,
+            end_col: 0,
+            should_panic: ShouldPanic::No,
+            compile_fail: false,
+            no_run: false,
+            test_type: TestType::Unknown,
+        }
+    }
+    let tests = vec![
+        TestDescAndFn { desc: desc("flaky"), testfn: StaticTestFn(flaky) },
+        TestDescAndFn { desc: desc("failing"), testfn: StaticTestFn(failing) },
+    ];
+
+    let mut retried = Vec::new();
+    let mut results = Vec::new();
+    let notify = |event: TestEvent| {
+        match event {
+            TestEvent::TeRetry(test) => retried.push((test.desc.name.to_string(), test.attempt)),
+            TestEvent::TeResult(test) => {
+                results.push((test.desc.name.to_string(), test.attempt, test.result))
+            }
+            _ => {}
+        }
+        Ok(())
+    };
+    let opts = TestOpts { run_tests: true, retries: 2, test_threads: Some(1), ..TestOpts::new() };
+    run_tests(&opts, tests, notify).unwrap();
+
+    assert_eq!(
+        retried,
+        vec![("flaky".to_string(), 1), ("failing".to_string(), 1), ("failing".to_string(), 2)]
+    );
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0], ("flaky".to_string(), 2, TrOk));
+    assert_eq!(results[1].0, "failing");
+    assert_eq!(results[1].1, 3);
+    assert_ne!(results[1].2, TrOk);
+}
@@ -521,6 +521,7 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         run_tests: true,
         bench_benchmarks: true,
         nocapture: config.nocapture,
+        capture_fd: false,
         color: config.color,
         shuffle: false,
         shuffle_seed: None,
@@ -529,6 +530,11 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         list: false,
         options: test::Options::new(),
         time_options: None,
+        test_timeout: None,
+        retries: 0,
+        shard: None,
+        save_baseline: None,
+        baseline: None,
         force_run_in_process: false,
         fail_fast: std::env::var_os("RUSTC_TEST_FAIL_FAST").is_some(),
     }
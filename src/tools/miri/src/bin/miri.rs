@@ -394,6 +394,8 @@ fn main() {
                         "-Zmiri-isolation-error must be `abort`, `hide`, `warn`, or `warn-nobacktrace`"
                     ),
             };
+        } else if arg == "-Zmiri-emulate-processes" {
+            miri_config.emulate_processes = true;
         } else if arg == "-Zmiri-ignore-leaks" {
             miri_config.ignore_leaks = true;
             miri_config.collect_leak_backtraces = false;
@@ -516,6 +518,14 @@ fn main() {
                 Err(err) => show_error!("-Zmiri-tag-gc requires a `u32`: {}", err),
             };
             miri_config.gc_interval = interval;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-coverage=") {
+            miri_config.coverage = Some(PathBuf::from(param));
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-explore-schedules=") {
+            let bound = match param.parse::<u32>() {
+                Ok(bound) => bound,
+                Err(err) => show_error!("-Zmiri-explore-schedules requires a `u32`: {}", err),
+            };
+            miri_config.explore_schedules = Some(bound);
         } else if let Some(param) = arg.strip_prefix("-Zmiri-measureme=") {
             miri_config.measureme_out = Some(param.to_string());
         } else if let Some(param) = arg.strip_prefix("-Zmiri-backtrace=") {
@@ -571,6 +581,20 @@ fn main() {
         );
     }
 
+    // Exploring schedules runs the program many times, which must behave the same every time.
+    if miri_config.explore_schedules.is_some() {
+        if matches!(miri_config.isolated_op, miri::IsolatedOp::Allow) {
+            show_error!(
+                "-Zmiri-explore-schedules cannot be used along with -Zmiri-disable-isolation"
+            );
+        }
+        if miri_config.emulate_processes {
+            show_error!(
+                "-Zmiri-explore-schedules cannot be used along with -Zmiri-emulate-processes"
+            );
+        }
+    }
+
     debug!("rustc arguments: {:?}", rustc_args);
     debug!("crate arguments: {:?}", miri_config.args);
     run_compiler(rustc_args, /* target_crate: */ true, &mut MiriCompilerCalls { miri_config })
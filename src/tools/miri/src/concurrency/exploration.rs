@@ -0,0 +1,188 @@
+//! Systematic exploration of thread schedules, for `-Zmiri-explore-schedules`.
+//!
+//! Instead of preempting threads randomly, the scheduler takes a decision whenever more than one
+//! thread could run next: when the active thread blocks, yields or terminates, and after the
+//! active thread did something other threads can observe, like an atomic access, a lock operation
+//! or spawning a thread. Switching away from a thread that could have kept running is a
+//! preemption.
+//!
+//! The program is executed once per schedule, in a depth-first search over these decisions: every
+//! execution replays the decisions of the previous one up to the last decision that has an
+//! alternative left, takes that alternative, and then takes the default decisions. Only schedules
+//! with at most a given number of preemptions are explored; most concurrency bugs already show up
+//! with one or two preemptions, while the number of schedules grows exponentially with the bound.
+//! This requires the program to be deterministic for a given schedule, which is why the host
+//! cannot be accessed during the exploration.
+//!
+//! Since the default decision at a preemption point is to keep running the active thread, a thread
+//! that spins until another thread sets a flag would never stop. So after `FAIRNESS_BOUND`
+//! preemption points in a row at which other threads could have run, the scheduler switches to
+//! another thread anyway. That switch is not a decision and does not count as a preemption.
+
+use std::cell::Cell;
+
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Span;
+
+use crate::*;
+
+/// The number of preemption points in a row at which the active thread keeps running while other
+/// threads could run, before the scheduler switches to another thread.
+const FAIRNESS_BOUND: u32 = 64;
+
+#[derive(Debug)]
+pub struct ScheduleExplorer {
+    /// The maximal number of preemptions of a schedule.
+    preemption_bound: u32,
+    /// The choices of the first decisions, taken from the previous schedule.
+    replay: Vec<usize>,
+    /// The decisions taken so far.
+    decisions: Vec<Decision>,
+    /// The number of preemptions so far.
+    preemptions: u32,
+    /// The number of preemption points at which other threads could have run since the scheduler
+    /// last switched threads.
+    streak: u32,
+    /// Whether the active thread did something other threads can observe since the last
+    /// decision.
+    visible_op: Cell<bool>,
+    /// The thread to switch to once the active thread was preempted.
+    switch_to: Option<ThreadId>,
+}
+
+#[derive(Debug)]
+struct Decision {
+    /// The thread that was active.
+    active: ThreadId,
+    /// The threads that could run next. The first one is the one the scheduler picks by default,
+    /// which is the active thread itself at a preemption point.
+    options: Vec<ThreadId>,
+    /// The index of the thread that was picked.
+    chosen: usize,
+    /// Where the active thread was, if it could have kept running.
+    preemption_point: Option<Span>,
+    /// The number of preemptions before this decision.
+    preemptions: u32,
+}
+
+impl ScheduleExplorer {
+    /// Creates the explorer for the schedule that starts with the `replay` choices, as returned
+    /// by `next_schedule`. The first schedule starts with an empty `replay`.
+    pub fn new(preemption_bound: u32, replay: Vec<usize>) -> Self {
+        ScheduleExplorer {
+            preemption_bound,
+            replay,
+            decisions: Vec::new(),
+            preemptions: 0,
+            streak: 0,
+            visible_op: Cell::new(false),
+            switch_to: None,
+        }
+    }
+
+    /// Records that the active thread did something other threads can observe, so the next
+    /// preemption point is a decision.
+    pub fn note_visible_op(&self) {
+        self.visible_op.set(true);
+    }
+
+    /// Returns whether the active thread reached a preemption point, and resets it.
+    pub fn take_visible_op(&self) -> bool {
+        self.visible_op.replace(false)
+    }
+
+    /// Counts a preemption point at which other threads could run, and returns whether the active
+    /// thread reached the fairness bound, in which case the scheduler switches to another thread
+    /// without taking a decision.
+    pub fn reached_fairness_bound(&mut self) -> bool {
+        self.streak += 1;
+        self.streak > FAIRNESS_BOUND
+    }
+
+    /// Records that the scheduler switches threads, which starts a new streak.
+    pub fn reset_streak(&mut self) {
+        self.streak = 0;
+    }
+
+    /// Records that the active thread was preempted, and `thread` runs next.
+    pub fn set_switch_to(&mut self, thread: ThreadId) {
+        self.switch_to = Some(thread);
+    }
+
+    /// Returns the thread to switch to if the active thread was preempted.
+    pub fn take_switch_to(&mut self) -> Option<ThreadId> {
+        self.switch_to.take()
+    }
+
+    /// Picks the next thread to run among `options`, which has at least two elements.
+    /// `preemption_point` is where `active` is if it could keep running, in which case it is the
+    /// first option.
+    pub fn decide<'tcx>(
+        &mut self,
+        active: ThreadId,
+        options: Vec<ThreadId>,
+        preemption_point: Option<Span>,
+    ) -> InterpResult<'tcx, ThreadId> {
+        let chosen = self.replay.get(self.decisions.len()).copied().unwrap_or(0);
+        if chosen >= options.len() {
+            throw_unsup_format!(
+                "the program behaved differently when a schedule was replayed, \
+                but `-Zmiri-explore-schedules` requires a deterministic program"
+            );
+        }
+        let preemptions = self.preemptions;
+        if preemption_point.is_some() && chosen != 0 {
+            self.preemptions += 1;
+        }
+        let next = options[chosen];
+        self.decisions.push(Decision { active, options, chosen, preemption_point, preemptions });
+        Ok(next)
+    }
+
+    /// Returns the choices the next schedule starts with, or `None` if all schedules were
+    /// explored.
+    pub fn next_schedule(&self) -> Option<Vec<usize>> {
+        let (idx, decision) = self.decisions.iter().enumerate().rev().find(|(_, decision)| {
+            decision.chosen + 1 < decision.options.len()
+                // Every other choice at a preemption point is another preemption.
+                && (decision.preemption_point.is_none()
+                    || decision.preemptions < self.preemption_bound)
+        })?;
+        let mut replay: Vec<usize> =
+            self.decisions[..idx].iter().map(|decision| decision.chosen).collect();
+        replay.push(decision.chosen + 1);
+        Some(replay)
+    }
+
+    /// Describes the decisions in which this schedule differs from the default schedule.
+    pub fn describe(&self, tcx: TyCtxt<'_>, threads: &ThreadManager<'_, '_>) -> Vec<String> {
+        let name = |thread: ThreadId| {
+            format!(
+                "{} (`{}`)",
+                thread.to_u32(),
+                String::from_utf8_lossy(threads.get_thread_name(thread))
+            )
+        };
+        self.decisions
+            .iter()
+            .filter(|decision| decision.chosen != 0)
+            .map(|decision| {
+                let next = name(decision.options[decision.chosen]);
+                match decision.preemption_point {
+                    Some(span) =>
+                        format!(
+                            "thread {} was preempted at {} to run thread {next}",
+                            name(decision.active),
+                            tcx.sess.source_map().span_to_embeddable_string(span)
+                        ),
+                    None =>
+                        format!(
+                            "when thread {} stopped, thread {next} ran instead of thread {}",
+                            name(decision.active),
+                            name(decision.options[0])
+                        ),
+                }
+            })
+            .collect()
+    }
+}
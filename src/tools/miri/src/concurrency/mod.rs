@@ -1,4 +1,5 @@
 pub mod data_race;
+pub mod exploration;
 mod range_object_map;
 #[macro_use]
 pub mod sync;
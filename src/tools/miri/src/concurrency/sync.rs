@@ -348,6 +348,7 @@ fn mutex_is_locked(&self, id: MutexId) -> bool {
     /// Lock by setting the mutex owner and increasing the lock count.
     fn mutex_lock(&mut self, id: MutexId, thread: ThreadId) {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         let mutex = &mut this.machine.threads.sync.mutexes[id];
         if let Some(current_owner) = mutex.owner {
             assert_eq!(thread, current_owner, "mutex already locked by another thread");
@@ -370,6 +371,7 @@ fn mutex_lock(&mut self, id: MutexId, thread: ThreadId) {
     /// return `None`.
     fn mutex_unlock(&mut self, id: MutexId, expected_owner: ThreadId) -> Option<usize> {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         let current_span = this.machine.current_span();
         let mutex = &mut this.machine.threads.sync.mutexes[id];
         if let Some(current_owner) = mutex.owner {
@@ -456,6 +458,7 @@ fn rwlock_is_write_locked(&self, id: RwLockId) -> bool {
     /// this lock.
     fn rwlock_reader_lock(&mut self, id: RwLockId, reader: ThreadId) {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         assert!(!this.rwlock_is_write_locked(id), "the lock is write locked");
         trace!("rwlock_reader_lock: {:?} now also held (one more time) by {:?}", id, reader);
         let rwlock = &mut this.machine.threads.sync.rwlocks[id];
@@ -470,6 +473,7 @@ fn rwlock_reader_lock(&mut self, id: RwLockId, reader: ThreadId) {
     /// Returns `true` if succeeded, `false` if this `reader` did not hold the lock.
     fn rwlock_reader_unlock(&mut self, id: RwLockId, reader: ThreadId) -> bool {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         let current_span = this.machine.current_span();
         let rwlock = &mut this.machine.threads.sync.rwlocks[id];
         match rwlock.readers.entry(reader) {
@@ -519,6 +523,7 @@ fn rwlock_enqueue_and_block_reader(&mut self, id: RwLockId, reader: ThreadId) {
     #[inline]
     fn rwlock_writer_lock(&mut self, id: RwLockId, writer: ThreadId) {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         assert!(!this.rwlock_is_locked(id), "the rwlock is already locked");
         trace!("rwlock_writer_lock: {:?} now held by {:?}", id, writer);
         let rwlock = &mut this.machine.threads.sync.rwlocks[id];
@@ -532,6 +537,7 @@ fn rwlock_writer_lock(&mut self, id: RwLockId, writer: ThreadId) {
     #[inline]
     fn rwlock_writer_unlock(&mut self, id: RwLockId, expected_writer: ThreadId) -> bool {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         let current_span = this.machine.current_span();
         let rwlock = &mut this.machine.threads.sync.rwlocks[id];
         if let Some(current_writer) = rwlock.writer {
@@ -624,6 +630,7 @@ fn condvar_wait(&mut self, id: CondvarId, thread: ThreadId, lock: CondvarLock) {
     /// variable.
     fn condvar_signal(&mut self, id: CondvarId) -> Option<(ThreadId, CondvarLock)> {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         let current_thread = this.get_active_thread();
         let current_span = this.machine.current_span();
         let condvar = &mut this.machine.threads.sync.condvars[id];
@@ -658,6 +665,7 @@ fn futex_wait(&mut self, addr: u64, thread: ThreadId, bitset: u32) {
 
     fn futex_wake(&mut self, addr: u64, bitset: u32) -> Option<ThreadId> {
         let this = self.eval_context_mut();
+        this.machine.threads.note_visible_op();
         let current_thread = this.get_active_thread();
         let current_span = this.machine.current_span();
         let futex = &mut this.machine.threads.sync.futexes.get_mut(&addr)?;
@@ -4,6 +4,7 @@
 use std::collections::hash_map::Entry;
 use std::num::TryFromIntError;
 use std::sync::atomic::{AtomicBool, Ordering::Relaxed};
+use std::sync::Once;
 use std::task::Poll;
 use std::time::{Duration, SystemTime};
 
@@ -18,6 +19,7 @@
 use rustc_target::spec::abi::Abi;
 
 use crate::concurrency::data_race;
+use crate::concurrency::exploration::ScheduleExplorer;
 use crate::concurrency::sync::SynchronizationState;
 use crate::shims::tls;
 use crate::*;
@@ -28,8 +30,12 @@ enum SchedulingAction {
     ExecuteStep,
     /// Execute a timeout callback.
     ExecuteTimeoutCallback,
+    /// Call the I/O callbacks, after an I/O object changed state.
+    ExecuteIoCallbacks,
     /// Wait for a bit, until there is a timeout to be called.
     Sleep(Duration),
+    /// All threads are blocked on I/O, let the other processes run until they unblock one of them.
+    WaitForOtherProcesses,
 }
 
 /// Trait for callbacks that can be executed when some event happens, such as after a timeout.
@@ -39,6 +45,30 @@ pub trait MachineCallback<'mir, 'tcx>: VisitTags {
 
 type TimeoutCallback<'mir, 'tcx> = Box<dyn MachineCallback<'mir, 'tcx> + 'tcx>;
 
+/// Trait for the callbacks of threads blocked on I/O, such as in `epoll_wait`. They are called
+/// whenever an I/O object changed state, until they unblock their thread.
+pub trait IoCallback<'mir, 'tcx>: VisitTags {
+    /// Checks whether the blocked thread can make progress, and unblocks it if so. Returns whether
+    /// the thread was unblocked.
+    fn call(
+        &self,
+        ecx: &mut InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
+    ) -> InterpResult<'tcx, bool>;
+}
+
+type IoCallbackBox<'mir, 'tcx> = Box<dyn IoCallback<'mir, 'tcx> + 'tcx>;
+
+/// An operation that blocks its thread until it can complete, such as a `read` from an empty pipe.
+/// See `EvalContextExt::block_on_io_op`.
+pub trait BlockingIoOp<'mir, 'tcx>: VisitTags {
+    /// Completes the operation and writes its result, unless it would block. Returns whether the
+    /// operation completed.
+    fn try_complete(
+        &self,
+        ecx: &mut InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
+    ) -> InterpResult<'tcx, bool>;
+}
+
 /// A thread identifier.
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct ThreadId(u32);
@@ -322,6 +352,12 @@ pub struct ThreadManager<'mir, 'tcx> {
     yield_active_thread: bool,
     /// Callbacks that are called once the specified time passes.
     timeout_callbacks: FxHashMap<ThreadId, TimeoutCallbackInfo<'mir, 'tcx>>,
+    /// Callbacks of the threads blocked on I/O.
+    io_callbacks: FxHashMap<ThreadId, IoCallbackBox<'mir, 'tcx>>,
+    /// Whether an I/O object changed state since the `io_callbacks` were last called.
+    io_state_changed: bool,
+    /// Takes the scheduling decisions if `-Zmiri-explore-schedules` is set.
+    explorer: Option<ScheduleExplorer>,
 }
 
 impl VisitTags for ThreadManager<'_, '_> {
@@ -330,8 +366,11 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
             threads,
             thread_local_alloc_ids,
             timeout_callbacks,
+            io_callbacks,
             active_thread: _,
             yield_active_thread: _,
+            io_state_changed: _,
+            explorer: _,
             sync,
         } = self;
 
@@ -344,6 +383,9 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
         for callback in timeout_callbacks.values() {
             callback.callback.visit_tags(visit);
         }
+        for callback in io_callbacks.values() {
+            callback.visit_tags(visit);
+        }
         sync.visit_tags(visit);
     }
 }
@@ -360,6 +402,9 @@ fn default() -> Self {
             thread_local_alloc_ids: Default::default(),
             yield_active_thread: false,
             timeout_callbacks: FxHashMap::default(),
+            io_callbacks: FxHashMap::default(),
+            io_state_changed: false,
+            explorer: None,
         }
     }
 }
@@ -413,6 +458,7 @@ pub fn all_stacks(
 
     /// Create a new thread and returns its id.
     fn create_thread(&mut self, on_stack_empty: StackEmptyCallback<'mir, 'tcx>) -> ThreadId {
+        self.note_visible_op();
         let new_thread_id = ThreadId::new(self.threads.len());
         self.threads.push(Thread::new(None, Some(on_stack_empty)));
         new_thread_id
@@ -560,6 +606,75 @@ pub fn get_thread_name(&self, thread: ThreadId) -> &[u8] {
         self.threads[thread].thread_name()
     }
 
+    /// Makes `explorer` take the scheduling decisions, for `-Zmiri-explore-schedules`.
+    pub fn explore_schedule(&mut self, explorer: ScheduleExplorer) {
+        self.explorer = Some(explorer);
+    }
+
+    /// Returns the explorer, with the decisions taken so far.
+    pub fn take_explorer(&mut self) -> Option<ScheduleExplorer> {
+        self.explorer.take()
+    }
+
+    /// Records that the active thread did something other threads can observe, which makes the
+    /// next preemption point a scheduling decision when exploring schedules.
+    pub fn note_visible_op(&self) {
+        if let Some(explorer) = &self.explorer {
+            explorer.note_visible_op();
+        }
+    }
+
+    /// The enabled threads other than the active one, in the order the scheduler tries them.
+    fn other_enabled_threads(&self) -> Vec<ThreadId> {
+        self.threads
+            .iter_enumerated()
+            .skip(self.active_thread.index() + 1)
+            .chain(self.threads.iter_enumerated().take(self.active_thread.index()))
+            .filter(|(_, thread)| thread.state == ThreadState::Enabled)
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// Lets the explorer decide whether to preempt the active thread, which is at `span`.
+    fn explore_preemption(&mut self, span: Span) -> InterpResult<'tcx> {
+        let mut options = vec![self.active_thread];
+        options.extend(self.other_enabled_threads());
+        if options.len() < 2 {
+            return Ok(());
+        }
+        let explorer = self.explorer.as_mut().unwrap();
+        if explorer.reached_fairness_bound() {
+            // Switch to the thread the scheduler would run next without exploration.
+            explorer.set_switch_to(options[1]);
+            self.yield_active_thread = true;
+            return Ok(());
+        }
+        let next = explorer.decide(self.active_thread, options, Some(span))?;
+        if next != self.active_thread {
+            explorer.set_switch_to(next);
+            self.yield_active_thread = true;
+        }
+        Ok(())
+    }
+
+    /// Lets the explorer pick the thread to run next, after the active thread blocked, yielded,
+    /// terminated or was preempted. A thread that yielded only runs again if no other thread can.
+    fn explore_switch(&mut self) -> InterpResult<'tcx> {
+        let options = self.other_enabled_threads();
+        let explorer = self.explorer.as_mut().unwrap();
+        explorer.reset_streak();
+        if let Some(next) = explorer.take_switch_to() {
+            self.active_thread = next;
+            return Ok(());
+        }
+        match options.len() {
+            0 => {}
+            1 => self.active_thread = options[0],
+            _ => self.active_thread = explorer.decide(self.active_thread, options, None)?,
+        }
+        Ok(())
+    }
+
     /// Put the thread into the blocked state.
     fn block_thread(&mut self, thread: ThreadId) {
         let state = &mut self.threads[thread].state;
@@ -602,6 +717,28 @@ fn unregister_timeout_callback_if_exists(&mut self, thread: ThreadId) {
         self.timeout_callbacks.remove(&thread);
     }
 
+    /// Register the given `callback` to be called whenever an I/O object changed state, until it
+    /// unblocks `thread`.
+    ///
+    /// The callback will be called with `thread` being the active thread, and
+    /// the callback may not change the active thread.
+    fn register_io_callback(&mut self, thread: ThreadId, callback: IoCallbackBox<'mir, 'tcx>) {
+        self.io_callbacks.try_insert(thread, callback).unwrap();
+    }
+
+    /// Unregister the I/O callback for the `thread`.
+    fn unregister_io_callback_if_exists(&mut self, thread: ThreadId) {
+        self.io_callbacks.remove(&thread);
+    }
+
+    /// Record that an I/O object changed state, so that the threads blocked on I/O check whether
+    /// they can make progress before the next thread is scheduled.
+    fn io_state_changed(&mut self) {
+        if !self.io_callbacks.is_empty() {
+            self.io_state_changed = true;
+        }
+    }
+
     /// Get a callback that is ready to be called.
     fn get_ready_callback(
         &mut self,
@@ -671,7 +808,15 @@ fn thread_terminated(
     /// used in stateless model checkers such as Loom: run the active thread as
     /// long as we can and switch only when we have to (the active thread was
     /// blocked, terminated, or has explicitly asked to be preempted).
-    fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
+    ///
+    /// `other_processes` says whether there are other processes that might unblock threads that
+    /// are blocked on I/O, in which case a program with only such blocked threads is not
+    /// deadlocked yet.
+    fn schedule(
+        &mut self,
+        clock: &Clock,
+        other_processes: bool,
+    ) -> InterpResult<'tcx, SchedulingAction> {
         // This thread and the program can keep going.
         if self.threads[self.active_thread].state == ThreadState::Enabled
             && !self.yield_active_thread
@@ -690,6 +835,11 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
         if potential_sleep_time == Some(Duration::new(0, 0)) {
             return Ok(SchedulingAction::ExecuteTimeoutCallback);
         }
+        // Similarly, the threads blocked on I/O get to check whether an I/O object that changed
+        // state lets them make progress before any other thread takes an action.
+        if self.io_state_changed {
+            return Ok(SchedulingAction::ExecuteIoCallbacks);
+        }
         // No callbacks immediately scheduled, pick a regular thread to execute.
         // The active thread blocked or yielded. So we go search for another enabled thread.
         // Crucially, we start searching at the current active thread ID, rather than at 0, since we
@@ -698,16 +848,20 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
         // `skip(N)` means we start iterating at thread N, so we skip 1 more to start just *after*
         // the active thread. Then after that we look at `take(N)`, i.e., the threads *before* the
         // active thread.
-        let threads = self
-            .threads
-            .iter_enumerated()
-            .skip(self.active_thread.index() + 1)
-            .chain(self.threads.iter_enumerated().take(self.active_thread.index()));
-        for (id, thread) in threads {
-            debug_assert_ne!(self.active_thread, id);
-            if thread.state == ThreadState::Enabled {
-                self.active_thread = id;
-                break;
+        if self.explorer.is_some() {
+            self.explore_switch()?;
+        } else {
+            let threads = self
+                .threads
+                .iter_enumerated()
+                .skip(self.active_thread.index() + 1)
+                .chain(self.threads.iter_enumerated().take(self.active_thread.index()));
+            for (id, thread) in threads {
+                debug_assert_ne!(self.active_thread, id);
+                if thread.state == ThreadState::Enabled {
+                    self.active_thread = id;
+                    break;
+                }
             }
         }
         self.yield_active_thread = false;
@@ -722,14 +876,72 @@ fn schedule(&mut self, clock: &Clock) -> InterpResult<'tcx, SchedulingAction> {
             // timeout_callbacks, which may unblock some of the threads. Hence,
             // sleep until the first callback.
             Ok(SchedulingAction::Sleep(sleep_time))
+        } else if other_processes && !self.io_callbacks.is_empty() {
+            Ok(SchedulingAction::WaitForOtherProcesses)
         } else {
             throw_machine_stop!(TerminationInfo::Deadlock);
         }
     }
 }
 
+/// Set once the user pressed Ctrl-C.
+static SIGNALED: AtomicBool = AtomicBool::new(false);
+
+/// Makes Ctrl-C stop the interpreter loop. Only the first call has an effect.
+fn set_ctrlc_handler() {
+    static SET_HANDLER: Once = Once::new();
+    SET_HANDLER.call_once(|| {
+        ctrlc::set_handler(move || {
+            // Indicate that we have ben signaled to stop. If we were already signaled, exit
+            // immediately. In our interpreter loop we try to consult this value often, but if for
+            // whatever reason we don't get to that check or the cleanup we do upon finding that
+            // this bool has become true takes a long time, the exit here will promptly exit the
+            // process on the second Ctrl-C.
+            if SIGNALED.swap(true, Relaxed) {
+                std::process::exit(1);
+            }
+        })
+        .unwrap();
+    });
+}
+
 impl<'mir, 'tcx: 'mir> EvalContextPrivExt<'mir, 'tcx> for MiriInterpCx<'mir, 'tcx> {}
 trait EvalContextPrivExt<'mir, 'tcx: 'mir>: MiriInterpCxExt<'mir, 'tcx> {
+    /// Decides which action to take next, takes it, and returns it.
+    fn run_scheduling_action(
+        &mut self,
+        other_processes: bool,
+    ) -> InterpResult<'tcx, SchedulingAction> {
+        let this = self.eval_context_mut();
+        if SIGNALED.load(Relaxed) {
+            this.machine.handle_abnormal_termination();
+            std::process::exit(1);
+        }
+        let action = this.machine.threads.schedule(&this.machine.clock, other_processes)?;
+        match action {
+            SchedulingAction::ExecuteStep => {
+                if !this.step()? {
+                    // See if this thread can do something else.
+                    match this.run_on_stack_empty()? {
+                        Poll::Pending => {} // keep going
+                        Poll::Ready(()) => this.terminate_active_thread()?,
+                    }
+                }
+            }
+            SchedulingAction::ExecuteTimeoutCallback => {
+                this.run_timeout_callback()?;
+            }
+            SchedulingAction::ExecuteIoCallbacks => {
+                this.run_io_callbacks()?;
+            }
+            SchedulingAction::Sleep(duration) => {
+                this.machine.clock.sleep(duration);
+            }
+            SchedulingAction::WaitForOtherProcesses => {}
+        }
+        Ok(action)
+    }
+
     /// Execute a timeout callback on the callback's thread.
     #[inline]
     fn run_timeout_callback(&mut self) -> InterpResult<'tcx> {
@@ -759,6 +971,28 @@ fn run_timeout_callback(&mut self) -> InterpResult<'tcx> {
         Ok(())
     }
 
+    /// Call the I/O callbacks, each on the callback's thread, and drop the ones that unblocked
+    /// their thread.
+    fn run_io_callbacks(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        this.machine.threads.io_state_changed = false;
+        // Call the callbacks in the order of their thread, to keep the scheduler deterministic.
+        let mut threads: Vec<ThreadId> =
+            this.machine.threads.io_callbacks.keys().copied().collect();
+        threads.sort();
+        for thread in threads {
+            let callback = this.machine.threads.io_callbacks.remove(&thread).unwrap();
+            // See `run_timeout_callback` for why the active thread is changed here.
+            let old_thread = this.set_active_thread(thread);
+            let unblocked = callback.call(this)?;
+            this.set_active_thread(old_thread);
+            if !unblocked {
+                this.machine.threads.register_io_callback(thread, callback);
+            }
+        }
+        Ok(())
+    }
+
     #[inline]
     fn run_on_stack_empty(&mut self) -> InterpResult<'tcx, Poll<()>> {
         let this = self.eval_context_mut();
@@ -988,13 +1222,22 @@ fn yield_active_thread(&mut self) {
     }
 
     #[inline]
-    fn maybe_preempt_active_thread(&mut self) {
+    fn maybe_preempt_active_thread(&mut self) -> InterpResult<'tcx> {
         use rand::Rng as _;
 
         let this = self.eval_context_mut();
+        if let Some(explorer) = &this.machine.threads.explorer {
+            // Only the operations other threads can observe are worth preempting after.
+            if explorer.take_visible_op() {
+                let span = this.machine.current_span();
+                this.machine.threads.explore_preemption(span)?;
+            }
+            return Ok(());
+        }
         if this.machine.rng.get_mut().gen_bool(this.machine.preemption_rate) {
             this.yield_active_thread();
         }
+        Ok(())
     }
 
     #[inline]
@@ -1017,45 +1260,91 @@ fn unregister_timeout_callback_if_exists(&mut self, thread: ThreadId) {
         this.machine.threads.unregister_timeout_callback_if_exists(thread);
     }
 
+    #[inline]
+    fn register_io_callback(&mut self, thread: ThreadId, callback: IoCallbackBox<'mir, 'tcx>) {
+        let this = self.eval_context_mut();
+        this.machine.threads.register_io_callback(thread, callback);
+    }
+
+    #[inline]
+    fn unregister_io_callback_if_exists(&mut self, thread: ThreadId) {
+        let this = self.eval_context_mut();
+        this.machine.threads.unregister_io_callback_if_exists(thread);
+    }
+
+    /// Completes `op` right away unless it would block. Otherwise, blocks the active thread and
+    /// retries `op` whenever an I/O object changed state, until it completes.
+    fn block_on_io_op(&mut self, op: impl BlockingIoOp<'mir, 'tcx> + 'tcx) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        if op.try_complete(this)? {
+            return Ok(());
+        }
+
+        let thread = this.get_active_thread();
+        this.block_thread(thread);
+
+        struct Callback<Op> {
+            thread: ThreadId,
+            op: Op,
+        }
+
+        impl<Op: VisitTags> VisitTags for Callback<Op> {
+            fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                let Callback { thread: _, op } = self;
+                op.visit_tags(visit);
+            }
+        }
+
+        impl<'mir, 'tcx: 'mir, Op: BlockingIoOp<'mir, 'tcx>> IoCallback<'mir, 'tcx> for Callback<Op> {
+            fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx, bool> {
+                if !self.op.try_complete(this)? {
+                    return Ok(false);
+                }
+                this.unblock_thread(self.thread);
+                Ok(true)
+            }
+        }
+
+        this.register_io_callback(thread, Box::new(Callback { thread, op }));
+        Ok(())
+    }
+
+    /// Record that an I/O object changed state, for example because data was written to a pipe,
+    /// which may unblock the threads blocked on I/O.
+    #[inline]
+    fn io_state_changed(&mut self) {
+        let this = self.eval_context_mut();
+        this.machine.threads.io_state_changed();
+    }
+
     /// Run the core interpreter loop. Returns only when an interrupt occurs (an error or program
     /// termination).
     fn run_threads(&mut self) -> InterpResult<'tcx, !> {
-        static SIGNALED: AtomicBool = AtomicBool::new(false);
-        ctrlc::set_handler(move || {
-            // Indicate that we have ben signaled to stop. If we were already signaled, exit
-            // immediately. In our interpreter loop we try to consult this value often, but if for
-            // whatever reason we don't get to that check or the cleanup we do upon finding that
-            // this bool has become true takes a long time, the exit here will promptly exit the
-            // process on the second Ctrl-C.
-            if SIGNALED.swap(true, Relaxed) {
-                std::process::exit(1);
-            }
-        })
-        .unwrap();
         let this = self.eval_context_mut();
+        set_ctrlc_handler();
         loop {
-            if SIGNALED.load(Relaxed) {
-                this.machine.handle_abnormal_termination();
-                std::process::exit(1);
-            }
-            match this.machine.threads.schedule(&this.machine.clock)? {
-                SchedulingAction::ExecuteStep => {
-                    if !this.step()? {
-                        // See if this thread can do something else.
-                        match this.run_on_stack_empty()? {
-                            Poll::Pending => {} // keep going
-                            Poll::Ready(()) => this.terminate_active_thread()?,
-                        }
-                    }
-                }
-                SchedulingAction::ExecuteTimeoutCallback => {
-                    this.run_timeout_callback()?;
-                }
-                SchedulingAction::Sleep(duration) => {
-                    this.machine.clock.sleep(duration);
-                }
+            this.run_scheduling_action(/* other_processes */ false)?;
+        }
+    }
+
+    /// Run the core interpreter loop of one of several processes, for at most `max_actions`
+    /// scheduling decisions or until all threads are blocked on I/O that only another process can
+    /// unblock. Returns whether any thread made progress. Like for `run_threads`, the termination
+    /// of the program is returned as an error.
+    fn run_threads_for(&mut self, max_actions: u32) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_mut();
+        set_ctrlc_handler();
+        let mut progress = false;
+        for _ in 0..max_actions {
+            match this.run_scheduling_action(/* other_processes */ true)? {
+                SchedulingAction::WaitForOtherProcesses => break,
+                // The I/O callbacks only make progress if they unblock a thread, which then
+                // executes a step.
+                SchedulingAction::ExecuteIoCallbacks => {}
+                _ => progress = true,
             }
         }
+        Ok(progress)
     }
 
     /// Handles thread termination of the active thread: wakes up threads joining on this one,
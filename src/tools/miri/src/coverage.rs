@@ -0,0 +1,174 @@
+//! Line coverage of the interpreted program, for `-Zmiri-coverage`.
+//!
+//! We count how often every basic block of the functions in the local crates was executed, and
+//! every line gets the count of the most executed block with a statement on that line. The report
+//! uses the LCOV tracefile format, which tools like `genhtml` and `grcov` understand.
+
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def_id::{CrateNum, DefId};
+use rustc_index::IndexVec;
+use rustc_middle::mir::{self, BasicBlock};
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::{hygiene, FileName, Span};
+
+#[derive(Debug)]
+pub struct Coverage {
+    /// The crates whose functions are reported.
+    local_crates: Vec<CrateNum>,
+    functions: FxHashMap<DefId, FunctionCoverage>,
+}
+
+#[derive(Debug)]
+struct FunctionCoverage {
+    /// How often the function was called.
+    calls: u64,
+    /// How often each basic block was executed.
+    blocks: IndexVec<BasicBlock, u64>,
+}
+
+impl FunctionCoverage {
+    fn new(body: &mir::Body<'_>) -> Self {
+        FunctionCoverage { calls: 0, blocks: IndexVec::from_elem_n(0, body.basic_blocks.len()) }
+    }
+}
+
+/// The coverage of one source file.
+#[derive(Default)]
+struct FileCoverage {
+    /// The line, name and number of calls of each function that starts in this file.
+    functions: Vec<(usize, String, u64)>,
+    /// The execution count of each line that has a statement on it.
+    lines: BTreeMap<usize, u64>,
+}
+
+impl Coverage {
+    pub fn new(local_crates: Vec<CrateNum>) -> Self {
+        Coverage { local_crates, functions: FxHashMap::default() }
+    }
+
+    fn is_local(&self, def_id: DefId) -> bool {
+        def_id.is_local() || self.local_crates.contains(&def_id.krate)
+    }
+
+    /// Records a call of `instance`, whose MIR is `body`. Shims and functions of other crates
+    /// are not recorded.
+    pub fn enter_frame(&mut self, instance: ty::Instance<'_>, body: &mir::Body<'_>) {
+        let ty::InstanceDef::Item(def_id) = instance.def else { return };
+        if !self.is_local(def_id) {
+            return;
+        }
+        self.functions.entry(def_id).or_insert_with(|| FunctionCoverage::new(body)).calls += 1;
+    }
+
+    /// Records that `block` of `instance` is about to execute its terminator, which means that
+    /// all of its statements were executed.
+    pub fn execute_block(&mut self, instance: ty::Instance<'_>, block: BasicBlock) {
+        let ty::InstanceDef::Item(def_id) = instance.def else { return };
+        if let Some(function) = self.functions.get_mut(&def_id) {
+            function.blocks[block] += 1;
+        }
+    }
+
+    /// Adds the counts of `other`, the coverage of another process or schedule, to `self`.
+    pub fn merge(&mut self, other: Coverage) {
+        for (def_id, other) in other.functions {
+            match self.functions.get_mut(&def_id) {
+                None => {
+                    self.functions.insert(def_id, other);
+                }
+                Some(function) => {
+                    function.calls += other.calls;
+                    for (count, other_count) in function.blocks.iter_mut().zip(other.blocks) {
+                        *count += other_count;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Appends the report to `path`, so that the reports of all the programs of a test run end
+    /// up in the same file. The functions of the current crate are always reported,
+    /// including the ones that were never called. The functions of the other local crates are
+    /// only reported if they were called, since we cannot enumerate them.
+    pub fn write_lcov(&self, tcx: TyCtxt<'_>, path: &Path) -> io::Result<()> {
+        let mut def_ids: Vec<DefId> = tcx
+            .hir()
+            .body_owners()
+            .filter(|&def_id| tcx.def_kind(def_id).is_fn_like())
+            .map(|def_id| def_id.to_def_id())
+            .collect();
+        def_ids.extend(self.functions.keys().copied().filter(|def_id| !def_id.is_local()));
+
+        let mut files: BTreeMap<String, FileCoverage> = BTreeMap::new();
+        for def_id in def_ids {
+            let body = tcx.optimized_mir(def_id);
+            // Functions generated by macros, like derived trait implementations, have no
+            // meaningful lines.
+            if body.span.from_expansion() {
+                continue;
+            }
+            let Some((file, line)) = source_line(tcx, body.span) else { continue };
+            let function = self.functions.get(&def_id);
+            let calls = function.map_or(0, |function| function.calls);
+            let file = files.entry(file).or_default();
+            file.functions.push((line, tcx.def_path_str(def_id), calls));
+
+            for (block, data) in body.basic_blocks.iter_enumerated() {
+                let count = function.map_or(0, |function| function.blocks[block]);
+                let spans =
+                    data.statements.iter().map(|statement| statement.source_info.span).chain(
+                        data.terminator.as_ref().map(|terminator| terminator.source_info.span),
+                    );
+                for span in spans {
+                    // Attribute code expanded from macros to the place the macro was called.
+                    let span = hygiene::walk_chain(span, body.span.ctxt());
+                    if span.is_dummy() || !body.span.contains(span) {
+                        continue;
+                    }
+                    // The span is in the same file as the function.
+                    let Some((_, line)) = source_line(tcx, span) else { continue };
+                    let line_count = file.lines.entry(line).or_default();
+                    *line_count = (*line_count).max(count);
+                }
+            }
+        }
+
+        let mut out = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        for (name, file) in &mut files {
+            file.functions.sort();
+            writeln!(out, "TN:")?;
+            writeln!(out, "SF:{name}")?;
+            for (line, function, _) in &file.functions {
+                writeln!(out, "FN:{line},{function}")?;
+            }
+            for (_, function, calls) in &file.functions {
+                writeln!(out, "FNDA:{calls},{function}")?;
+            }
+            writeln!(out, "FNF:{}", file.functions.len())?;
+            let hit = file.functions.iter().filter(|(_, _, calls)| *calls > 0).count();
+            writeln!(out, "FNH:{hit}")?;
+            for (line, count) in &file.lines {
+                writeln!(out, "DA:{line},{count}")?;
+            }
+            writeln!(out, "LF:{}", file.lines.len())?;
+            let hit = file.lines.values().filter(|&&count| count > 0).count();
+            writeln!(out, "LH:{hit}")?;
+            writeln!(out, "end_of_record")?;
+        }
+        out.flush()
+    }
+}
+
+/// Returns the file and line `span` starts at, if it is in a real source file.
+fn source_line(tcx: TyCtxt<'_>, span: Span) -> Option<(String, usize)> {
+    let lo = tcx.sess.source_map().lookup_char_pos(span.lo());
+    match &lo.file.name {
+        FileName::Real(_) => Some((lo.file.name.prefer_local().to_string(), lo.line)),
+        _ => None,
+    }
+}
@@ -1,11 +1,12 @@
 //! Main evaluator loop and setting up the initial stack frame.
 
+use std::cell::RefCell;
 use std::ffi::{OsStr, OsString};
 use std::iter;
 use std::panic::{self, AssertUnwindSafe};
 use std::path::PathBuf;
+use std::rc::Rc;
 use std::task::Poll;
-use std::thread;
 
 use log::info;
 use rustc_middle::ty::Ty;
@@ -25,6 +26,7 @@
 use rustc_session::config::EntryFnType;
 
 use crate::shims::tls;
+use crate::shims::unix::{Process, ProcessManager, ROOT_PID};
 use crate::*;
 
 /// When the main thread would exit, we will yield to any other thread that is ready to execute.
@@ -32,6 +34,10 @@
 /// will hang the program.
 const MAIN_THREAD_YIELDS_AT_SHUTDOWN: u32 = 256;
 
+/// With `-Zmiri-emulate-processes`, the number of scheduling decisions a process takes before the
+/// next process gets to run.
+const PROCESS_TIME_SLICE: u32 = 1000;
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum AlignmentCheck {
     /// Do not check alignment.
@@ -153,6 +159,12 @@ pub struct MiriConfig {
     pub page_size: Option<u64>,
     /// Whether to collect a backtrace when each allocation is created, just in case it leaks.
     pub collect_leak_backtraces: bool,
+    /// Whether the interpreted program can spawn itself as a new process.
+    pub emulate_processes: bool,
+    /// If `Some`, write an LCOV coverage report of the interpreted program to this file.
+    pub coverage: Option<PathBuf>,
+    /// If `Some`, run the program once for every schedule with at most this many preemptions.
+    pub explore_schedules: Option<u32>,
 }
 
 impl Default for MiriConfig {
@@ -189,6 +201,9 @@ fn default() -> MiriConfig {
             num_cpus: 1,
             page_size: None,
             collect_leak_backtraces: true,
+            emulate_processes: false,
+            coverage: None,
+            explore_schedules: None,
         }
     }
 }
@@ -419,40 +434,149 @@ pub fn create_ecx<'mir, 'tcx: 'mir>(
     Ok(ecx)
 }
 
-/// Evaluates the entry function specified by `entry_id`.
-/// Returns `Some(return_code)` if program executed completed.
-/// Returns `None` if an evaluation error occurred.
-#[allow(clippy::needless_lifetimes)]
-pub fn eval_entry<'tcx>(
+/// Like `create_ecx`, but aborts if the initialization fails.
+fn create_ecx_or_abort<'mir, 'tcx: 'mir>(
     tcx: TyCtxt<'tcx>,
     entry_id: DefId,
     entry_type: EntryFnType,
-    config: MiriConfig,
-) -> Option<i64> {
-    // Copy setting before we move `config`.
-    let ignore_leaks = config.ignore_leaks;
-
-    let mut ecx = match create_ecx(tcx, entry_id, entry_type, &config) {
+    config: &MiriConfig,
+) -> InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>> {
+    match create_ecx(tcx, entry_id, entry_type, config) {
         Ok(v) => v,
         Err(err) => {
             let (kind, backtrace) = err.into_parts();
             backtrace.print_backtrace();
             panic!("Miri initialization error: {kind:?}")
         }
-    };
+    }
+}
 
-    // Perform the main execution.
-    let res: thread::Result<InterpResult<'_, !>> =
-        panic::catch_unwind(AssertUnwindSafe(|| ecx.run_threads()));
-    let res = res.unwrap_or_else(|panic_payload| {
+/// Calls `f`, and prints the interpreter backtrace if Miri itself panics.
+fn catch_ice<'mir, 'tcx: 'mir, T>(
+    ecx: &mut InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
+    f: impl FnOnce(&mut InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>) -> T,
+) -> T {
+    let res = panic::catch_unwind(AssertUnwindSafe(|| f(ecx)));
+    res.unwrap_or_else(|panic_payload| {
         ecx.handle_ice();
         panic::resume_unwind(panic_payload)
-    });
+    })
+}
+
+/// Evaluates the entry function specified by `entry_id`.
+/// Returns `Some(return_code)` if program executed completed.
+/// Returns `None` if an evaluation error occurred.
+#[allow(clippy::needless_lifetimes)]
+pub fn eval_entry<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: MiriConfig,
+) -> Option<i64> {
+    if let Some(preemption_bound) = config.explore_schedules {
+        return explore_schedules(tcx, entry_id, entry_type, &config, preemption_bound);
+    }
+    let mut ecx = create_ecx_or_abort(tcx, entry_id, entry_type, &config);
+    if config.emulate_processes {
+        return eval_processes(ecx, entry_id, entry_type, &config);
+    }
+
+    // Perform the main execution.
+    let res: InterpResult<'_, !> = catch_ice(&mut ecx, |ecx| ecx.run_threads());
     let res = match res {
         Err(res) => res,
         // `Ok` can never happen
         Ok(never) => match never {},
     };
+    let return_code = finish_program(&mut ecx, res, config.ignore_leaks);
+    write_coverage(&ecx, &config);
+    return_code
+}
+
+/// Evaluates the entry function with `-Zmiri-explore-schedules`, once for every schedule with at
+/// most `preemption_bound` preemptions, until one of them fails. Returns like `eval_entry`; if a
+/// schedule fails, the preemptions that led to it are reported after the error. The coverage
+/// report covers all the schedules that were run.
+fn explore_schedules<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+    preemption_bound: u32,
+) -> Option<i64> {
+    let mut replay = Vec::new();
+    let mut schedules = 0u64;
+    let mut coverage: Option<Coverage> = None;
+    loop {
+        schedules += 1;
+        let mut ecx = create_ecx_or_abort(tcx, entry_id, entry_type, config);
+        ecx.machine.threads.explore_schedule(ScheduleExplorer::new(preemption_bound, replay));
+        let res: InterpResult<'_, !> = catch_ice(&mut ecx, |ecx| ecx.run_threads());
+        let res = match res {
+            Err(res) => res,
+            // `Ok` can never happen
+            Ok(never) => match never {},
+        };
+        let return_code = finish_program(&mut ecx, res, config.ignore_leaks);
+        if let Some(schedule_coverage) = ecx.machine.coverage.take() {
+            match &mut coverage {
+                Some(coverage) => coverage.merge(schedule_coverage),
+                None => coverage = Some(schedule_coverage),
+            }
+        }
+        let explorer = ecx.machine.threads.take_explorer().unwrap();
+        if return_code != Some(0) {
+            tcx.sess.note_without_error(format!("the failure happened in schedule {schedules}"));
+            let decisions = explorer.describe(tcx, &ecx.machine.threads);
+            if decisions.is_empty() {
+                tcx.sess.note_without_error("this is the default schedule, without preemptions");
+            }
+            for decision in decisions {
+                tcx.sess.note_without_error(decision);
+            }
+            ecx.machine.coverage = coverage;
+            write_coverage(&ecx, config);
+            return return_code;
+        }
+        match explorer.next_schedule() {
+            Some(next) => replay = next,
+            None => {
+                ecx.machine.coverage = coverage;
+                write_coverage(&ecx, config);
+                break;
+            }
+        }
+    }
+    tcx.sess.note_without_error(format!(
+        "all {schedules} schedules with a preemption bound of {preemption_bound} succeeded"
+    ));
+    Some(0)
+}
+
+/// Writes the coverage report of `ecx`, if `-Zmiri-coverage` is set.
+fn write_coverage<'mir, 'tcx: 'mir>(
+    ecx: &InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
+    config: &MiriConfig,
+) {
+    if let (Some(coverage), Some(path)) = (&ecx.machine.coverage, &config.coverage) {
+        if let Err(err) = coverage.write_lcov(*ecx.tcx, path) {
+            ecx.tcx.sess.fatal(format!(
+                "failed to write the coverage report to `{}`: {err}",
+                path.display()
+            ));
+        }
+    }
+}
+
+/// Cleans up after the program stopped with `res`, reports errors and checks for leaks.
+/// Returns `Some(return_code)` if program executed completed.
+/// Returns `None` if an evaluation error occurred.
+fn finish_program<'mir, 'tcx: 'mir>(
+    ecx: &mut InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
+    res: InterpErrorInfo<'tcx>,
+    ignore_leaks: bool,
+) -> Option<i64> {
+    let tcx = *ecx.tcx;
 
     // Machine cleanup. Only do this if all threads have terminated; threads that are still running
     // might cause Stacked Borrows errors (https://github.com/rust-lang/miri/issues/2396).
@@ -461,11 +585,11 @@ pub fn eval_entry<'tcx>(
         // might not have joined the main thread (https://github.com/rust-lang/miri/issues/2020,
         // https://github.com/rust-lang/miri/issues/2508).
         ecx.allow_data_races_all_threads_done();
-        EnvVars::cleanup(&mut ecx).expect("error during env var cleanup");
+        EnvVars::cleanup(ecx).expect("error during env var cleanup");
     }
 
     // Process the result.
-    let (return_code, leak_check) = report_error(&ecx, res)?;
+    let (return_code, leak_check) = report_error(ecx, res)?;
     if leak_check && !ignore_leaks {
         // Check for thread leaks.
         if !ecx.have_all_terminated() {
@@ -477,7 +601,7 @@ pub fn eval_entry<'tcx>(
         info!("Additional static roots: {:?}", ecx.machine.static_roots);
         let leaks = ecx.find_leaked_allocations(&ecx.machine.static_roots);
         if !leaks.is_empty() {
-            report_leaks(&ecx, leaks);
+            report_leaks(ecx, leaks);
             let leak_message = "the evaluated program leaked memory, pass `-Zmiri-ignore-leaks` to disable this check";
             if ecx.machine.collect_leak_backtraces {
                 // If we are collecting leak backtraces, each leak is a distinct error diagnostic.
@@ -494,6 +618,116 @@ pub fn eval_entry<'tcx>(
     Some(return_code)
 }
 
+/// Evaluates the entry function with `-Zmiri-emulate-processes`, in the root process `root` of the
+/// processes the program spawns. Every process has its own `InterpCx`, and they take turns to run
+/// for `PROCESS_TIME_SLICE` scheduling decisions. The evaluation ends when the root process
+/// stops, and returns like `eval_entry`; an error in any other process is reported when it
+/// happens and makes the evaluation fail as well.
+fn eval_processes<'mir, 'tcx: 'mir>(
+    mut root: InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>,
+    entry_id: DefId,
+    entry_type: EntryFnType,
+    config: &MiriConfig,
+) -> Option<i64> {
+    let tcx = *root.tcx;
+    let exe = config.args.first().cloned().unwrap_or_default();
+    let manager = Rc::new(RefCell::new(ProcessManager::new(exe)));
+    root.machine.process = Some(Process::new(ROOT_PID, Rc::clone(&manager)));
+    // The root process always comes first.
+    let mut processes = vec![root];
+
+    loop {
+        let mut progress = false;
+        let mut idx = 0;
+        while idx < processes.len() {
+            let ecx = &mut processes[idx];
+            let pid = ecx.machine.process.as_ref().unwrap().pid;
+            let killed = manager.borrow_mut().take_kill(pid);
+            let status = if let Some(signal) = killed {
+                // Killed processes stop right away, without any cleanup.
+                signal
+            } else {
+                // Other processes may have changed the state of the I/O objects this one waits
+                // for, or stopped.
+                ecx.io_state_changed();
+                let res = match catch_ice(ecx, |ecx| ecx.run_threads_for(PROCESS_TIME_SLICE)) {
+                    Ok(process_progress) => {
+                        progress |= process_progress;
+                        idx += 1;
+                        continue;
+                    }
+                    Err(res) => res,
+                };
+                let return_code = finish_program(ecx, res, config.ignore_leaks);
+                if pid == ROOT_PID {
+                    // Any processes that are still running are abandoned.
+                    let return_code = if manager.borrow().failed { None } else { return_code };
+                    write_processes_coverage(processes, config);
+                    return return_code;
+                }
+                match return_code {
+                    Some(code) => i32::try_from(code & 0xff).unwrap() << 8,
+                    None => {
+                        tcx.sess.note_without_error(format!(
+                            "the error above occurred in the process with PID {pid}"
+                        ));
+                        manager.borrow_mut().failed = true;
+                        // The parent sees the process as aborted.
+                        ecx.eval_libc_i32("SIGABRT")
+                    }
+                }
+            };
+            let mut ecx = processes.remove(idx);
+            let communicate = ecx.machine.communicate();
+            ecx.machine.file_handler.close_all(communicate);
+            if let Some(coverage) = ecx.machine.coverage.take() {
+                processes[0].machine.coverage.as_mut().unwrap().merge(coverage);
+            }
+            manager.borrow_mut().terminated(pid, status);
+            progress = true;
+        }
+
+        let spawned = manager.borrow_mut().take_spawned();
+        for process in spawned {
+            let mut config = config.clone();
+            config.args = process.args;
+            // The new process gets exactly the environment it was spawned with, even with
+            // isolation.
+            config.forwarded_env_vars =
+                process.env.iter().map(|(name, _)| name.to_string_lossy().into_owned()).collect();
+            config.env = process.env;
+            let mut ecx = create_ecx_or_abort(tcx, entry_id, entry_type, &config);
+            ecx.machine.file_handler.handles = process.fds;
+            ecx.machine.process = Some(Process::new(process.pid, Rc::clone(&manager)));
+            processes.push(ecx);
+            progress = true;
+        }
+
+        if !progress {
+            // All threads of all processes are blocked on I/O.
+            let res = err_machine_stop!(TerminationInfo::Deadlock).into();
+            let return_code = finish_program(&mut processes[0], res, config.ignore_leaks);
+            write_processes_coverage(processes, config);
+            return return_code;
+        }
+    }
+}
+
+/// Writes the coverage report of all `processes` together, if `-Zmiri-coverage` is set. The
+/// root process comes first.
+fn write_processes_coverage<'mir, 'tcx: 'mir>(
+    mut processes: Vec<InterpCx<'mir, 'tcx, MiriMachine<'mir, 'tcx>>>,
+    config: &MiriConfig,
+) {
+    let mut root = processes.remove(0);
+    for mut ecx in processes {
+        if let Some(coverage) = ecx.machine.coverage.take() {
+            root.machine.coverage.as_mut().unwrap().merge(coverage);
+        }
+    }
+    write_coverage(&root, config);
+}
+
 /// Turns an array of arguments into a Windows command line string.
 ///
 /// The string will be UTF-16 encoded and NUL terminated.
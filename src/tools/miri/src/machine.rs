@@ -410,6 +410,11 @@ pub struct MiriMachine<'mir, 'tcx> {
     pub(crate) file_handler: shims::unix::FileHandler,
     /// The table of directory descriptors.
     pub(crate) dir_handler: shims::unix::DirHandler,
+    /// The emulated loopback network.
+    pub(crate) network: shims::unix::Network,
+    /// The emulated process this interpreter runs, if `-Zmiri-emulate-processes` is set. This is
+    /// set up by `eval_processes`.
+    pub(crate) process: Option<shims::unix::Process>,
 
     /// This machine's monotone clock.
     pub(crate) clock: Clock,
@@ -475,6 +480,8 @@ pub struct MiriMachine<'mir, 'tcx> {
     pub(crate) report_progress: Option<u32>,
     // The total number of blocks that have been executed.
     pub(crate) basic_block_count: u64,
+    /// The execution counts of the basic blocks, if `-Zmiri-coverage` is set.
+    pub(crate) coverage: Option<Coverage>,
 
     /// Handle of the optional shared object file for external functions.
     #[cfg(target_os = "linux")]
@@ -503,6 +510,7 @@ impl<'mir, 'tcx> MiriMachine<'mir, 'tcx> {
     pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>) -> Self {
         let tcx = layout_cx.tcx;
         let local_crates = helpers::get_local_crates(tcx);
+        let coverage = config.coverage.as_ref().map(|_| Coverage::new(local_crates.clone()));
         let layouts =
             PrimitiveLayouts::new(layout_cx).expect("Couldn't get layouts of primitive types");
         let profiler = config.measureme_out.as_ref().map(|out| {
@@ -568,6 +576,8 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             enforce_abi: config.check_abi,
             file_handler: FileHandler::new(config.mute_stdout_stderr),
             dir_handler: Default::default(),
+            network: Default::default(),
+            process: None,
             layouts,
             threads: ThreadManager::default(),
             static_roots: Vec::new(),
@@ -587,6 +597,7 @@ pub(crate) fn new(config: &MiriConfig, layout_cx: LayoutCx<'tcx, TyCtxt<'tcx>>)
             preemption_rate: config.preemption_rate,
             report_progress: config.report_progress,
             basic_block_count: 0,
+            coverage,
             clock: Clock::new(config.isolated_op == IsolatedOp::Allow),
             #[cfg(target_os = "linux")]
             external_so_lib: config.external_so_file.as_ref().map(|lib_file_path| {
@@ -761,6 +772,8 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
             data_race,
             intptrcast,
             file_handler,
+            network: _,
+            process: _,
             tcx: _,
             isolated_op: _,
             validate: _,
@@ -783,6 +796,7 @@ fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
             preemption_rate: _,
             report_progress: _,
             basic_block_count: _,
+            coverage: _,
             external_so_lib: _,
             gc_interval: _,
             since_gc: _,
@@ -1248,6 +1262,10 @@ fn init_frame_extra(
             None
         };
 
+        if let Some(coverage) = &mut ecx.machine.coverage {
+            coverage.enter_frame(frame.instance, frame.body);
+        }
+
         let borrow_tracker = ecx.machine.borrow_tracker.as_ref();
 
         let extra = FrameExtra {
@@ -1275,6 +1293,13 @@ fn stack_mut<'a>(
     fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx> {
         ecx.machine.basic_block_count += 1u64; // a u64 that is only incremented by 1 will "never" overflow
         ecx.machine.since_gc += 1;
+        if ecx.machine.coverage.is_some() {
+            let frame = ecx.frame();
+            if let Either::Left(loc) = frame.loc {
+                let instance = frame.instance;
+                ecx.machine.coverage.as_mut().unwrap().execute_block(instance, loc.block);
+            }
+        }
         // Possibly report our progress.
         if let Some(report_progress) = ecx.machine.report_progress {
             if ecx.machine.basic_block_count % u64::from(report_progress) == 0 {
@@ -1294,7 +1319,7 @@ fn before_terminator(ecx: &mut InterpCx<'mir, 'tcx, Self>) -> InterpResult<'tcx>
         }
 
         // These are our preemption points.
-        ecx.maybe_preempt_active_thread();
+        ecx.maybe_preempt_active_thread()?;
 
         // Make sure some time passes.
         ecx.machine.clock.tick();
@@ -468,6 +468,10 @@ fn getpid(&mut self) -> InterpResult<'tcx, i32> {
         let this = self.eval_context_mut();
         this.assert_target_os_is_unix("getpid");
 
+        if let Some(process) = &this.machine.process {
+            return Ok(process.pid);
+        }
+
         this.check_no_isolation("`getpid`")?;
 
         // The reason we need to do this wacky of a conversion is because
@@ -0,0 +1,23 @@
+//! The state of file descriptions that is shared by several kinds of them, independently of the
+//! target: pipes, socketpairs and TCP connections all buffer their data in `ByteStream`s, and all
+//! file descriptions report what they are ready for to `poll` and `epoll`.
+
+pub mod stream;
+
+/// The events a file description is ready for.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EpollReadiness {
+    /// `EPOLLIN`: reading would not block.
+    pub readable: bool,
+    /// `EPOLLOUT`: writing would not block.
+    pub writable: bool,
+    /// `EPOLLRDHUP`: the peer closed its writing end.
+    pub peer_write_closed: bool,
+    /// `EPOLLHUP`: the peer closed the connection entirely.
+    pub hangup: bool,
+    /// `EPOLLERR`: an error condition, like a pipe without readers.
+    pub error: bool,
+    /// A counter that is incremented whenever the state of the file description changes, for
+    /// edge-triggered events.
+    pub generation: u64,
+}
@@ -0,0 +1,95 @@
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind};
+
+/// The number of bytes a `ByteStream` can hold before writes would block. This is the default
+/// capacity of a pipe on Linux.
+const STREAM_CAPACITY: usize = 0x10000;
+
+/// A unidirectional stream of bytes, which is the shared state of a pipe, and of each direction
+/// of a socketpair or TCP connection.
+#[derive(Debug)]
+pub struct ByteStream {
+    /// The bytes that were written but not read yet.
+    buffer: VecDeque<u8>,
+    /// The number of open file descriptions reading from this stream.
+    pub readers: usize,
+    /// The number of open file descriptions writing to this stream.
+    pub writers: usize,
+    /// Incremented whenever the stream changes in a way that is of interest to its readers, that
+    /// is when data is written or a writer is closed. See `EpollReadiness::generation`.
+    pub read_generation: u64,
+    /// Incremented whenever the stream changes in a way that is of interest to its writers, that
+    /// is when data is read or a reader is closed.
+    pub write_generation: u64,
+}
+
+impl ByteStream {
+    /// Creates an empty stream with one reader and one writer.
+    pub fn new() -> Self {
+        ByteStream {
+            buffer: VecDeque::new(),
+            readers: 1,
+            writers: 1,
+            read_generation: 0,
+            write_generation: 0,
+        }
+    }
+
+    /// Whether reading from the stream would not block: there is data, or all writers are gone
+    /// and the reader would see the end of the stream.
+    pub fn readable(&self) -> bool {
+        !self.buffer.is_empty() || self.writers == 0
+    }
+
+    /// Whether writing to the stream would not block.
+    pub fn writable(&self) -> bool {
+        self.buffer.len() < STREAM_CAPACITY || self.readers == 0
+    }
+
+    pub fn read(&mut self, bytes: &mut [u8]) -> io::Result<usize> {
+        let len = self.peek(bytes)?;
+        self.buffer.drain(..len);
+        self.write_generation = self.write_generation.wrapping_add(1);
+        Ok(len)
+    }
+
+    /// Like `read`, but leaves the bytes in the stream.
+    pub fn peek(&self, bytes: &mut [u8]) -> io::Result<usize> {
+        if self.buffer.is_empty() && !bytes.is_empty() {
+            return if self.writers == 0 { Ok(0) } else { Err(ErrorKind::WouldBlock.into()) };
+        }
+        let len = bytes.len().min(self.buffer.len());
+        for (dest, byte) in bytes.iter_mut().zip(self.buffer.iter()) {
+            *dest = *byte;
+        }
+        Ok(len)
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if self.readers == 0 {
+            // The Rust runtime ignores `SIGPIPE`, so the write just fails with `EPIPE`.
+            return Err(ErrorKind::BrokenPipe.into());
+        }
+        let len = bytes.len().min(STREAM_CAPACITY.saturating_sub(self.buffer.len()));
+        if len == 0 && !bytes.is_empty() {
+            return Err(ErrorKind::WouldBlock.into());
+        }
+        self.buffer.extend(&bytes[..len]);
+        self.read_generation = self.read_generation.wrapping_add(1);
+        Ok(len)
+    }
+
+    pub fn close_reader(&mut self) {
+        self.readers = self.readers.checked_sub(1).unwrap();
+        if self.readers == 0 {
+            // Nobody can read these bytes anymore.
+            self.buffer.clear();
+        }
+        self.write_generation = self.write_generation.wrapping_add(1);
+    }
+
+    pub fn close_writer(&mut self) {
+        self.writers = self.writers.checked_sub(1).unwrap();
+        self.read_generation = self.read_generation.wrapping_add(1);
+    }
+}
@@ -11,6 +11,8 @@
 use shims::foreign_items::EmulateByNameResult;
 use shims::unix::fs::EvalContextExt as _;
 use shims::unix::mem::EvalContextExt as _;
+use shims::unix::net::EvalContextExt as _;
+use shims::unix::process::EvalContextExt as _;
 use shims::unix::sync::EvalContextExt as _;
 use shims::unix::thread::EvalContextExt as _;
 
@@ -80,8 +82,7 @@ fn emulate_foreign_item_by_name(
                 let fd = this.read_scalar(fd)?.to_i32()?;
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(count)?;
-                let result = this.read(fd, buf, count)?;
-                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                this.read(fd, buf, count, dest)?;
             }
             "write" => {
                 let [fd, buf, n] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -89,9 +90,7 @@ fn emulate_foreign_item_by_name(
                 let buf = this.read_pointer(buf)?;
                 let count = this.read_target_usize(n)?;
                 trace!("Called write({:?}, {:?}, {:?})", fd, buf, count);
-                let result = this.write(fd, buf, count)?;
-                // Now, `result` is the value we return back to the program.
-                this.write_scalar(Scalar::from_target_isize(result, this), dest)?;
+                this.write(fd, buf, count, dest)?;
             }
             "unlink" => {
                 let [path] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -175,6 +174,138 @@ fn emulate_foreign_item_by_name(
                 this.write_scalar(Scalar::from_i32(result), dest)?;
             }
 
+            // Socket related shims
+            "socket" => {
+                let [domain, type_, protocol] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.socket(domain, type_, protocol)?;
+                this.write_scalar(result, dest)?;
+            }
+            "bind" => {
+                let [fd, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.bind(fd, addr, addrlen)?;
+                this.write_scalar(result, dest)?;
+            }
+            "listen" => {
+                let [fd, backlog] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.listen(fd, backlog)?;
+                this.write_scalar(result, dest)?;
+            }
+            "connect" => {
+                let [fd, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.connect(fd, addr, addrlen)?;
+                this.write_scalar(result, dest)?;
+            }
+            "accept" => {
+                let [fd, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.accept(fd, addr, addrlen, None, dest)?;
+            }
+            "send" => {
+                let [fd, buf, len, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.send(fd, buf, len, flags, None, dest)?;
+            }
+            "sendto" => {
+                let [fd, buf, len, flags, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.send(fd, buf, len, flags, Some((addr, addrlen)), dest)?;
+            }
+            "recv" => {
+                let [fd, buf, len, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.recv(fd, buf, len, flags, None, dest)?;
+            }
+            "recvfrom" => {
+                let [fd, buf, len, flags, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.recv(fd, buf, len, flags, Some((addr, addrlen)), dest)?;
+            }
+            "getsockname" => {
+                let [fd, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getsockname(fd, addr, addrlen)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getpeername" => {
+                let [fd, addr, addrlen] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getpeername(fd, addr, addrlen)?;
+                this.write_scalar(result, dest)?;
+            }
+            "setsockopt" => {
+                let [fd, level, name, value, len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.setsockopt(fd, level, name, value, len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getsockopt" => {
+                let [fd, level, name, value, len] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.getsockopt(fd, level, name, value, len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "shutdown" => {
+                let [fd, how] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.shutdown(fd, how)?;
+                this.write_scalar(result, dest)?;
+            }
+            "ioctl" => {
+                // `ioctl` is variadic. The argument count is checked based on the request in
+                // `this.ioctl()`, so we do not use `check_shim` here.
+                this.check_abi_and_shim_symbol_clash(abi, Abi::C { unwind: false }, link_name)?;
+                let result = this.ioctl(args)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+
+            // Process related shims
+            "posix_spawn_file_actions_init" => {
+                let [file_actions] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn_file_actions_init(file_actions)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawn_file_actions_destroy" => {
+                let [file_actions] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn_file_actions_destroy(file_actions)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawn_file_actions_adddup2" => {
+                let [file_actions, fd, newfd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn_file_actions_adddup2(file_actions, fd, newfd)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawn_file_actions_addclose" => {
+                let [file_actions, fd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn_file_actions_addclose(file_actions, fd)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawnattr_init" => {
+                let [attr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawnattr_init(attr)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawnattr_setflags" => {
+                let [attr, flags] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawnattr_setflags(attr, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "posix_spawnattr_setsigdefault" | "posix_spawnattr_setsigmask" => {
+                let [attr, sigset] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.read_pointer(attr)?;
+                this.read_pointer(sigset)?;
+                // Signals are not supported, so there is nothing to do here.
+                this.write_null(dest)?;
+            }
+            "posix_spawnattr_destroy" => {
+                let [attr] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.read_pointer(attr)?;
+                this.write_null(dest)?;
+            }
+            "posix_spawn" | "posix_spawnp" => {
+                let [pid, path, file_actions, attrp, argv, envp] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.posix_spawn(link_name.as_str(), pid, path, file_actions, attrp, argv, envp)?;
+                this.write_scalar(result, dest)?;
+            }
+            "waitpid" => {
+                let [pid, status, options] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.waitpid(pid, status, options, dest)?;
+            }
+            "kill" => {
+                let [pid, sig] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.kill(pid, sig)?;
+                this.write_scalar(result, dest)?;
+            }
+
             // Time related shims
             "gettimeofday" => {
                 let [tv, tz] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
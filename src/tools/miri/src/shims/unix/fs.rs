@@ -14,7 +14,9 @@
 use rustc_middle::ty::TyCtxt;
 use rustc_target::abi::{Align, Size};
 
+use crate::concurrency::thread::BlockingIoOp;
 use crate::shims::os_str::bytes_to_os_str;
+use crate::shims::unix::fd::EpollReadiness;
 use crate::*;
 use shims::time::system_time_to_duration;
 
@@ -66,6 +68,26 @@ fn is_tty(&self) -> bool {
         false
     }
 
+    /// Whether `O_NONBLOCK` is set, so that `read` and `write` fail with `EWOULDBLOCK` instead of
+    /// blocking the thread when the file description is not ready.
+    fn is_nonblocking(&self) -> bool {
+        false
+    }
+
+    /// Sets or clears `O_NONBLOCK`.
+    fn set_nonblocking<'tcx>(&mut self, _nonblock: bool) -> InterpResult<'tcx> {
+        throw_unsup_format!("cannot change the blocking mode of {}", self.name());
+    }
+
+    /// The events this file description is ready for, or `None` if it cannot be watched with
+    /// `epoll`.
+    fn epoll_readiness(&self) -> Option<EpollReadiness> {
+        None
+    }
+
+    /// Visits the tags of the pointers stored in this file description.
+    fn visit_tags(&self, _visit: &mut dyn FnMut(BorTag)) {}
+
     #[cfg(unix)]
     fn as_unix_host_fd(&self) -> Option<i32> {
         None
@@ -270,14 +292,56 @@ fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
     }
 }
 
+/// `/dev/null`, which can be opened even with isolation enabled since it does not leak any
+/// information about the host.
+#[derive(Debug)]
+struct DevNull;
+
+impl FileDescriptor for DevNull {
+    fn name(&self) -> &'static str {
+        "/dev/null"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        _bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(Ok(0))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(Ok(bytes.len()))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        Ok(Ok(0))
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(DevNull))
+    }
+}
+
 #[derive(Debug)]
 pub struct FileHandler {
     pub handles: BTreeMap<i32, Box<dyn FileDescriptor>>,
 }
 
 impl VisitTags for FileHandler {
-    fn visit_tags(&self, _visit: &mut dyn FnMut(BorTag)) {
-        // All our FileDescriptor do not have any tags.
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        for file_descriptor in self.handles.values() {
+            file_descriptor.visit_tags(visit);
+        }
     }
 }
 
@@ -295,6 +359,15 @@ pub(crate) fn new(mute_stdout_stderr: bool) -> FileHandler {
         FileHandler { handles }
     }
 
+    /// Closes all file descriptors, when the process they belong to stopped.
+    pub(crate) fn close_all(&mut self, communicate_allowed: bool) {
+        for file_descriptor in std::mem::take(&mut self.handles).into_values() {
+            // Some file descriptions, like the standard streams of the host, cannot be closed and
+            // are just dropped.
+            let _ = file_descriptor.close(communicate_allowed);
+        }
+    }
+
     pub fn insert_fd(&mut self, file_handle: Box<dyn FileDescriptor>) -> i32 {
         self.insert_fd_with_min_fd(file_handle, 0)
     }
@@ -329,8 +402,101 @@ fn insert_fd_with_min_fd(&mut self, file_handle: Box<dyn FileDescriptor>, min_fd
     }
 }
 
+/// A `read` or `write` that has to wait until the file description is ready if it is blocking.
+#[derive(Clone, Debug)]
+enum FdOp {
+    Read { buf: Pointer<Option<Provenance>>, count: u64 },
+    Write { buf: Pointer<Option<Provenance>>, count: u64 },
+}
+
+impl VisitTags for FdOp {
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        match self {
+            FdOp::Read { buf, count: _ } | FdOp::Write { buf, count: _ } => buf.visit_tags(visit),
+        }
+    }
+}
+
+/// An `FdOp` on `fd`, whose result is written to `dest`.
+struct FdOpCall<'tcx> {
+    fd: i32,
+    op: FdOp,
+    dest: PlaceTy<'tcx, Provenance>,
+}
+
+impl VisitTags for FdOpCall<'_> {
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        let FdOpCall { fd: _, op, dest } = self;
+        op.visit_tags(visit);
+        dest.visit_tags(visit);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> BlockingIoOp<'mir, 'tcx> for FdOpCall<'tcx> {
+    fn try_complete(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx, bool> {
+        let Some(result) = this.try_fd_op(self.fd, &self.op)? else {
+            return Ok(false);
+        };
+        this.write_int(result, &self.dest)?;
+        Ok(true)
+    }
+}
+
 impl<'mir, 'tcx: 'mir> EvalContextExtPrivate<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
 trait EvalContextExtPrivate<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Performs `op` on `fd` and writes the result to `dest`. If the file description is not
+    /// ready, the operation either fails with `EWOULDBLOCK`, or blocks the thread until it is
+    /// ready.
+    fn fd_op(&mut self, fd: i32, op: FdOp, dest: &PlaceTy<'tcx, Provenance>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        this.block_on_io_op(FdOpCall { fd, op, dest: dest.clone() })
+    }
+
+    /// Tries to perform `op` on `fd`. Returns `None` if the thread should block until the file
+    /// description is ready, and the result of the operation otherwise.
+    fn try_fd_op(&mut self, fd: i32, op: &FdOp) -> InterpResult<'tcx, Option<i64>> {
+        let this = self.eval_context_mut();
+        let communicate = this.machine.communicate();
+
+        let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) else {
+            trace!("FD not found");
+            return Ok(Some(this.handle_not_found()?));
+        };
+        let nonblocking = file_descriptor.is_nonblocking();
+
+        let result = match *op {
+            FdOp::Read { buf, count } => {
+                let file_descriptor = this.machine.file_handler.handles.get_mut(&fd).unwrap();
+                trace!("read: FD mapped to {:?}", file_descriptor);
+                // We want to read at most `count` bytes. We are sure that `count` is not negative
+                // because it was a target's `usize`. Also we are sure that its smaller than
+                // `usize::MAX` because it is bounded by the host's `isize`.
+                let mut bytes = vec![0; usize::try_from(count).unwrap()];
+                let result = file_descriptor.read(communicate, &mut bytes, *this.tcx)?;
+                if result.is_ok() {
+                    // If reading to `bytes` did not fail, we write those bytes to the buffer.
+                    this.write_bytes_ptr(buf, bytes)?;
+                }
+                result
+            }
+            FdOp::Write { buf, count } => {
+                let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(count))?;
+                file_descriptor.write(communicate, bytes, *this.tcx)?
+            }
+        };
+
+        match result {
+            Err(e) if e.kind() == ErrorKind::WouldBlock && !nonblocking => Ok(None),
+            result => {
+                this.io_state_changed();
+                // `File::read` and `File::write` never return a value larger than `count`, so
+                // this cannot fail.
+                let result = result.map(|c| i64::try_from(c).unwrap());
+                Ok(Some(this.try_unwrap_io_result(result)?))
+            }
+        }
+    }
+
     fn macos_stat_write_buf(
         &mut self,
         metadata: FileMetadata,
@@ -612,6 +778,9 @@ fn open(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
 
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if path == Path::new("/dev/null") {
+                return Ok(this.machine.file_handler.insert_fd(Box::new(DevNull)));
+            }
             this.reject_in_isolation("`open`", reject_with)?;
             this.set_last_error_from_io_error(ErrorKind::PermissionDenied)?;
             return Ok(-1);
@@ -712,6 +881,8 @@ fn close(&mut self, fd_op: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, Scalar
         Ok(Scalar::from_i32(
             if let Some(file_descriptor) = this.machine.file_handler.handles.remove(&fd) {
                 let result = file_descriptor.close(this.machine.communicate())?;
+                // Closing one end of a pipe or socket changes the state of the other end.
+                this.io_state_changed();
                 this.try_unwrap_io_result(result)?
             } else {
                 this.handle_not_found()?
@@ -735,7 +906,8 @@ fn read(
         fd: i32,
         buf: Pointer<Option<Provenance>>,
         count: u64,
-    ) -> InterpResult<'tcx, i64> {
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         // Isolation check is done via `FileDescriptor` trait.
@@ -755,35 +927,8 @@ fn read(
         let count = count
             .min(u64::try_from(this.target_isize_max()).unwrap())
             .min(u64::try_from(isize::MAX).unwrap());
-        let communicate = this.machine.communicate();
 
-        if let Some(file_descriptor) = this.machine.file_handler.handles.get_mut(&fd) {
-            trace!("read: FD mapped to {:?}", file_descriptor);
-            // We want to read at most `count` bytes. We are sure that `count` is not negative
-            // because it was a target's `usize`. Also we are sure that its smaller than
-            // `usize::MAX` because it is bounded by the host's `isize`.
-            let mut bytes = vec![0; usize::try_from(count).unwrap()];
-            // `File::read` never returns a value larger than `count`,
-            // so this cannot fail.
-            let result = file_descriptor
-                .read(communicate, &mut bytes, *this.tcx)?
-                .map(|c| i64::try_from(c).unwrap());
-
-            match result {
-                Ok(read_bytes) => {
-                    // If reading to `bytes` did not fail, we write those bytes to the buffer.
-                    this.write_bytes_ptr(buf, bytes)?;
-                    Ok(read_bytes)
-                }
-                Err(e) => {
-                    this.set_last_error_from_io_error(e.kind())?;
-                    Ok(-1)
-                }
-            }
-        } else {
-            trace!("read: FD not found");
-            this.handle_not_found()
-        }
+        this.fd_op(fd, FdOp::Read { buf, count }, dest)
     }
 
     fn write(
@@ -791,7 +936,8 @@ fn write(
         fd: i32,
         buf: Pointer<Option<Provenance>>,
         count: u64,
-    ) -> InterpResult<'tcx, i64> {
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         // Isolation check is done via `FileDescriptor` trait.
@@ -809,17 +955,8 @@ fn write(
         let count = count
             .min(u64::try_from(this.target_isize_max()).unwrap())
             .min(u64::try_from(isize::MAX).unwrap());
-        let communicate = this.machine.communicate();
 
-        if let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) {
-            let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(count))?;
-            let result = file_descriptor
-                .write(communicate, bytes, *this.tcx)?
-                .map(|c| i64::try_from(c).unwrap());
-            this.try_unwrap_io_result(result)
-        } else {
-            this.handle_not_found()
-        }
+        this.fd_op(fd, FdOp::Write { buf, count }, dest)
     }
 
     fn lseek64(
@@ -1667,15 +1804,22 @@ fn readlink(
         let buf = this.read_pointer(buf_op)?;
         let bufsize = this.read_target_usize(bufsize_op)?;
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
-            this.reject_in_isolation("`readlink`", reject_with)?;
-            let eacc = this.eval_libc("EACCES");
-            this.set_last_error(eacc)?;
-            return Ok(-1);
-        }
+        let result = match &this.machine.process {
+            // The executable of the emulated processes is the interpreted program, and not Miri.
+            Some(process) if pathname == Path::new("/proc/self/exe") =>
+                Ok(PathBuf::from(&process.manager.borrow().exe)),
+            _ => {
+                // Reject if isolation is enabled.
+                if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+                    this.reject_in_isolation("`readlink`", reject_with)?;
+                    let eacc = this.eval_libc("EACCES");
+                    this.set_last_error(eacc)?;
+                    return Ok(-1);
+                }
 
-        let result = std::fs::read_link(pathname);
+                std::fs::read_link(pathname)
+            }
+        };
         match result {
             Ok(resolved) => {
                 // 'readlink' truncates the resolved path if the provided buffer is not large
@@ -1,16 +1,23 @@
+use std::time::Duration;
+
 use rustc_middle::ty::ScalarInt;
 
+use crate::concurrency::thread::{IoCallback, MachineCallback, Time};
+use crate::shims::unix::fs::FileDescriptor;
 use crate::*;
-use epoll::{Epoll, EpollEvent};
+use epoll::{Epoll, EpollEvent, InterestList};
 use event::Event;
+use pipe::Pipe;
 use socketpair::SocketPair;
 
 use shims::unix::fs::EvalContextExt as _;
 
 use std::cell::Cell;
+use std::rc::Rc;
 
 pub mod epoll;
 pub mod event;
+pub mod pipe;
 pub mod socketpair;
 
 impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
@@ -30,7 +37,7 @@ fn epoll_create1(
 
         let epoll_cloexec = this.eval_libc_i32("EPOLL_CLOEXEC");
         if flags == epoll_cloexec {
-            // Miri does not support exec, so this flag has no effect.
+            // This flag has no effect, see `posix_spawn`.
         } else if flags != 0 {
             throw_unsup_format!("epoll_create1 flags {flags} are not implemented");
         }
@@ -70,42 +77,67 @@ fn epoll_ctl(
         let epoll_ctl_mod = this.eval_libc_i32("EPOLL_CTL_MOD");
         let epoll_ctl_del = this.eval_libc_i32("EPOLL_CTL_DEL");
 
-        if op == epoll_ctl_add || op == epoll_ctl_mod {
-            let event = this.deref_pointer_as(event, this.libc_ty_layout("epoll_event"))?;
-
-            let events = this.project_field(&event, 0)?;
-            let events = this.read_scalar(&events)?.to_u32()?;
-            let data = this.project_field(&event, 1)?;
-            let data = this.read_scalar(&data)?;
-            let event = EpollEvent { events, data };
-
-            if let Some(epfd) = this.machine.file_handler.handles.get_mut(&epfd) {
-                let epfd = epfd
-                    .as_any_mut()
-                    .downcast_mut::<Epoll>()
-                    .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
+        let Some(epoll) = this.machine.file_handler.handles.get(&epfd) else {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        };
+        let epoll = epoll
+            .as_any()
+            .downcast_ref::<Epoll>()
+            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?
+            .clone();
+        let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) else {
+            return Ok(Scalar::from_i32(this.handle_not_found()?));
+        };
+        let error = if fd == epfd {
+            Some("EINVAL")
+        } else if file_descriptor.epoll_readiness().is_none() {
+            // This is what Linux does for regular files, which are always ready.
+            Some("EPERM")
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            let error = this.eval_libc(error);
+            this.set_last_error(error)?;
+            return Ok(Scalar::from_i32(-1));
+        }
 
-                epfd.file_descriptors.insert(fd, event);
-                Ok(Scalar::from_i32(0))
+        let mut interest_list = epoll.interest_list.borrow_mut();
+        let registered = interest_list.file_descriptors.contains_key(&fd);
+        let error = if op == epoll_ctl_add || op == epoll_ctl_mod {
+            if op == epoll_ctl_add && registered {
+                Some("EEXIST")
+            } else if op == epoll_ctl_mod && !registered {
+                Some("ENOENT")
             } else {
-                Ok(Scalar::from_i32(this.handle_not_found()?))
+                let event = this.deref_pointer_as(event, this.libc_ty_layout("epoll_event"))?;
+
+                let events = this.project_field(&event, 0)?;
+                let events = this.read_scalar(&events)?.to_u32()?;
+                let data = this.project_field(&event, 1)?;
+                let data = this.read_scalar(&data)?;
+                // Modifying an event re-arms it, so it is reported again if the file descriptor is
+                // ready, even for edge-triggered and one-shot events.
+                let event = EpollEvent { events, data, reported_generation: None, disabled: false };
+
+                interest_list.file_descriptors.insert(fd, event);
+                None
             }
         } else if op == epoll_ctl_del {
-            if let Some(epfd) = this.machine.file_handler.handles.get_mut(&epfd) {
-                let epfd = epfd
-                    .as_any_mut()
-                    .downcast_mut::<Epoll>()
-                    .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_ctl`"))?;
-
-                epfd.file_descriptors.remove(&fd);
-                Ok(Scalar::from_i32(0))
-            } else {
-                Ok(Scalar::from_i32(this.handle_not_found()?))
-            }
+            if interest_list.file_descriptors.remove(&fd).is_some() { None } else { Some("ENOENT") }
         } else {
-            let einval = this.eval_libc("EINVAL");
-            this.set_last_error(einval)?;
+            Some("EINVAL")
+        };
+        drop(interest_list);
+
+        if let Some(error) = error {
+            let error = this.eval_libc(error);
+            this.set_last_error(error)?;
             Ok(Scalar::from_i32(-1))
+        } else {
+            // The file descriptor may already be ready.
+            this.io_state_changed();
+            Ok(Scalar::from_i32(0))
         }
     }
 
@@ -144,25 +176,392 @@ fn epoll_wait(
         events: &OpTy<'tcx, Provenance>,
         maxevents: &OpTy<'tcx, Provenance>,
         timeout: &OpTy<'tcx, Provenance>,
-    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
         let this = self.eval_context_mut();
 
         let epfd = this.read_scalar(epfd)?.to_i32()?;
-        let _events = this.read_scalar(events)?.to_pointer(this)?;
-        let _maxevents = this.read_scalar(maxevents)?.to_i32()?;
-        let _timeout = this.read_scalar(timeout)?.to_i32()?;
-
-        if let Some(epfd) = this.machine.file_handler.handles.get_mut(&epfd) {
-            let _epfd = epfd
-                .as_any_mut()
-                .downcast_mut::<Epoll>()
-                .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?;
-
-            // FIXME return number of events ready when scheme for marking events ready exists
-            throw_unsup_format!("returning ready events from epoll_wait is not yet implemented");
-        } else {
-            Ok(Scalar::from_i32(this.handle_not_found()?))
+        let maxevents = this.read_scalar(maxevents)?.to_i32()?;
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
+
+        let Ok(maxevents) = u32::try_from(maxevents) else {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            this.write_scalar(Scalar::from_i32(-1), dest)?;
+            return Ok(());
+        };
+        if maxevents == 0 {
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            this.write_scalar(Scalar::from_i32(-1), dest)?;
+            return Ok(());
+        }
+        let Some(epoll) = this.machine.file_handler.handles.get(&epfd) else {
+            let result = this.handle_not_found()?;
+            this.write_scalar(Scalar::from_i32(result), dest)?;
+            return Ok(());
+        };
+        let epoll = epoll
+            .as_any()
+            .downcast_ref::<Epoll>()
+            .ok_or_else(|| err_unsup_format!("non-epoll FD passed to `epoll_wait`"))?
+            .clone();
+        let events = this.deref_pointer_as(events, this.libc_ty_layout("epoll_event"))?;
+
+        let count = this.epoll_report_events(&epoll, &events, maxevents)?;
+        if count > 0 || timeout == 0 {
+            this.write_scalar(Scalar::from_i32(count), dest)?;
+            return Ok(());
+        }
+
+        // Nothing is ready yet, so we block the thread until an I/O object changes state in a
+        // way that makes one of the watched file descriptors ready, or until the timeout expires.
+        // A negative timeout means to wait forever.
+        let thread = this.get_active_thread();
+        this.block_thread(thread);
+
+        struct EpollWaitCallback<'tcx> {
+            thread: ThreadId,
+            epoll: Epoll,
+            events: MPlaceTy<'tcx, Provenance>,
+            maxevents: u32,
+            dest: PlaceTy<'tcx, Provenance>,
+        }
+
+        impl<'tcx> VisitTags for EpollWaitCallback<'tcx> {
+            fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                let EpollWaitCallback { thread: _, epoll, events, maxevents: _, dest } = self;
+                FileDescriptor::visit_tags(epoll, visit);
+                events.visit_tags(visit);
+                dest.visit_tags(visit);
+            }
         }
+
+        impl<'mir, 'tcx: 'mir> IoCallback<'mir, 'tcx> for EpollWaitCallback<'tcx> {
+            fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx, bool> {
+                let count = this.epoll_report_events(&self.epoll, &self.events, self.maxevents)?;
+                if count == 0 {
+                    return Ok(false);
+                }
+                this.unblock_thread(self.thread);
+                this.unregister_timeout_callback_if_exists(self.thread);
+                this.write_scalar(Scalar::from_i32(count), &self.dest)?;
+                Ok(true)
+            }
+        }
+
+        this.register_io_callback(
+            thread,
+            Box::new(EpollWaitCallback { thread, epoll, events, maxevents, dest: dest.clone() }),
+        );
+
+        if let Ok(timeout) = u64::try_from(timeout) {
+            struct Callback<'tcx> {
+                thread: ThreadId,
+                dest: PlaceTy<'tcx, Provenance>,
+            }
+
+            impl<'tcx> VisitTags for Callback<'tcx> {
+                fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                    let Callback { thread: _, dest } = self;
+                    dest.visit_tags(visit);
+                }
+            }
+
+            impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+                fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                    // No file descriptor became ready in time.
+                    this.unblock_thread(self.thread);
+                    this.unregister_io_callback_if_exists(self.thread);
+                    this.write_scalar(Scalar::from_i32(0), &self.dest)?;
+                    Ok(())
+                }
+            }
+
+            let timeout_time = Time::Monotonic(
+                this.machine.clock.now().checked_add(Duration::from_millis(timeout)).unwrap(),
+            );
+            this.register_timeout_callback(
+                thread,
+                timeout_time,
+                Box::new(Callback { thread, dest: dest.clone() }),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Writes the events of the ready file descriptors in the interest list of `epoll` to
+    /// `events`, an array of `maxevents` `epoll_event`s, and returns how many were written.
+    ///
+    /// Level-triggered events are reported as long as the file descriptor is ready. Edge-triggered
+    /// events (`EPOLLET`) are only reported again once the state of the file descriptor changed
+    /// since the last time they were reported, and one-shot events (`EPOLLONESHOT`) are only
+    /// reported again once they were modified with `EPOLL_CTL_MOD`.
+    fn epoll_report_events(
+        &mut self,
+        epoll: &Epoll,
+        events: &MPlaceTy<'tcx, Provenance>,
+        maxevents: u32,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let epollin = this.eval_libc_u32("EPOLLIN");
+        let epollout = this.eval_libc_u32("EPOLLOUT");
+        let epollrdhup = this.eval_libc_u32("EPOLLRDHUP");
+        let epollhup = this.eval_libc_u32("EPOLLHUP");
+        let epollerr = this.eval_libc_u32("EPOLLERR");
+        let epollet = this.eval_libc_u32("EPOLLET");
+        let epolloneshot = this.eval_libc_u32("EPOLLONESHOT");
+
+        let mut interest_list = epoll.interest_list.borrow_mut();
+        let InterestList { file_descriptors, last_reported } = &mut *interest_list;
+        // Start looking after the file descriptor that was reported last, so that all ready file
+        // descriptors get reported in turn.
+        let mut fds: Vec<i32> = file_descriptors.keys().copied().collect();
+        let start = fds.partition_point(|&fd| Some(fd) <= *last_reported);
+        fds.rotate_left(start);
+
+        let mut ready = Vec::new();
+        let mut closed = Vec::new();
+        for fd in fds {
+            if ready.len() == usize::try_from(maxevents).unwrap() {
+                break;
+            }
+            // A file descriptor is removed from the interest list once it is closed. We also do
+            // that if the same number was then reused for another kind of file descriptor.
+            let Some(readiness) = this
+                .machine
+                .file_handler
+                .handles
+                .get(&fd)
+                .and_then(|file_descriptor| file_descriptor.epoll_readiness())
+            else {
+                closed.push(fd);
+                continue;
+            };
+            let event = file_descriptors.get_mut(&fd).unwrap();
+            if event.disabled {
+                continue;
+            }
+
+            let mut flags = 0;
+            if readiness.readable {
+                flags |= epollin;
+            }
+            if readiness.writable {
+                flags |= epollout;
+            }
+            if readiness.peer_write_closed {
+                flags |= epollrdhup;
+            }
+            if readiness.hangup {
+                flags |= epollhup;
+            }
+            if readiness.error {
+                flags |= epollerr;
+            }
+            // `EPOLLHUP` and `EPOLLERR` are always reported, even if they were not asked for.
+            let flags = flags & (event.events | epollhup | epollerr);
+            if flags == 0 {
+                continue;
+            }
+            if event.events & epollet != 0
+                && event.reported_generation == Some(readiness.generation)
+            {
+                continue;
+            }
+
+            event.reported_generation = Some(readiness.generation);
+            if event.events & epolloneshot != 0 {
+                event.disabled = true;
+            }
+            ready.push((flags, event.data));
+            *last_reported = Some(fd);
+        }
+        for fd in closed {
+            file_descriptors.remove(&fd);
+        }
+        drop(interest_list);
+
+        for (i, (flags, data)) in ready.iter().enumerate() {
+            let offset = events.layout.size.checked_mul(u64::try_from(i).unwrap(), this).unwrap();
+            let event = events.offset(offset, events.layout, this)?;
+            let events_place = this.project_field(&event, 0)?;
+            this.write_scalar(Scalar::from_u32(*flags), &events_place)?;
+            let data_place = this.project_field(&event, 1)?;
+            this.write_scalar(*data, &data_place)?;
+        }
+        Ok(i32::try_from(ready.len()).unwrap())
+    }
+
+    /// Waits until one of the `nfds` file descriptors in the `pollfd` array `fds` is ready for
+    /// the requested events, or until `timeout` milliseconds passed. A negative timeout means to
+    /// wait forever. Returns the number of file descriptors with events in their `revents`.
+    ///
+    /// File descriptors that cannot be watched with `epoll`, like regular files, are always
+    /// ready.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/poll.2.html>
+    fn poll(
+        &mut self,
+        fds: &OpTy<'tcx, Provenance>,
+        nfds: &OpTy<'tcx, Provenance>,
+        timeout: &OpTy<'tcx, Provenance>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fds = this.deref_pointer_as(fds, this.libc_ty_layout("pollfd"))?;
+        let nfds = this.read_target_usize(nfds)?;
+        let timeout = this.read_scalar(timeout)?.to_i32()?;
+
+        let count = this.poll_report_events(&fds, nfds)?;
+        if count > 0 || timeout == 0 {
+            this.write_scalar(Scalar::from_i32(count), dest)?;
+            return Ok(());
+        }
+
+        // Like for `epoll_wait`, we block the thread until one of the file descriptors becomes
+        // ready or the timeout expires.
+        let thread = this.get_active_thread();
+        this.block_thread(thread);
+
+        struct PollCallback<'tcx> {
+            thread: ThreadId,
+            fds: MPlaceTy<'tcx, Provenance>,
+            nfds: u64,
+            dest: PlaceTy<'tcx, Provenance>,
+        }
+
+        impl<'tcx> VisitTags for PollCallback<'tcx> {
+            fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                let PollCallback { thread: _, fds, nfds: _, dest } = self;
+                fds.visit_tags(visit);
+                dest.visit_tags(visit);
+            }
+        }
+
+        impl<'mir, 'tcx: 'mir> IoCallback<'mir, 'tcx> for PollCallback<'tcx> {
+            fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx, bool> {
+                let count = this.poll_report_events(&self.fds, self.nfds)?;
+                if count == 0 {
+                    return Ok(false);
+                }
+                this.unblock_thread(self.thread);
+                this.unregister_timeout_callback_if_exists(self.thread);
+                this.write_scalar(Scalar::from_i32(count), &self.dest)?;
+                Ok(true)
+            }
+        }
+
+        this.register_io_callback(
+            thread,
+            Box::new(PollCallback { thread, fds, nfds, dest: dest.clone() }),
+        );
+
+        if let Ok(timeout) = u64::try_from(timeout) {
+            struct Callback<'tcx> {
+                thread: ThreadId,
+                dest: PlaceTy<'tcx, Provenance>,
+            }
+
+            impl<'tcx> VisitTags for Callback<'tcx> {
+                fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+                    let Callback { thread: _, dest } = self;
+                    dest.visit_tags(visit);
+                }
+            }
+
+            impl<'mir, 'tcx: 'mir> MachineCallback<'mir, 'tcx> for Callback<'tcx> {
+                fn call(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx> {
+                    // No file descriptor became ready in time.
+                    this.unblock_thread(self.thread);
+                    this.unregister_io_callback_if_exists(self.thread);
+                    this.write_scalar(Scalar::from_i32(0), &self.dest)?;
+                    Ok(())
+                }
+            }
+
+            let timeout_time = Time::Monotonic(
+                this.machine.clock.now().checked_add(Duration::from_millis(timeout)).unwrap(),
+            );
+            this.register_timeout_callback(
+                thread,
+                timeout_time,
+                Box::new(Callback { thread, dest: dest.clone() }),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Sets the `revents` of the `nfds` entries of the `pollfd` array `fds`, and returns how many
+    /// of them are not zero.
+    fn poll_report_events(
+        &mut self,
+        fds: &MPlaceTy<'tcx, Provenance>,
+        nfds: u64,
+    ) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        let pollin = this.eval_libc("POLLIN").to_i16()?;
+        let pollout = this.eval_libc("POLLOUT").to_i16()?;
+        let pollrdhup = this.eval_libc("POLLRDHUP").to_i16()?;
+        let pollhup = this.eval_libc("POLLHUP").to_i16()?;
+        let pollerr = this.eval_libc("POLLERR").to_i16()?;
+        let pollnval = this.eval_libc("POLLNVAL").to_i16()?;
+
+        let mut count = 0;
+        for i in 0..nfds {
+            let offset = fds.layout.size.checked_mul(i, this).unwrap();
+            let pollfd = fds.offset(offset, fds.layout, this)?;
+            let fd = this.project_field_named(&pollfd, "fd")?;
+            let fd = this.read_scalar(&fd)?.to_i32()?;
+            let events = this.project_field_named(&pollfd, "events")?;
+            let events = this.read_scalar(&events)?.to_i16()?;
+
+            let revents = if fd < 0 {
+                // Negative file descriptors are ignored.
+                0
+            } else {
+                match this.machine.file_handler.handles.get(&fd) {
+                    None => pollnval,
+                    Some(file_descriptor) =>
+                        match file_descriptor.epoll_readiness() {
+                            None => (pollin | pollout) & events,
+                            Some(readiness) => {
+                                let mut flags = 0;
+                                if readiness.readable {
+                                    flags |= pollin;
+                                }
+                                if readiness.writable {
+                                    flags |= pollout;
+                                }
+                                if readiness.peer_write_closed {
+                                    flags |= pollrdhup;
+                                }
+                                // `POLLHUP` and `POLLERR` are always reported, even if they were
+                                // not asked for.
+                                flags &= events;
+                                if readiness.hangup {
+                                    flags |= pollhup;
+                                }
+                                if readiness.error {
+                                    flags |= pollerr;
+                                }
+                                flags
+                            }
+                        },
+                }
+            };
+            if revents != 0 {
+                count += 1;
+            }
+            let revents_place = this.project_field_named(&pollfd, "revents")?;
+            this.write_scalar(Scalar::from_i16(revents), &revents_place)?;
+        }
+        Ok(count)
     }
 
     /// This function creates an `Event` that is used as an event wait/notify mechanism by
@@ -174,14 +573,12 @@ fn epoll_wait(
     /// `select`, and `close` operations can be performed on the file descriptor. For more
     /// information on these operations, see the man page linked below.
     ///
-    /// The `flags` are not currently implemented for eventfd.
     /// The `flags` may be bitwise ORed to change the behavior of `eventfd`:
     /// `EFD_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptor.
     /// `EFD_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file description.
     /// `EFD_SEMAPHORE` - miri does not support semaphore-like semantics.
     ///
     /// <https://linux.die.net/man/2/eventfd>
-    #[expect(clippy::needless_if)]
     fn eventfd(
         &mut self,
         val: &OpTy<'tcx, Provenance>,
@@ -196,23 +593,27 @@ fn eventfd(
         let efd_nonblock = this.eval_libc_i32("EFD_NONBLOCK");
         let efd_semaphore = this.eval_libc_i32("EFD_SEMAPHORE");
 
-        if flags & (efd_cloexec | efd_nonblock | efd_semaphore) == 0 {
-            throw_unsup_format!("{flags} is unsupported");
+        if flags & !(efd_cloexec | efd_nonblock | efd_semaphore) != 0 {
+            throw_unsup_format!("eventfd flags {flags:#x} are not implemented");
         }
-        // FIXME handle the cloexec and nonblock flags
-        if flags & efd_cloexec == efd_cloexec {}
-        if flags & efd_nonblock == efd_nonblock {}
         if flags & efd_semaphore == efd_semaphore {
             throw_unsup_format!("EFD_SEMAPHORE is unsupported");
         }
+        // `EFD_CLOEXEC` has no effect, see `posix_spawn`.
+        let nonblock = flags & efd_nonblock == efd_nonblock;
 
         let fh = &mut this.machine.file_handler;
-        let fd = fh.insert_fd(Box::new(Event { val: Cell::new(val.into()) }));
+        let fd = fh.insert_fd(Box::new(Event {
+            val: Rc::new(Cell::new(val.into())),
+            generation: Rc::new(Cell::new(0)),
+            nonblock,
+        }));
         Ok(Scalar::from_i32(fd))
     }
 
-    /// Currently this function creates new `SocketPair`s without specifying the domain, type, or
-    /// protocol of the new socket and these are stored in the socket values `sv` argument.
+    /// Currently this function only supports creating pairs of connected `AF_UNIX` stream sockets,
+    /// which are stored in the socket values `sv` argument. The data written to one socket can
+    /// be read from the other one.
     ///
     /// This function creates an unnamed pair of connected sockets in the specified domain, of the
     /// specified type, and using the optionally specified protocol.
@@ -237,15 +638,30 @@ fn socketpair(
     ) -> InterpResult<'tcx, Scalar<Provenance>> {
         let this = self.eval_context_mut();
 
-        let _domain = this.read_scalar(domain)?.to_i32()?;
-        let _type_ = this.read_scalar(type_)?.to_i32()?;
-        let _protocol = this.read_scalar(protocol)?.to_i32()?;
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
         let sv = this.deref_pointer(sv)?;
 
+        let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+        let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+        if domain != this.eval_libc_i32("AF_UNIX") {
+            throw_unsup_format!("socketpair domain {domain} is not supported");
+        }
+        // `SOCK_CLOEXEC` has no effect, see `posix_spawn`.
+        if type_ & !(sock_nonblock | sock_cloexec) != this.eval_libc_i32("SOCK_STREAM") {
+            throw_unsup_format!("socketpair only supports stream sockets");
+        }
+        if protocol != 0 {
+            throw_unsup_format!("socketpair protocol {protocol} is not supported");
+        }
+        let nonblock = type_ & sock_nonblock == sock_nonblock;
+
+        let (first, second) = SocketPair::new_pair(nonblock);
         let fh = &mut this.machine.file_handler;
-        let sv0 = fh.insert_fd(Box::new(SocketPair));
+        let sv0 = fh.insert_fd(Box::new(first));
         let sv0 = ScalarInt::try_from_int(sv0, sv.layout.size).unwrap();
-        let sv1 = fh.insert_fd(Box::new(SocketPair));
+        let sv1 = fh.insert_fd(Box::new(second));
         let sv1 = ScalarInt::try_from_int(sv1, sv.layout.size).unwrap();
 
         this.write_scalar(sv0, &sv)?;
@@ -253,4 +669,47 @@ fn socketpair(
 
         Ok(Scalar::from_i32(0))
     }
+
+    /// This function creates a pipe, a unidirectional data channel. The file descriptors of the
+    /// reading end and of the writing end are stored in `pipefd[0]` and `pipefd[1]`.
+    ///
+    /// `pipe` is the same as `pipe2` with `flags` set to 0. The `flags` may be bitwise ORed:
+    /// `O_CLOEXEC` - Set the close-on-exec (`FD_CLOEXEC`) flag on the new file descriptors.
+    /// `O_NONBLOCK` - Set the `O_NONBLOCK` file status flag on the new open file descriptions.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/pipe.2.html>
+    fn pipe2(
+        &mut self,
+        pipefd: &OpTy<'tcx, Provenance>,
+        flags: Option<&OpTy<'tcx, Provenance>>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let pipefd = this.deref_pointer(pipefd)?;
+        let flags = match flags {
+            Some(flags) => this.read_scalar(flags)?.to_i32()?,
+            None => 0,
+        };
+
+        let o_nonblock = this.eval_libc_i32("O_NONBLOCK");
+        let o_cloexec = this.eval_libc_i32("O_CLOEXEC");
+        if flags & !(o_nonblock | o_cloexec) != 0 {
+            throw_unsup_format!("pipe2 flags {flags:#x} are not implemented");
+        }
+        // `O_CLOEXEC` has no effect, see `posix_spawn`.
+        let nonblock = flags & o_nonblock == o_nonblock;
+
+        let (reader, writer) = Pipe::new_pair(nonblock);
+        let fh = &mut this.machine.file_handler;
+        let reader = fh.insert_fd(Box::new(reader));
+        let writer = fh.insert_fd(Box::new(writer));
+
+        this.write_scalar(Scalar::from_i32(reader), &pipefd)?;
+        this.write_scalar(
+            Scalar::from_i32(writer),
+            &pipefd.offset(pipefd.layout.size, pipefd.layout, this)?,
+        )?;
+
+        Ok(Scalar::from_i32(0))
+    }
 }
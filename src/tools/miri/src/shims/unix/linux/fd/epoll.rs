@@ -2,18 +2,31 @@
 
 use crate::shims::unix::fs::FileDescriptor;
 
-use rustc_data_structures::fx::FxHashMap;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::io;
+use std::rc::Rc;
 
 /// An `Epoll` file descriptor connects file handles and epoll events
 #[derive(Clone, Debug, Default)]
 pub struct Epoll {
-    /// The file descriptors we are watching, and what we are watching for.
-    pub file_descriptors: FxHashMap<i32, EpollEvent>,
+    /// The file descriptors we are watching, and what we are watching for. This is shared with
+    /// the duplicates of this file descriptor.
+    pub interest_list: Rc<RefCell<InterestList>>,
+}
+
+/// The interest list of an `Epoll` instance.
+#[derive(Debug, Default)]
+pub struct InterestList {
+    /// The watched file descriptors, in the order they are checked by `epoll_wait`.
+    pub file_descriptors: BTreeMap<i32, EpollEvent>,
+    /// The file descriptor that was reported last by `epoll_wait`. The next call starts looking
+    /// for events after it, so that all ready file descriptors get reported in turn even when
+    /// there are more of them than `maxevents`.
+    pub last_reported: Option<i32>,
 }
 
 /// Epoll Events associate events with data.
-/// These fields are currently unused by miri.
 /// This matches the `epoll_event` struct defined
 /// by the epoll_ctl man page. For more information
 /// see the man page:
@@ -25,6 +38,11 @@ pub struct EpollEvent {
     /// `Scalar<Provenance>` is used to represent the
     /// `epoll_data` type union.
     pub data: Scalar<Provenance>,
+    /// The generation of the file description when it was last reported. Edge-triggered events
+    /// are only reported again once the generation changed.
+    pub reported_generation: Option<u64>,
+    /// Set once an `EPOLLONESHOT` event was reported, until the event is modified.
+    pub disabled: bool,
 }
 
 impl FileDescriptor for Epoll {
@@ -42,4 +60,10 @@ fn close<'tcx>(
     ) -> InterpResult<'tcx, io::Result<i32>> {
         Ok(Ok(0))
     }
+
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        for event in self.interest_list.borrow().file_descriptors.values() {
+            event.data.visit_tags(visit);
+        }
+    }
 }
@@ -1,3 +1,4 @@
+use crate::shims::unix::fd::EpollReadiness;
 use crate::shims::unix::fs::FileDescriptor;
 
 use rustc_const_eval::interpret::InterpResult;
@@ -5,10 +6,13 @@
 use rustc_target::abi::Endian;
 
 use std::cell::Cell;
-use std::io;
+use std::io::{self, ErrorKind};
+use std::rc::Rc;
+
+/// The largest value the counter of an `Event` can hold.
+const MAX_COUNTER: u64 = 0xffff_ffff_ffff_fffe;
 
 /// A kind of file descriptor created by `eventfd`.
-/// The `Event` type isn't currently written to by `eventfd`.
 /// The interface is meant to keep track of objects associated
 /// with a file descriptor. For more information see the man
 /// page below:
@@ -18,7 +22,18 @@
 pub struct Event {
     /// The object contains an unsigned 64-bit integer (uint64_t) counter that is maintained by the
     /// kernel. This counter is initialized with the value specified in the argument initval.
-    pub val: Cell<u64>,
+    /// It is shared with the duplicates of this file descriptor.
+    pub val: Rc<Cell<u64>>,
+    /// Incremented on every read and write, see `EpollReadiness::generation`.
+    pub generation: Rc<Cell<u64>>,
+    /// Whether `EFD_NONBLOCK` was set.
+    pub nonblock: bool,
+}
+
+impl Event {
+    fn bump_generation(&self) {
+        self.generation.set(self.generation.get().wrapping_add(1));
+    }
 }
 
 impl FileDescriptor for Event {
@@ -27,7 +42,11 @@ fn name(&self) -> &'static str {
     }
 
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
-        Ok(Box::new(Event { val: self.val.clone() }))
+        Ok(Box::new(Event {
+            val: self.val.clone(),
+            generation: self.generation.clone(),
+            nonblock: self.nonblock,
+        }))
     }
 
     fn close<'tcx>(
@@ -37,6 +56,37 @@ fn close<'tcx>(
         Ok(Ok(0))
     }
 
+    /// A read returns the value of the counter as an 8-byte integer in native endianess, and
+    /// resets the counter to zero. If the counter is zero, the read either blocks until the
+    /// counter becomes nonzero, or fails with the error EAGAIN if the file descriptor has been
+    /// made nonblocking.
+    ///
+    /// A read fails with the error EINVAL if the size of the supplied buffer is less than 8
+    /// bytes.
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let Some(bytes) = bytes.get_mut(..8) else {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        };
+        let val = self.val.get();
+        if val == 0 {
+            return Ok(Err(ErrorKind::WouldBlock.into()));
+        }
+        // Convert from host endianess to target endianess.
+        let val = match tcx.sess.target.endian {
+            Endian::Little => val.to_le_bytes(),
+            Endian::Big => val.to_be_bytes(),
+        };
+        bytes.copy_from_slice(&val);
+        self.val.set(0);
+        self.bump_generation();
+        Ok(Ok(8))
+    }
+
     /// A write call adds the 8-byte integer value supplied in
     /// its buffer (in native endianess) to the counter.  The maximum value that may be
     /// stored in the counter is the largest unsigned 64-bit value
@@ -55,18 +105,44 @@ fn write<'tcx>(
         bytes: &[u8],
         tcx: TyCtxt<'tcx>,
     ) -> InterpResult<'tcx, io::Result<usize>> {
-        let v1 = self.val.get();
-        let bytes: [u8; 8] = bytes.try_into().unwrap(); // FIXME fail gracefully when this has the wrong size
+        let Some(bytes) = bytes.get(..8) else {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        };
+        let bytes: [u8; 8] = bytes.try_into().unwrap();
         // Convert from target endianess to host endianess.
         let num = match tcx.sess.target.endian {
             Endian::Little => u64::from_le_bytes(bytes),
             Endian::Big => u64::from_be_bytes(bytes),
         };
-        // FIXME handle blocking when addition results in exceeding the max u64 value
-        // or fail with EAGAIN if the file descriptor is nonblocking.
-        let v2 = v1.checked_add(num).unwrap();
-        self.val.set(v2);
-        assert_eq!(8, bytes.len());
-        Ok(Ok(8))
+        if num == u64::MAX {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        }
+        match self.val.get().checked_add(num) {
+            Some(val) if val <= MAX_COUNTER => {
+                self.val.set(val);
+                self.bump_generation();
+                Ok(Ok(8))
+            }
+            _ => Ok(Err(ErrorKind::WouldBlock.into())),
+        }
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.nonblock
+    }
+
+    fn set_nonblocking<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.nonblock = nonblock;
+        Ok(())
+    }
+
+    fn epoll_readiness(&self) -> Option<EpollReadiness> {
+        let val = self.val.get();
+        Some(EpollReadiness {
+            readable: val > 0,
+            writable: val < MAX_COUNTER,
+            generation: self.generation.get(),
+            ..EpollReadiness::default()
+        })
     }
 }
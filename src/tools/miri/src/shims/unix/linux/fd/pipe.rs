@@ -0,0 +1,118 @@
+use crate::*;
+
+use crate::shims::unix::fd::stream::ByteStream;
+use crate::shims::unix::fd::EpollReadiness;
+use crate::shims::unix::fs::FileDescriptor;
+
+use rustc_middle::ty::TyCtxt;
+
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+/// One end of a pipe created by `pipe` or `pipe2`.
+#[derive(Debug)]
+pub struct Pipe {
+    /// The data in the pipe, shared by both ends.
+    pub stream: Rc<RefCell<ByteStream>>,
+    /// Whether this is the writing end of the pipe.
+    pub write_end: bool,
+    /// Whether `O_NONBLOCK` was set.
+    pub nonblock: bool,
+}
+
+impl Pipe {
+    /// Creates the reading and the writing end of a new pipe.
+    pub fn new_pair(nonblock: bool) -> (Pipe, Pipe) {
+        let stream = Rc::new(RefCell::new(ByteStream::new()));
+        (
+            Pipe { stream: stream.clone(), write_end: false, nonblock },
+            Pipe { stream, write_end: true, nonblock },
+        )
+    }
+}
+
+impl FileDescriptor for Pipe {
+    fn name(&self) -> &'static str {
+        "pipe"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        if self.write_end {
+            throw_unsup_format!("cannot read from the writing end of a pipe");
+        }
+        Ok(self.stream.borrow_mut().read(bytes))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        if !self.write_end {
+            throw_unsup_format!("cannot write to the reading end of a pipe");
+        }
+        Ok(self.stream.borrow_mut().write(bytes))
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.nonblock
+    }
+
+    fn set_nonblocking<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.nonblock = nonblock;
+        Ok(())
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        let mut stream = self.stream.borrow_mut();
+        if self.write_end {
+            stream.writers = stream.writers.checked_add(1).unwrap();
+        } else {
+            stream.readers = stream.readers.checked_add(1).unwrap();
+        }
+        Ok(Box::new(Pipe {
+            stream: self.stream.clone(),
+            write_end: self.write_end,
+            nonblock: self.nonblock,
+        }))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        if self.write_end {
+            self.stream.borrow_mut().close_writer();
+        } else {
+            self.stream.borrow_mut().close_reader();
+        }
+        Ok(Ok(0))
+    }
+
+    fn epoll_readiness(&self) -> Option<EpollReadiness> {
+        let stream = self.stream.borrow();
+        Some(if self.write_end {
+            EpollReadiness {
+                writable: stream.writable(),
+                // Like on Linux, the writing end reports an error once there are no readers.
+                error: stream.readers == 0,
+                generation: stream.write_generation,
+                ..EpollReadiness::default()
+            }
+        } else {
+            EpollReadiness {
+                readable: stream.readable(),
+                hangup: stream.writers == 0,
+                generation: stream.read_generation,
+                ..EpollReadiness::default()
+            }
+        })
+    }
+}
@@ -1,28 +1,104 @@
 use crate::*;
 
+use crate::shims::unix::fd::stream::ByteStream;
+use crate::shims::unix::fd::EpollReadiness;
 use crate::shims::unix::fs::FileDescriptor;
 
+use rustc_middle::ty::TyCtxt;
+
+use std::cell::RefCell;
 use std::io;
+use std::rc::Rc;
 
-/// Pair of connected sockets.
+/// One end of a pair of connected stream sockets.
 ///
-/// We currently don't allow sending any data through this pair, so this can be just a dummy.
+/// Each end reads from the stream the other end writes to.
 #[derive(Debug)]
-pub struct SocketPair;
+pub struct SocketPair {
+    /// The stream this end reads from.
+    pub readbuf: Rc<RefCell<ByteStream>>,
+    /// The stream this end writes to.
+    pub writebuf: Rc<RefCell<ByteStream>>,
+    /// Whether `SOCK_NONBLOCK` was set.
+    pub nonblock: bool,
+}
+
+impl SocketPair {
+    /// Creates both ends of a new pair of connected sockets.
+    pub fn new_pair(nonblock: bool) -> (SocketPair, SocketPair) {
+        let first = Rc::new(RefCell::new(ByteStream::new()));
+        let second = Rc::new(RefCell::new(ByteStream::new()));
+        (
+            SocketPair { readbuf: first.clone(), writebuf: second.clone(), nonblock },
+            SocketPair { readbuf: second, writebuf: first, nonblock },
+        )
+    }
+}
 
 impl FileDescriptor for SocketPair {
     fn name(&self) -> &'static str {
         "socketpair"
     }
 
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(self.readbuf.borrow_mut().read(bytes))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(self.writebuf.borrow_mut().write(bytes))
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.nonblock
+    }
+
+    fn set_nonblocking<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.nonblock = nonblock;
+        Ok(())
+    }
+
     fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
-        Ok(Box::new(SocketPair))
+        let mut readbuf = self.readbuf.borrow_mut();
+        readbuf.readers = readbuf.readers.checked_add(1).unwrap();
+        let mut writebuf = self.writebuf.borrow_mut();
+        writebuf.writers = writebuf.writers.checked_add(1).unwrap();
+        Ok(Box::new(SocketPair {
+            readbuf: self.readbuf.clone(),
+            writebuf: self.writebuf.clone(),
+            nonblock: self.nonblock,
+        }))
     }
 
     fn close<'tcx>(
         self: Box<Self>,
         _communicate_allowed: bool,
     ) -> InterpResult<'tcx, io::Result<i32>> {
+        self.readbuf.borrow_mut().close_reader();
+        self.writebuf.borrow_mut().close_writer();
         Ok(Ok(0))
     }
+
+    fn epoll_readiness(&self) -> Option<EpollReadiness> {
+        let readbuf = self.readbuf.borrow();
+        let writebuf = self.writebuf.borrow();
+        let peer_write_closed = readbuf.writers == 0;
+        Some(EpollReadiness {
+            readable: readbuf.readable(),
+            writable: writebuf.writable(),
+            peer_write_closed,
+            hangup: peer_write_closed && writebuf.readers == 0,
+            error: false,
+            generation: readbuf.read_generation.wrapping_add(writebuf.write_generation),
+        })
+    }
 }
@@ -1,3 +1,6 @@
+use std::ffi::OsStr;
+use std::iter;
+
 use rustc_span::Symbol;
 use rustc_target::spec::abi::Abi;
 
@@ -9,6 +12,7 @@
 use shims::unix::linux::fd::EvalContextExt as _;
 use shims::unix::linux::mem::EvalContextExt as _;
 use shims::unix::linux::sync::futex;
+use shims::unix::net::EvalContextExt as _;
 use shims::unix::sync::EvalContextExt as _;
 use shims::unix::thread::EvalContextExt as _;
 
@@ -60,8 +64,12 @@ fn emulate_foreign_item_by_name(
             "epoll_wait" => {
                 let [epfd, events, maxevents, timeout] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
-                let result = this.epoll_wait(epfd, events, maxevents, timeout)?;
-                this.write_scalar(result, dest)?;
+                this.epoll_wait(epfd, events, maxevents, timeout, dest)?;
+            }
+            "poll" => {
+                let [fds, nfds, timeout] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.poll(fds, nfds, timeout, dest)?;
             }
             "eventfd" => {
                 let [val, flag] =
@@ -75,6 +83,22 @@ fn emulate_foreign_item_by_name(
                 let ptr = this.mremap(old_address, old_size, new_size, flags)?;
                 this.write_scalar(ptr, dest)?;
             }
+            "pipe" => {
+                let [pipefd] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pipe2(pipefd, None)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pipe2" => {
+                let [pipefd, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = this.pipe2(pipefd, Some(flags))?;
+                this.write_scalar(result, dest)?;
+            }
+            "accept4" => {
+                let [fd, addr, addrlen, flags] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                this.accept(fd, addr, addrlen, Some(flags), dest)?;
+            }
             "socketpair" => {
                 let [domain, type_, protocol, sv] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -193,6 +217,29 @@ fn emulate_foreign_item_by_name(
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
                 getrandom(this, ptr, len, flags, dest)?;
             }
+            "sigemptyset" => {
+                let [set] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+                this.write_bytes_ptr(
+                    set.ptr,
+                    iter::repeat(0u8).take(set.layout.size.bytes_usize()),
+                )?;
+                this.write_null(dest)?;
+            }
+            "sigaddset" => {
+                let [set, signum] =
+                    this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                let result = sigaddset(this, set, signum)?;
+                this.write_scalar(Scalar::from_i32(result), dest)?;
+            }
+            "gnu_get_libc_version" => {
+                let [] = this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
+                // `std` only uses `posix_spawn` with glibc 2.24 or newer, see
+                // `-Zmiri-emulate-processes`.
+                let version =
+                    this.alloc_os_str_as_c_str(OsStr::new("2.36"), MiriMemoryKind::Machine.into())?;
+                this.write_pointer(version, dest)?;
+            }
             "sched_getaffinity" => {
                 let [pid, cpusetsize, mask] =
                     this.check_shim(abi, Abi::C { unwind: false }, link_name, args)?;
@@ -220,6 +267,33 @@ fn emulate_foreign_item_by_name(
     }
 }
 
+// Shims `sigaddset` for glibc's `sigset_t`, which is an array of words with one bit per signal.
+fn sigaddset<'tcx>(
+    this: &mut MiriInterpCx<'_, 'tcx>,
+    set: &OpTy<'tcx, Provenance>,
+    signum: &OpTy<'tcx, Provenance>,
+) -> InterpResult<'tcx, i32> {
+    let set = this.deref_pointer_as(set, this.libc_ty_layout("sigset_t"))?;
+    let signum = this.read_scalar(signum)?.to_i32()?;
+
+    let words = this.project_field_named(&set, "__val")?;
+    let word_bits = this.project_index(&words, 0)?.layout.size.bits();
+    // Signal numbers start at 1.
+    let Some(bit) = u64::try_from(signum)
+        .ok()
+        .and_then(|signum| signum.checked_sub(1))
+        .filter(|&bit| bit < set.layout.size.bits())
+    else {
+        let einval = this.eval_libc("EINVAL");
+        this.set_last_error(einval)?;
+        return Ok(-1);
+    };
+    let word = this.project_index(&words, bit / word_bits)?;
+    let value = this.read_scalar(&word)?.to_bits(word.layout.size)?;
+    this.write_scalar(Scalar::from_uint(value | 1 << (bit % word_bits), word.layout.size), &word)?;
+    Ok(0)
+}
+
 // Shims the linux `getrandom` syscall.
 fn getrandom<'tcx>(
     this: &mut MiriInterpCx<'_, 'tcx>,
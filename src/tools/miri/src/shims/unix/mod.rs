@@ -1,8 +1,11 @@
 pub mod dlsym;
 pub mod foreign_items;
 
+mod fd;
 mod fs;
 mod mem;
+mod net;
+mod process;
 mod sync;
 mod thread;
 
@@ -12,6 +15,8 @@
 mod macos;
 
 pub use fs::{DirHandler, FileHandler};
+pub use net::Network;
+pub use process::{Process, ProcessManager, ROOT_PID};
 
 // Make up some constants.
 const UID: u32 = 1000;
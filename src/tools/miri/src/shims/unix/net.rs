@@ -0,0 +1,1340 @@
+//! An emulated network stack for TCP and UDP sockets.
+//!
+//! Sockets can only talk to other sockets of the interpreted program over loopback addresses, so
+//! this works under isolation and never touches the host network. Connections are established
+//! immediately when `connect` is called, and the data sent over them is buffered in `ByteStream`s
+//! like for pipes.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind};
+use std::iter;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::rc::{Rc, Weak};
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::TyCtxt;
+use rustc_target::abi::{Align, Size};
+
+use crate::concurrency::thread::BlockingIoOp;
+use crate::shims::unix::fd::stream::ByteStream;
+use crate::shims::unix::fd::EpollReadiness;
+use crate::shims::unix::fs::{EvalContextExt as _, FileDescriptor};
+use crate::*;
+
+/// The first port that is handed out to sockets that are bound to port 0, or implicitly bound by
+/// `connect` or `sendto`. This is the start of the ephemeral port range suggested by IANA; the
+/// range ends at `u16::MAX`.
+const EPHEMERAL_PORT_MIN: u16 = 49152;
+
+/// The number of datagrams a UDP socket can hold before further datagrams sent to it are dropped.
+const DATAGRAM_QUEUE_CAPACITY: usize = 256;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Tcp,
+    Udp,
+}
+
+/// A port a socket is bound to. The IP address a socket is bound to is only used for reporting
+/// the local address, all loopback addresses of a family share the same ports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Port {
+    protocol: Protocol,
+    ipv6: bool,
+    number: u16,
+}
+
+/// The emulated network, which keeps track of the ports sockets are bound to.
+#[derive(Debug)]
+pub struct Network {
+    /// The bound sockets. Closing the last file descriptor of a socket frees its port.
+    ports: FxHashMap<Port, Weak<RefCell<SocketInner>>>,
+    /// The ephemeral port that is tried next.
+    next_ephemeral_port: u16,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        Network { ports: FxHashMap::default(), next_ephemeral_port: EPHEMERAL_PORT_MIN }
+    }
+}
+
+impl Network {
+    fn lookup(&self, port: Port) -> Option<Rc<RefCell<SocketInner>>> {
+        self.ports.get(&port).and_then(Weak::upgrade)
+    }
+
+    /// Binds `socket` to `addr`, picking an ephemeral port if the port of `addr` is 0, and
+    /// returns the address the socket is now bound to.
+    fn bind(
+        &mut self,
+        socket: &Rc<RefCell<SocketInner>>,
+        addr: SocketAddr,
+    ) -> io::Result<SocketAddr> {
+        let mut inner = socket.borrow_mut();
+        if inner.local_addr.is_some() || addr.is_ipv6() != inner.ipv6 {
+            return Err(ErrorKind::InvalidInput.into());
+        }
+        if !addr.ip().is_loopback() && !addr.ip().is_unspecified() {
+            // There are no other network interfaces.
+            return Err(ErrorKind::AddrNotAvailable.into());
+        }
+        let mut port = Port { protocol: inner.protocol, ipv6: inner.ipv6, number: addr.port() };
+        if port.number == 0 {
+            port.number = self.next_ephemeral_port;
+            while self.lookup(port).is_some() {
+                port.number = port.number.checked_add(1).unwrap_or(EPHEMERAL_PORT_MIN);
+                if port.number == self.next_ephemeral_port {
+                    return Err(ErrorKind::AddrInUse.into());
+                }
+            }
+            self.next_ephemeral_port = port.number.checked_add(1).unwrap_or(EPHEMERAL_PORT_MIN);
+        } else if self.lookup(port).is_some() {
+            return Err(ErrorKind::AddrInUse.into());
+        }
+        self.ports.insert(port, Rc::downgrade(socket));
+        let local_addr = SocketAddr::new(addr.ip(), port.number);
+        inner.local_addr = Some(local_addr);
+        Ok(local_addr)
+    }
+
+    /// Binds `socket` to an ephemeral port if it is not bound yet, and returns its local address.
+    fn ensure_bound(&mut self, socket: &Rc<RefCell<SocketInner>>) -> io::Result<SocketAddr> {
+        let (local_addr, ipv6) = {
+            let inner = socket.borrow();
+            (inner.local_addr, inner.ipv6)
+        };
+        match local_addr {
+            Some(local_addr) => Ok(local_addr),
+            None => self.bind(socket, SocketAddr::new(unspecified_ip(ipv6), 0)),
+        }
+    }
+}
+
+fn unspecified_ip(ipv6: bool) -> IpAddr {
+    if ipv6 { Ipv6Addr::UNSPECIFIED.into() } else { Ipv4Addr::UNSPECIFIED.into() }
+}
+
+/// Resolves the address a socket connects or sends to. Like on Linux, the unspecified address
+/// refers to the local host.
+fn resolve_remote_addr<'tcx>(mut addr: SocketAddr) -> InterpResult<'tcx, SocketAddr> {
+    if addr.ip().is_unspecified() {
+        addr.set_ip(if addr.is_ipv6() {
+            Ipv6Addr::LOCALHOST.into()
+        } else {
+            Ipv4Addr::LOCALHOST.into()
+        });
+    }
+    if !addr.ip().is_loopback() {
+        throw_unsup_format!("cannot reach {addr}: only loopback networking is supported");
+    }
+    Ok(addr)
+}
+
+/// The address a socket bound to `local_addr` appears to have for a peer at `remote_addr`.
+fn source_addr(local_addr: SocketAddr, remote_addr: SocketAddr) -> SocketAddr {
+    if local_addr.ip().is_unspecified() {
+        SocketAddr::new(remote_addr.ip(), local_addr.port())
+    } else {
+        local_addr
+    }
+}
+
+/// A TCP or UDP socket, which is shared with the duplicates of its file descriptor.
+#[derive(Debug)]
+pub struct InetSocket {
+    inner: Rc<RefCell<SocketInner>>,
+}
+
+#[derive(Debug)]
+pub struct SocketInner {
+    protocol: Protocol,
+    ipv6: bool,
+    /// Whether `SOCK_NONBLOCK` or `FIONBIO` was set.
+    nonblock: bool,
+    /// The value of the `TCP_NODELAY` option, which has no effect.
+    nodelay: bool,
+    /// The address the socket is bound to, if any.
+    local_addr: Option<SocketAddr>,
+    state: SocketState,
+    /// Incremented whenever a connection or datagram arrives, see `EpollReadiness::generation`.
+    generation: u64,
+}
+
+#[derive(Debug)]
+enum SocketState {
+    /// A TCP socket that is neither listening nor connected.
+    Idle,
+    /// A TCP socket that accepts connections.
+    Listening { pending: VecDeque<PendingConnection> },
+    /// A connected TCP socket.
+    Connected(Connection),
+    /// A UDP socket, with the datagrams it received and the peer it is connected to, if any.
+    Datagram { queue: VecDeque<Datagram>, peer: Option<SocketAddr> },
+}
+
+/// A connection that was established with a listening socket, but not accepted yet.
+#[derive(Debug)]
+struct PendingConnection {
+    /// The address the peer connected to.
+    local_addr: SocketAddr,
+    connection: Connection,
+}
+
+/// One end of a TCP connection.
+#[derive(Debug)]
+struct Connection {
+    peer_addr: SocketAddr,
+    /// The stream this end reads from.
+    readbuf: Rc<RefCell<ByteStream>>,
+    /// The stream this end writes to.
+    writebuf: Rc<RefCell<ByteStream>>,
+    /// Whether this end was shut down for reading.
+    read_shutdown: bool,
+    /// Whether this end was shut down for writing.
+    write_shutdown: bool,
+}
+
+#[derive(Debug)]
+struct Datagram {
+    source: SocketAddr,
+    data: Vec<u8>,
+}
+
+impl Connection {
+    /// Creates both ends of a new connection between `client_addr` and `server_addr`, and returns
+    /// the client end and the server end.
+    fn new_pair(client_addr: SocketAddr, server_addr: SocketAddr) -> (Connection, Connection) {
+        let to_client = Rc::new(RefCell::new(ByteStream::new()));
+        let to_server = Rc::new(RefCell::new(ByteStream::new()));
+        (
+            Connection {
+                peer_addr: server_addr,
+                readbuf: to_client.clone(),
+                writebuf: to_server.clone(),
+                read_shutdown: false,
+                write_shutdown: false,
+            },
+            Connection {
+                peer_addr: client_addr,
+                readbuf: to_server,
+                writebuf: to_client,
+                read_shutdown: false,
+                write_shutdown: false,
+            },
+        )
+    }
+
+    fn recv(&self, bytes: &mut [u8], peek: bool) -> io::Result<usize> {
+        if self.read_shutdown {
+            return Ok(0);
+        }
+        let mut readbuf = self.readbuf.borrow_mut();
+        if peek { readbuf.peek(bytes) } else { readbuf.read(bytes) }
+    }
+
+    fn send(&self, bytes: &[u8]) -> io::Result<usize> {
+        if self.write_shutdown {
+            return Err(ErrorKind::BrokenPipe.into());
+        }
+        self.writebuf.borrow_mut().write(bytes)
+    }
+
+    fn shutdown(&mut self, read: bool, write: bool) {
+        if read && !self.read_shutdown {
+            self.read_shutdown = true;
+            self.readbuf.borrow_mut().close_reader();
+        }
+        if write && !self.write_shutdown {
+            self.write_shutdown = true;
+            self.writebuf.borrow_mut().close_writer();
+        }
+    }
+
+    fn epoll_readiness(&self) -> EpollReadiness {
+        let readbuf = self.readbuf.borrow();
+        let writebuf = self.writebuf.borrow();
+        let peer_write_closed = readbuf.writers == 0;
+        EpollReadiness {
+            readable: self.read_shutdown || readbuf.readable(),
+            writable: self.write_shutdown || writebuf.writable(),
+            peer_write_closed,
+            hangup: peer_write_closed && writebuf.readers == 0,
+            error: false,
+            generation: readbuf.read_generation.wrapping_add(writebuf.write_generation),
+        }
+    }
+}
+
+impl SocketInner {
+    fn new(protocol: Protocol, ipv6: bool, nonblock: bool) -> Self {
+        let state = match protocol {
+            Protocol::Tcp => SocketState::Idle,
+            Protocol::Udp => SocketState::Datagram { queue: VecDeque::new(), peer: None },
+        };
+        SocketInner {
+            protocol,
+            ipv6,
+            nonblock,
+            nodelay: false,
+            local_addr: None,
+            state,
+            generation: 0,
+        }
+    }
+
+    /// Receives data from a connected TCP socket, or a datagram from a UDP socket together with
+    /// the address it was sent from. A datagram that does not fit into `bytes` is truncated.
+    fn recv(&mut self, bytes: &mut [u8], peek: bool) -> io::Result<(usize, Option<SocketAddr>)> {
+        match &mut self.state {
+            SocketState::Connected(connection) =>
+                connection.recv(bytes, peek).map(|len| (len, None)),
+            SocketState::Datagram { queue, .. } => {
+                let Some(datagram) = queue.front() else {
+                    return Err(ErrorKind::WouldBlock.into());
+                };
+                let len = bytes.len().min(datagram.data.len());
+                bytes[..len].copy_from_slice(&datagram.data[..len]);
+                let source = datagram.source;
+                if !peek {
+                    queue.pop_front();
+                }
+                Ok((len, Some(source)))
+            }
+            SocketState::Idle | SocketState::Listening { .. } =>
+                Err(ErrorKind::NotConnected.into()),
+        }
+    }
+
+    /// Queues a datagram sent to this UDP socket. Like on a real network, datagrams that do not
+    /// fit are dropped.
+    fn deliver(&mut self, datagram: Datagram) {
+        let SocketState::Datagram { queue, peer } = &mut self.state else {
+            return;
+        };
+        // Connected sockets only receive datagrams from their peer.
+        if peer.is_some_and(|peer| peer != datagram.source)
+            || queue.len() >= DATAGRAM_QUEUE_CAPACITY
+        {
+            return;
+        }
+        queue.push_back(datagram);
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        match &self.state {
+            SocketState::Connected(connection) => Some(connection.peer_addr),
+            SocketState::Datagram { peer, .. } => *peer,
+            SocketState::Idle | SocketState::Listening { .. } => None,
+        }
+    }
+
+    fn close(self) {
+        match self.state {
+            SocketState::Listening { pending } =>
+                for mut pending in pending {
+                    pending.connection.shutdown(true, true);
+                },
+            SocketState::Connected(mut connection) => connection.shutdown(true, true),
+            SocketState::Idle | SocketState::Datagram { .. } => {}
+        }
+    }
+}
+
+impl FileDescriptor for InetSocket {
+    fn name(&self) -> &'static str {
+        "socket"
+    }
+
+    fn read<'tcx>(
+        &mut self,
+        _communicate_allowed: bool,
+        bytes: &mut [u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        let result = self.inner.borrow_mut().recv(bytes, /* peek */ false);
+        Ok(result.map(|(len, _source)| len))
+    }
+
+    fn write<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        bytes: &[u8],
+        _tcx: TyCtxt<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<usize>> {
+        Ok(match &self.inner.borrow().state {
+            SocketState::Connected(connection) => connection.send(bytes),
+            SocketState::Datagram { .. } =>
+                throw_unsup_format!("writing to a UDP socket is only supported with `send`"),
+            SocketState::Idle | SocketState::Listening { .. } =>
+                Err(ErrorKind::NotConnected.into()),
+        })
+    }
+
+    fn is_nonblocking(&self) -> bool {
+        self.inner.borrow().nonblock
+    }
+
+    fn set_nonblocking<'tcx>(&mut self, nonblock: bool) -> InterpResult<'tcx> {
+        self.inner.borrow_mut().nonblock = nonblock;
+        Ok(())
+    }
+
+    fn dup(&mut self) -> io::Result<Box<dyn FileDescriptor>> {
+        Ok(Box::new(InetSocket { inner: self.inner.clone() }))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+    ) -> InterpResult<'tcx, io::Result<i32>> {
+        // The file descriptors are the only strong references to a socket, so the socket is
+        // closed with its last file descriptor. That also frees its port.
+        if let Ok(inner) = Rc::try_unwrap(self.inner) {
+            inner.into_inner().close();
+        }
+        Ok(Ok(0))
+    }
+
+    fn epoll_readiness(&self) -> Option<EpollReadiness> {
+        let inner = self.inner.borrow();
+        Some(match &inner.state {
+            // Like on Linux, a socket that is not connected reports a hangup.
+            SocketState::Idle => EpollReadiness {
+                writable: true,
+                hangup: true,
+                generation: inner.generation,
+                ..EpollReadiness::default()
+            },
+            SocketState::Listening { pending } => EpollReadiness {
+                readable: !pending.is_empty(),
+                generation: inner.generation,
+                ..EpollReadiness::default()
+            },
+            SocketState::Connected(connection) => connection.epoll_readiness(),
+            SocketState::Datagram { queue, .. } => EpollReadiness {
+                readable: !queue.is_empty(),
+                writable: true,
+                generation: inner.generation,
+                ..EpollReadiness::default()
+            },
+        })
+    }
+}
+
+/// A socket operation that has to wait until the socket is ready if it is blocking.
+#[derive(Clone, Debug)]
+pub enum SocketOp<'tcx> {
+    Accept {
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Option<MPlaceTy<'tcx, Provenance>>,
+        nonblock: bool,
+    },
+    Recv {
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        peek: bool,
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Option<MPlaceTy<'tcx, Provenance>>,
+    },
+    Send {
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        to: Option<SocketAddr>,
+    },
+}
+
+impl VisitTags for SocketOp<'_> {
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        match self {
+            SocketOp::Accept { addr, addrlen, nonblock: _ } => {
+                addr.visit_tags(visit);
+                addrlen.visit_tags(visit);
+            }
+            SocketOp::Recv { buf, len: _, peek: _, addr, addrlen } => {
+                buf.visit_tags(visit);
+                addr.visit_tags(visit);
+                addrlen.visit_tags(visit);
+            }
+            SocketOp::Send { buf, len: _, to: _ } => buf.visit_tags(visit),
+        }
+    }
+}
+
+/// A `SocketOp` on the socket `fd`, whose result is written to `dest`.
+struct SocketOpCall<'tcx> {
+    fd: i32,
+    op: SocketOp<'tcx>,
+    /// Whether `MSG_DONTWAIT` was passed, which makes the operation fail instead of blocking.
+    dontwait: bool,
+    dest: PlaceTy<'tcx, Provenance>,
+}
+
+impl VisitTags for SocketOpCall<'_> {
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        let SocketOpCall { fd: _, op, dontwait: _, dest } = self;
+        op.visit_tags(visit);
+        dest.visit_tags(visit);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> BlockingIoOp<'mir, 'tcx> for SocketOpCall<'tcx> {
+    fn try_complete(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx, bool> {
+        let Some(result) = this.try_socket_op(self.fd, &self.op, self.dontwait)? else {
+            return Ok(false);
+        };
+        this.write_int(result, &self.dest)?;
+        Ok(true)
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Creates a TCP or UDP socket. Other kinds of sockets are not supported, except for the
+    /// `AF_UNIX` stream sockets created by `socketpair`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/socket.2.html>
+    fn socket(
+        &mut self,
+        domain: &OpTy<'tcx, Provenance>,
+        type_: &OpTy<'tcx, Provenance>,
+        protocol: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let domain = this.read_scalar(domain)?.to_i32()?;
+        let mut type_ = this.read_scalar(type_)?.to_i32()?;
+        let protocol = this.read_scalar(protocol)?.to_i32()?;
+
+        let ipv6 = if domain == this.eval_libc_i32("AF_INET") {
+            false
+        } else if domain == this.eval_libc_i32("AF_INET6") {
+            true
+        } else {
+            throw_unsup_format!(
+                "socket: domain {domain:#x} is unsupported, only AF_INET and AF_INET6 are allowed"
+            );
+        };
+
+        let mut nonblock = false;
+        if this.tcx.sess.target.os != "macos" {
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            nonblock = type_ & sock_nonblock == sock_nonblock;
+            // `SOCK_CLOEXEC` has no effect, see `posix_spawn`.
+            type_ &= !(sock_nonblock | sock_cloexec);
+        }
+        let socket_protocol = if type_ == this.eval_libc_i32("SOCK_STREAM")
+            && (protocol == 0 || protocol == this.eval_libc_i32("IPPROTO_TCP"))
+        {
+            Protocol::Tcp
+        } else if type_ == this.eval_libc_i32("SOCK_DGRAM")
+            && (protocol == 0 || protocol == this.eval_libc_i32("IPPROTO_UDP"))
+        {
+            Protocol::Udp
+        } else {
+            throw_unsup_format!(
+                "socket: type {type_:#x} with protocol {protocol} is unsupported, only TCP and UDP sockets are allowed"
+            );
+        };
+
+        let socket = SocketInner::new(socket_protocol, ipv6, nonblock);
+        let fd = this
+            .machine
+            .file_handler
+            .insert_fd(Box::new(InetSocket { inner: Rc::new(RefCell::new(socket)) }));
+        Ok(Scalar::from_i32(fd))
+    }
+
+    /// Binds a socket to a loopback or unspecified address.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/bind.2.html>
+    fn bind(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let result = match this.read_socket_addr(addr, addrlen)? {
+            Ok(addr) => this.machine.network.bind(&socket, addr).map(|_| 0),
+            Err(e) => Err(e),
+        };
+        Ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
+    }
+
+    /// Marks a TCP socket as accepting connections. Connections are never refused because the
+    /// `backlog` is full.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/listen.2.html>
+    fn listen(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        backlog: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let _backlog = this.read_scalar(backlog)?.to_i32()?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        if socket.borrow().protocol != Protocol::Tcp {
+            let eopnotsupp = this.eval_libc("EOPNOTSUPP");
+            this.set_last_error(eopnotsupp)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        // Like on Linux, listening on an unbound socket binds it to an ephemeral port.
+        if let Err(e) = this.machine.network.ensure_bound(&socket) {
+            this.set_last_error_from_io_error(e.kind())?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        let mut inner = socket.borrow_mut();
+        if matches!(inner.state, SocketState::Connected(_)) {
+            drop(inner);
+            let einval = this.eval_libc("EINVAL");
+            this.set_last_error(einval)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        if matches!(inner.state, SocketState::Idle) {
+            inner.state = SocketState::Listening { pending: VecDeque::new() };
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Connects a TCP socket to a listening socket, or sets the peer of a UDP socket. The
+    /// connection is established immediately, even for non-blocking sockets.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/connect.2.html>
+    fn connect(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+        let addr = match this.read_socket_addr(addr, addrlen)? {
+            Ok(addr) if addr.is_ipv6() == socket.borrow().ipv6 => resolve_remote_addr(addr)?,
+            Ok(_) => {
+                this.set_last_error_from_io_error(ErrorKind::InvalidInput)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(Scalar::from_i32(-1));
+            }
+        };
+
+        let protocol = socket.borrow().protocol;
+        if protocol == Protocol::Tcp {
+            let error = match socket.borrow().state {
+                SocketState::Idle => None,
+                SocketState::Connected(_) => Some("EISCONN"),
+                SocketState::Listening { .. } | SocketState::Datagram { .. } => Some("EINVAL"),
+            };
+            if let Some(error) = error {
+                let error = this.eval_libc(error);
+                this.set_last_error(error)?;
+                return Ok(Scalar::from_i32(-1));
+            }
+        }
+        let local_addr = match this.machine.network.ensure_bound(&socket) {
+            Ok(local_addr) => source_addr(local_addr, addr),
+            Err(e) => {
+                this.set_last_error_from_io_error(e.kind())?;
+                return Ok(Scalar::from_i32(-1));
+            }
+        };
+        // Like on Linux, a socket bound to the unspecified address gets the address it connects
+        // from as its local address.
+        socket.borrow_mut().local_addr = Some(local_addr);
+
+        match protocol {
+            Protocol::Tcp => {
+                let port = Port { protocol, ipv6: addr.is_ipv6(), number: addr.port() };
+                let listener = this.machine.network.lookup(port);
+                let Some(mut listener) = listener.as_ref().map(|listener| listener.borrow_mut())
+                else {
+                    this.set_last_error_from_io_error(ErrorKind::ConnectionRefused)?;
+                    return Ok(Scalar::from_i32(-1));
+                };
+                let SocketState::Listening { pending } = &mut listener.state else {
+                    drop(listener);
+                    this.set_last_error_from_io_error(ErrorKind::ConnectionRefused)?;
+                    return Ok(Scalar::from_i32(-1));
+                };
+                let (client, server) = Connection::new_pair(local_addr, addr);
+                pending.push_back(PendingConnection { local_addr: addr, connection: server });
+                listener.generation = listener.generation.wrapping_add(1);
+                drop(listener);
+                socket.borrow_mut().state = SocketState::Connected(client);
+            }
+            Protocol::Udp => {
+                let mut inner = socket.borrow_mut();
+                let SocketState::Datagram { peer, .. } = &mut inner.state else {
+                    bug!("UDP socket is not in the datagram state");
+                };
+                *peer = Some(addr);
+            }
+        }
+        this.io_state_changed();
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Accepts a pending connection of a listening socket, blocking until there is one unless the
+    /// socket is non-blocking. The result is written to `dest`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/accept.2.html>
+    fn accept(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+        flags: Option<&OpTy<'tcx, Provenance>>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let (addr, addrlen) = this.read_socket_addr_dest(addr, addrlen)?;
+        let mut nonblock = false;
+        if let Some(flags) = flags {
+            let flags = this.read_scalar(flags)?.to_i32()?;
+            let sock_nonblock = this.eval_libc_i32("SOCK_NONBLOCK");
+            let sock_cloexec = this.eval_libc_i32("SOCK_CLOEXEC");
+            if flags & !(sock_nonblock | sock_cloexec) != 0 {
+                throw_unsup_format!("accept4: flags {flags:#x} are unsupported");
+            }
+            // `SOCK_CLOEXEC` has no effect, see `posix_spawn`.
+            nonblock = flags & sock_nonblock == sock_nonblock;
+        }
+
+        this.socket_op(fd, SocketOp::Accept { addr, addrlen, nonblock }, false, dest)
+    }
+
+    /// Sends data over a connected socket, or a datagram to `to` over a UDP socket, blocking
+    /// until there is space in the buffer of the connection unless the socket is non-blocking.
+    /// The result is written to `dest`.
+    ///
+    /// Datagrams sent to ports that no socket is bound to are dropped.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/send.2.html>
+    fn send(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        buf: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        to: Option<(&OpTy<'tcx, Provenance>, &OpTy<'tcx, Provenance>)>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        let len = this.read_target_usize(len)?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+
+        this.check_ptr_access_align(
+            buf,
+            Size::from_bytes(len),
+            Align::ONE,
+            CheckInAllocMsg::MemoryAccessTest,
+        )?;
+
+        let msg_dontwait = this.eval_libc_i32("MSG_DONTWAIT");
+        // The Rust runtime ignores `SIGPIPE`, and we never raise it anyway.
+        let msg_nosignal =
+            if this.tcx.sess.target.os == "macos" { 0 } else { this.eval_libc_i32("MSG_NOSIGNAL") };
+        if flags & !(msg_dontwait | msg_nosignal) != 0 {
+            throw_unsup_format!("send: flags {flags:#x} are unsupported");
+        }
+
+        let to = match to {
+            Some((addr, addrlen)) if !this.ptr_is_null(this.read_pointer(addr)?)? =>
+                match this.read_socket_addr(addr, addrlen)? {
+                    Ok(addr) => Some(addr),
+                    Err(e) => {
+                        this.set_last_error_from_io_error(e.kind())?;
+                        return this.write_int(-1, dest);
+                    }
+                },
+            _ => None,
+        };
+
+        this.socket_op(fd, SocketOp::Send { buf, len, to }, flags & msg_dontwait != 0, dest)
+    }
+
+    /// Receives data from a connected socket, or a datagram from a UDP socket, blocking until
+    /// there is something to receive unless the socket is non-blocking. For UDP sockets, the
+    /// address the datagram was sent from is written to `from`. The result is written to `dest`.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/recv.2.html>
+    fn recv(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        buf: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+        from: Option<(&OpTy<'tcx, Provenance>, &OpTy<'tcx, Provenance>)>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let buf = this.read_pointer(buf)?;
+        let len = this.read_target_usize(len)?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+        let (addr, addrlen) = match from {
+            Some((addr, addrlen)) => this.read_socket_addr_dest(addr, addrlen)?,
+            None => (Pointer::null(), None),
+        };
+
+        this.check_ptr_access_align(
+            buf,
+            Size::from_bytes(len),
+            Align::ONE,
+            CheckInAllocMsg::MemoryAccessTest,
+        )?;
+
+        let msg_peek = this.eval_libc_i32("MSG_PEEK");
+        let msg_dontwait = this.eval_libc_i32("MSG_DONTWAIT");
+        if flags & !(msg_peek | msg_dontwait) != 0 {
+            throw_unsup_format!("recv: flags {flags:#x} are unsupported");
+        }
+        let peek = flags & msg_peek == msg_peek;
+
+        this.socket_op(
+            fd,
+            SocketOp::Recv { buf, len, peek, addr, addrlen },
+            flags & msg_dontwait != 0,
+            dest,
+        )
+    }
+
+    /// <https://man7.org/linux/man-pages/man2/getsockname.2.html>
+    fn getsockname(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let (addr, addrlen) = this.read_socket_addr_dest(addr, addrlen)?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let local_addr = {
+            let inner = socket.borrow();
+            inner.local_addr.unwrap_or_else(|| SocketAddr::new(unspecified_ip(inner.ipv6), 0))
+        };
+        this.write_socket_addr(local_addr, addr, addrlen.as_ref())?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// <https://man7.org/linux/man-pages/man2/getpeername.2.html>
+    fn getpeername(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let (addr, addrlen) = this.read_socket_addr_dest(addr, addrlen)?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let Some(peer_addr) = socket.borrow().peer_addr() else {
+            this.set_last_error_from_io_error(ErrorKind::NotConnected)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        this.write_socket_addr(peer_addr, addr, addrlen.as_ref())?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Only the options that `std` sets on sockets are supported. None of them have an effect.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/setsockopt.2.html>
+    fn setsockopt(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        level: &OpTy<'tcx, Provenance>,
+        name: &OpTy<'tcx, Provenance>,
+        value: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let name = this.read_scalar(name)?.to_i32()?;
+        let len = this.read_scalar(len)?.to_u32()?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        // All supported options are `c_int`s.
+        if u64::from(len) < this.machine.layouts.i32.size.bytes() {
+            this.set_last_error_from_io_error(ErrorKind::InvalidInput)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        let value = this.deref_pointer_as(value, this.machine.layouts.i32)?;
+        let value = this.read_scalar(&value)?.to_i32()?;
+
+        let sol_socket = this.eval_libc_i32("SOL_SOCKET");
+        let ipproto_tcp = this.eval_libc_i32("IPPROTO_TCP");
+        let ipproto_ipv6 = this.eval_libc_i32("IPPROTO_IPV6");
+        if level == sol_socket && name == this.eval_libc_i32("SO_REUSEADDR") {
+            // Closed sockets do not linger, so their addresses can always be reused.
+        } else if this.tcx.sess.target.os == "macos"
+            && level == sol_socket
+            && name == this.eval_libc_i32("SO_NOSIGPIPE")
+        {
+            // We never raise `SIGPIPE`.
+        } else if level == ipproto_tcp && name == this.eval_libc_i32("TCP_NODELAY") {
+            socket.borrow_mut().nodelay = value != 0;
+        } else if level == ipproto_ipv6 && name == this.eval_libc_i32("IPV6_V6ONLY") {
+            if value == 0 {
+                throw_unsup_format!("setsockopt: dual-stack IPv6 sockets are unsupported");
+            }
+        } else {
+            throw_unsup_format!("setsockopt: option {name} at level {level} is unsupported");
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// <https://man7.org/linux/man-pages/man2/getsockopt.2.html>
+    fn getsockopt(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        level: &OpTy<'tcx, Provenance>,
+        name: &OpTy<'tcx, Provenance>,
+        value: &OpTy<'tcx, Provenance>,
+        len: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let level = this.read_scalar(level)?.to_i32()?;
+        let name = this.read_scalar(name)?.to_i32()?;
+        let len = this.deref_pointer_as(len, this.libc_ty_layout("socklen_t"))?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let result = if level == this.eval_libc_i32("SOL_SOCKET")
+            && name == this.eval_libc_i32("SO_ERROR")
+        {
+            // Errors are always reported directly by the operation that caused them.
+            0
+        } else if level == this.eval_libc_i32("IPPROTO_TCP")
+            && name == this.eval_libc_i32("TCP_NODELAY")
+        {
+            socket.borrow().nodelay.into()
+        } else {
+            throw_unsup_format!("getsockopt: option {name} at level {level} is unsupported");
+        };
+
+        let size = this.machine.layouts.i32.size;
+        if u64::from(this.read_scalar(&len)?.to_u32()?) < size.bytes() {
+            this.set_last_error_from_io_error(ErrorKind::InvalidInput)?;
+            return Ok(Scalar::from_i32(-1));
+        }
+        let value = this.deref_pointer_as(value, this.machine.layouts.i32)?;
+        this.write_scalar(Scalar::from_i32(result), &value)?;
+        this.write_int(size.bytes(), &len)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// <https://man7.org/linux/man-pages/man2/shutdown.2.html>
+    fn shutdown(
+        &mut self,
+        fd: &OpTy<'tcx, Provenance>,
+        how: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let how = this.read_scalar(how)?.to_i32()?;
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Scalar::from_i32(-1));
+        };
+
+        let (read, write) = if how == this.eval_libc_i32("SHUT_RD") {
+            (true, false)
+        } else if how == this.eval_libc_i32("SHUT_WR") {
+            (false, true)
+        } else if how == this.eval_libc_i32("SHUT_RDWR") {
+            (true, true)
+        } else {
+            this.set_last_error_from_io_error(ErrorKind::InvalidInput)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        let SocketState::Connected(connection) = &mut socket.borrow_mut().state else {
+            this.set_last_error_from_io_error(ErrorKind::NotConnected)?;
+            return Ok(Scalar::from_i32(-1));
+        };
+        connection.shutdown(read, write);
+        this.io_state_changed();
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// `ioctl` only supports `FIOCLEX` and `FIONBIO`, which is how `std` makes file descriptors
+    /// close-on-exec and non-blocking.
+    fn ioctl(&mut self, args: &[OpTy<'tcx, Provenance>]) -> InterpResult<'tcx, i32> {
+        let this = self.eval_context_mut();
+
+        if args.len() < 2 {
+            throw_ub_format!(
+                "incorrect number of arguments for ioctl: got {}, expected at least 2",
+                args.len()
+            );
+        }
+        let fd = this.read_scalar(&args[0])?.to_i32()?;
+        let request = this.read_target_usize(&args[1])?;
+
+        if request == this.eval_libc("FIOCLEX").to_target_usize(this)? {
+            // This has no effect, see `posix_spawn`.
+            if this.machine.file_handler.handles.contains_key(&fd) {
+                Ok(0)
+            } else {
+                this.handle_not_found()
+            }
+        } else if request == this.eval_libc("FIONBIO").to_target_usize(this)? {
+            if args.len() < 3 {
+                throw_ub_format!(
+                    "incorrect number of arguments for ioctl with request=`FIONBIO`: got {}, expected at least 3",
+                    args.len()
+                );
+            }
+            let value = this.deref_pointer_as(&args[2], this.machine.layouts.i32)?;
+            let nonblock = this.read_scalar(&value)?.to_i32()? != 0;
+            let Some(file_descriptor) = this.machine.file_handler.handles.get_mut(&fd) else {
+                return this.handle_not_found();
+            };
+            file_descriptor.set_nonblocking(nonblock)?;
+            Ok(0)
+        } else {
+            throw_unsup_format!("the {request:#x} request is not supported for `ioctl`");
+        }
+    }
+
+    /// Performs `op` on the socket `fd` and writes the result to `dest`. If the socket is not
+    /// ready, the operation either fails with `EAGAIN`, or blocks the thread until the socket is
+    /// ready.
+    fn socket_op(
+        &mut self,
+        fd: i32,
+        op: SocketOp<'tcx>,
+        dontwait: bool,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        this.block_on_io_op(SocketOpCall { fd, op, dontwait, dest: dest.clone() })
+    }
+
+    /// Tries to perform `op` on the socket `fd`. Returns `None` if the thread should block until
+    /// the socket is ready, and the result of the operation otherwise.
+    fn try_socket_op(
+        &mut self,
+        fd: i32,
+        op: &SocketOp<'tcx>,
+        dontwait: bool,
+    ) -> InterpResult<'tcx, Option<i64>> {
+        let this = self.eval_context_mut();
+
+        let Some(socket) = this.inet_socket(fd)? else {
+            return Ok(Some(-1));
+        };
+        let result = match op {
+            SocketOp::Accept { addr, addrlen, nonblock } =>
+                this.socket_accept(&socket, *addr, addrlen.as_ref(), *nonblock)?,
+            SocketOp::Recv { buf, len, peek, addr, addrlen } =>
+                this.socket_recv(&socket, *buf, *len, *peek, *addr, addrlen.as_ref())?,
+            SocketOp::Send { buf, len, to } => this.socket_send(&socket, *buf, *len, *to)?,
+        };
+        match result {
+            Err(e)
+                if e.kind() == ErrorKind::WouldBlock && !dontwait && !socket.borrow().nonblock =>
+                Ok(None),
+            result => {
+                this.io_state_changed();
+                Ok(Some(this.try_unwrap_io_result(result)?))
+            }
+        }
+    }
+
+    fn socket_accept(
+        &mut self,
+        socket: &Rc<RefCell<SocketInner>>,
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Option<&MPlaceTy<'tcx, Provenance>>,
+        nonblock: bool,
+    ) -> InterpResult<'tcx, io::Result<i64>> {
+        let this = self.eval_context_mut();
+
+        let mut inner = socket.borrow_mut();
+        let SocketState::Listening { pending } = &mut inner.state else {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        };
+        let Some(PendingConnection { local_addr, connection }) = pending.pop_front() else {
+            return Ok(Err(ErrorKind::WouldBlock.into()));
+        };
+        let peer_addr = connection.peer_addr;
+        let mut accepted = SocketInner::new(Protocol::Tcp, inner.ipv6, nonblock);
+        accepted.nodelay = inner.nodelay;
+        accepted.local_addr = Some(local_addr);
+        accepted.state = SocketState::Connected(connection);
+        drop(inner);
+
+        let fd = this
+            .machine
+            .file_handler
+            .insert_fd(Box::new(InetSocket { inner: Rc::new(RefCell::new(accepted)) }));
+        this.write_socket_addr(peer_addr, addr, addrlen)?;
+        Ok(Ok(fd.into()))
+    }
+
+    fn socket_recv(
+        &mut self,
+        socket: &Rc<RefCell<SocketInner>>,
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        peek: bool,
+        addr: Pointer<Option<Provenance>>,
+        addrlen: Option<&MPlaceTy<'tcx, Provenance>>,
+    ) -> InterpResult<'tcx, io::Result<i64>> {
+        let this = self.eval_context_mut();
+
+        let mut bytes = vec![0; usize::try_from(len).unwrap()];
+        let (read, source) = match socket.borrow_mut().recv(&mut bytes, peek) {
+            Ok(result) => result,
+            Err(e) => return Ok(Err(e)),
+        };
+        bytes.truncate(read);
+        this.write_bytes_ptr(buf, bytes)?;
+        if let Some(source) = source {
+            this.write_socket_addr(source, addr, addrlen)?;
+        }
+        Ok(Ok(i64::try_from(read).unwrap()))
+    }
+
+    fn socket_send(
+        &mut self,
+        socket: &Rc<RefCell<SocketInner>>,
+        buf: Pointer<Option<Provenance>>,
+        len: u64,
+        to: Option<SocketAddr>,
+    ) -> InterpResult<'tcx, io::Result<i64>> {
+        let this = self.eval_context_mut();
+
+        let bytes = this.read_bytes_ptr_strip_provenance(buf, Size::from_bytes(len))?.to_vec();
+        let (protocol, ipv6, peer_addr) = {
+            let inner = socket.borrow();
+            if let SocketState::Connected(connection) = &inner.state {
+                // The destination address is ignored for connected TCP sockets.
+                let result = connection.send(&bytes);
+                return Ok(result.map(|len| i64::try_from(len).unwrap()));
+            }
+            (inner.protocol, inner.ipv6, inner.peer_addr())
+        };
+        if protocol == Protocol::Tcp {
+            return Ok(Err(ErrorKind::NotConnected.into()));
+        }
+
+        let Some(to) = to.or(peer_addr) else {
+            return Ok(Err(ErrorKind::NotConnected.into()));
+        };
+        if to.is_ipv6() != ipv6 {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        }
+        let to = resolve_remote_addr(to)?;
+        let source = match this.machine.network.ensure_bound(socket) {
+            Ok(local_addr) => source_addr(local_addr, to),
+            Err(e) => return Ok(Err(e)),
+        };
+
+        let port = Port { protocol, ipv6, number: to.port() };
+        if let Some(receiver) = this.machine.network.lookup(port) {
+            receiver.borrow_mut().deliver(Datagram { source, data: bytes });
+        }
+        Ok(Ok(i64::try_from(len).unwrap()))
+    }
+
+    /// Returns the socket `fd` refers to, or sets the last error and returns `None` if `fd` is
+    /// not a TCP or UDP socket.
+    fn inet_socket(&mut self, fd: i32) -> InterpResult<'tcx, Option<Rc<RefCell<SocketInner>>>> {
+        let this = self.eval_context_mut();
+
+        let Some(file_descriptor) = this.machine.file_handler.handles.get(&fd) else {
+            this.handle_not_found::<i32>()?;
+            return Ok(None);
+        };
+        if let Some(socket) = file_descriptor.as_any().downcast_ref::<InetSocket>() {
+            return Ok(Some(socket.inner.clone()));
+        }
+        if file_descriptor.name() == "socketpair" {
+            throw_unsup_format!(
+                "only `read` and `write` are supported on sockets from `socketpair`"
+            );
+        }
+        let enotsock = this.eval_libc("ENOTSOCK");
+        this.set_last_error(enotsock)?;
+        Ok(None)
+    }
+
+    /// Reads a `sockaddr_in` or `sockaddr_in6` of `addrlen` bytes. Other address families are
+    /// invalid for TCP and UDP sockets.
+    fn read_socket_addr(
+        &mut self,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, io::Result<SocketAddr>> {
+        let this = self.eval_context_mut();
+
+        let addrlen = u64::from(this.read_scalar(addrlen)?.to_u32()?);
+        // `sockaddr_in` is the smaller of the two, and starts with the address family like every
+        // `sockaddr`.
+        let sockaddr_in_layout = this.libc_ty_layout("sockaddr_in");
+        if addrlen < sockaddr_in_layout.size.bytes() {
+            return Ok(Err(ErrorKind::InvalidInput.into()));
+        }
+        let sockaddr_in = this.deref_pointer_as(addr, sockaddr_in_layout)?;
+        let family = this.project_field_named(&sockaddr_in, "sin_family")?;
+        let family = this.read_scalar(&family)?.to_uint(family.layout.size)?;
+        let family = i32::try_from(family).unwrap();
+
+        if family == this.eval_libc_i32("AF_INET") {
+            let port = this.project_field_named(&sockaddr_in, "sin_port")?;
+            let port = this.read_network_bytes(&port)?;
+            let ip = this.project_field_named(&sockaddr_in, "sin_addr")?;
+            let ip = this.project_field_named(&ip, "s_addr")?;
+            let ip = this.read_network_bytes(&ip)?;
+            let ip = Ipv4Addr::from(<[u8; 4]>::try_from(ip).unwrap());
+            let port = u16::from_be_bytes(port.try_into().unwrap());
+            Ok(Ok(SocketAddr::new(ip.into(), port)))
+        } else if family == this.eval_libc_i32("AF_INET6") {
+            let sockaddr_in6_layout = this.libc_ty_layout("sockaddr_in6");
+            if addrlen < sockaddr_in6_layout.size.bytes() {
+                return Ok(Err(ErrorKind::InvalidInput.into()));
+            }
+            let sockaddr_in6 = this.deref_pointer_as(addr, sockaddr_in6_layout)?;
+            let port = this.project_field_named(&sockaddr_in6, "sin6_port")?;
+            let port = this.read_network_bytes(&port)?;
+            let flowinfo = this.project_field_named(&sockaddr_in6, "sin6_flowinfo")?;
+            let flowinfo = this.read_scalar(&flowinfo)?.to_u32()?;
+            let ip = this.project_field_named(&sockaddr_in6, "sin6_addr")?;
+            let ip = this.project_field_named(&ip, "s6_addr")?;
+            let ip = this.read_network_bytes(&ip)?;
+            let scope_id = this.project_field_named(&sockaddr_in6, "sin6_scope_id")?;
+            let scope_id = this.read_scalar(&scope_id)?.to_u32()?;
+            let ip = Ipv6Addr::from(<[u8; 16]>::try_from(ip).unwrap());
+            let port = u16::from_be_bytes(port.try_into().unwrap());
+            Ok(Ok(SocketAddrV6::new(ip, port, flowinfo, scope_id).into()))
+        } else {
+            Ok(Err(ErrorKind::InvalidInput.into()))
+        }
+    }
+
+    /// Reads the bytes of a field that is stored in network byte order.
+    fn read_network_bytes(
+        &self,
+        place: &MPlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Vec<u8>> {
+        let this = self.eval_context_ref();
+        Ok(this.read_bytes_ptr_strip_provenance(place.ptr, place.layout.size)?.to_vec())
+    }
+
+    /// Reads the arguments that point to the buffer a socket address is returned in, and to its
+    /// length. The buffer may be null, in which case no address is returned.
+    fn read_socket_addr_dest(
+        &mut self,
+        addr: &OpTy<'tcx, Provenance>,
+        addrlen: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, (Pointer<Option<Provenance>>, Option<MPlaceTy<'tcx, Provenance>>)> {
+        let this = self.eval_context_mut();
+
+        let addr = this.read_pointer(addr)?;
+        if this.ptr_is_null(addr)? {
+            return Ok((addr, None));
+        }
+        let addrlen = this.deref_pointer_as(addrlen, this.libc_ty_layout("socklen_t"))?;
+        Ok((addr, Some(addrlen)))
+    }
+
+    /// Writes `addr` as a `sockaddr_in` or `sockaddr_in6` to `dest`, truncated to the length in
+    /// `dest_len`, and stores the full length in `dest_len`. Does nothing if `dest_len` is `None`.
+    fn write_socket_addr(
+        &mut self,
+        addr: SocketAddr,
+        dest: Pointer<Option<Provenance>>,
+        dest_len: Option<&MPlaceTy<'tcx, Provenance>>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(dest_len) = dest_len else {
+            return Ok(());
+        };
+        let layout = this.libc_ty_layout(match addr {
+            SocketAddr::V4(_) => "sockaddr_in",
+            SocketAddr::V6(_) => "sockaddr_in6",
+        });
+        // Build the whole address in a temporary allocation, so that it can be truncated.
+        let sockaddr = this.allocate(layout, MiriMemoryKind::Machine.into())?;
+        this.write_bytes_ptr(
+            sockaddr.ptr,
+            iter::repeat(0u8).take(usize::try_from(layout.size.bytes()).unwrap()),
+        )?;
+        match addr {
+            SocketAddr::V4(addr) => {
+                let af_inet = this.eval_libc_i32("AF_INET");
+                let family = this.project_field_named(&sockaddr, "sin_family")?;
+                this.write_int(af_inet, &family)?;
+                let port = this.project_field_named(&sockaddr, "sin_port")?;
+                this.write_bytes_ptr(port.ptr, addr.port().to_be_bytes())?;
+                let ip = this.project_field_named(&sockaddr, "sin_addr")?;
+                let ip = this.project_field_named(&ip, "s_addr")?;
+                this.write_bytes_ptr(ip.ptr, addr.ip().octets())?;
+            }
+            SocketAddr::V6(addr) => {
+                let af_inet6 = this.eval_libc_i32("AF_INET6");
+                let family = this.project_field_named(&sockaddr, "sin6_family")?;
+                this.write_int(af_inet6, &family)?;
+                let port = this.project_field_named(&sockaddr, "sin6_port")?;
+                this.write_bytes_ptr(port.ptr, addr.port().to_be_bytes())?;
+                let flowinfo = this.project_field_named(&sockaddr, "sin6_flowinfo")?;
+                this.write_int(addr.flowinfo(), &flowinfo)?;
+                let ip = this.project_field_named(&sockaddr, "sin6_addr")?;
+                let ip = this.project_field_named(&ip, "s6_addr")?;
+                this.write_bytes_ptr(ip.ptr, addr.ip().octets())?;
+                let scope_id = this.project_field_named(&sockaddr, "sin6_scope_id")?;
+                this.write_int(addr.scope_id(), &scope_id)?;
+            }
+        }
+
+        let len = u64::from(this.read_scalar(dest_len)?.to_u32()?).min(layout.size.bytes());
+        this.mem_copy(sockaddr.ptr, Align::ONE, dest, Align::ONE, Size::from_bytes(len), true)?;
+        this.deallocate_ptr(sockaddr.ptr, None, MiriMemoryKind::Machine.into())?;
+        this.write_int(layout.size.bytes(), dest_len)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,587 @@
+//! Emulation of `posix_spawn` and `waitpid` for `-Zmiri-emulate-processes`.
+//!
+//! The interpreted program can only spawn itself. Every process is interpreted by its own
+//! `InterpCx` with separate memory, and the processes take turns to run, see `eval_processes`.
+//! They can only communicate through the pipes and other file descriptions they share, and
+//! through their exit statuses.
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::iter;
+use std::mem;
+use std::rc::Rc;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_target::abi::Size;
+
+use crate::concurrency::thread::BlockingIoOp;
+use crate::shims::os_str::bytes_to_os_str;
+use crate::shims::unix::fs::FileDescriptor;
+use crate::*;
+
+/// The PID of the process Miri starts.
+pub const ROOT_PID: i32 = 1000;
+
+/// The state shared by all processes of the interpreted program.
+#[derive(Debug)]
+pub struct ProcessManager {
+    /// The path of the interpreted program, which is the only program that can be spawned. This
+    /// is also what `readlink("/proc/self/exe")` returns.
+    pub exe: String,
+    /// The PID of the next spawned process.
+    next_pid: i32,
+    /// The processes that were spawned, but did not start to run yet.
+    spawned: Vec<SpawnedProcess>,
+    /// The spawned processes that were not waited for yet, by PID.
+    children: FxHashMap<i32, Child>,
+    /// Whether a process other than the root process stopped because of an error.
+    pub failed: bool,
+}
+
+#[derive(Debug)]
+struct Child {
+    /// The PID of the process that spawned this one.
+    parent: i32,
+    state: ChildState,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ChildState {
+    Running,
+    /// The process was sent this signal, and stops before it runs again.
+    Killed(i32),
+    /// The process stopped with this wait status.
+    Terminated(i32),
+}
+
+/// A process that was spawned by `posix_spawn`.
+#[derive(Debug)]
+pub struct SpawnedProcess {
+    pub pid: i32,
+    pub args: Vec<String>,
+    pub env: Vec<(OsString, OsString)>,
+    /// The file descriptors the process inherits.
+    pub fds: BTreeMap<i32, Box<dyn FileDescriptor>>,
+}
+
+/// The result of waiting for a child process.
+enum WaitResult {
+    /// There is no child process to wait for.
+    NoChild,
+    /// None of the child processes stopped yet.
+    Running,
+    /// The child process with this PID stopped with this wait status.
+    Terminated(i32, i32),
+}
+
+impl ProcessManager {
+    pub fn new(exe: String) -> Self {
+        ProcessManager {
+            exe,
+            next_pid: ROOT_PID.checked_add(1).unwrap(),
+            spawned: Vec::new(),
+            children: FxHashMap::default(),
+            failed: false,
+        }
+    }
+
+    /// Takes the processes that were spawned since the last call, so that they can be started.
+    pub fn take_spawned(&mut self) -> Vec<SpawnedProcess> {
+        mem::take(&mut self.spawned)
+    }
+
+    /// Returns the signal `pid` was killed with, if it was killed since it last ran.
+    pub fn take_kill(&mut self, pid: i32) -> Option<i32> {
+        match self.children.get(&pid)?.state {
+            ChildState::Killed(signal) => Some(signal),
+            ChildState::Running | ChildState::Terminated(_) => None,
+        }
+    }
+
+    /// Records that `pid` stopped with the wait `status`.
+    pub fn terminated(&mut self, pid: i32, status: i32) {
+        self.children.get_mut(&pid).unwrap().state = ChildState::Terminated(status);
+    }
+
+    fn spawn(&mut self, parent: i32, mut process: SpawnedProcess) -> i32 {
+        let pid = self.next_pid;
+        self.next_pid = pid.checked_add(1).unwrap();
+        process.pid = pid;
+        self.spawned.push(process);
+        self.children.insert(pid, Child { parent, state: ChildState::Running });
+        pid
+    }
+
+    /// Waits for the child `pid` of `parent` to stop, or for any of its children if `pid` is
+    /// `None`. Stopped children are forgotten once they were waited for.
+    fn wait(&mut self, parent: i32, pid: Option<i32>) -> WaitResult {
+        let mut children = self
+            .children
+            .iter()
+            .filter(|(child_pid, child)| {
+                child.parent == parent && pid.map_or(true, |pid| pid == **child_pid)
+            })
+            .peekable();
+        if children.peek().is_none() {
+            return WaitResult::NoChild;
+        }
+        let terminated = children
+            .filter_map(|(child_pid, child)| match child.state {
+                ChildState::Terminated(status) => Some((*child_pid, status)),
+                ChildState::Running | ChildState::Killed(_) => None,
+            })
+            .min();
+        match terminated {
+            Some((child_pid, status)) => {
+                self.children.remove(&child_pid);
+                WaitResult::Terminated(child_pid, status)
+            }
+            None => WaitResult::Running,
+        }
+    }
+
+    /// Sends `signal` to `pid`. Returns `false` if there is no such process.
+    fn kill(&mut self, pid: i32, signal: i32) -> bool {
+        let Some(child) = self.children.get_mut(&pid) else {
+            return false;
+        };
+        if let ChildState::Running = child.state {
+            child.state = ChildState::Killed(signal);
+        }
+        true
+    }
+}
+
+/// The emulated process an interpreter runs.
+#[derive(Debug)]
+pub struct Process {
+    pub pid: i32,
+    pub manager: Rc<RefCell<ProcessManager>>,
+    /// The actions of the `posix_spawn_file_actions_t`s that were initialized and not destroyed
+    /// yet, by the ID that is stored in their first bytes.
+    file_actions: FxHashMap<u32, Vec<FileAction>>,
+    /// The ID of the next `posix_spawn_file_actions_t`. This starts at 1, so that an ID of 0 can
+    /// be recognized as uninitialized memory.
+    next_file_actions_id: u32,
+}
+
+/// An action that is taken on the file descriptors of a spawned process before it starts.
+#[derive(Clone, Copy, Debug)]
+enum FileAction {
+    Dup2 { fd: i32, newfd: i32 },
+    Close { fd: i32 },
+}
+
+impl Process {
+    pub fn new(pid: i32, manager: Rc<RefCell<ProcessManager>>) -> Self {
+        Process { pid, manager, file_actions: FxHashMap::default(), next_file_actions_id: 1 }
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExtPriv<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+trait EvalContextExtPriv<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    /// Returns the emulated process, or an error if `-Zmiri-emulate-processes` is not set.
+    fn process(&mut self, name: &str) -> InterpResult<'tcx, &mut Process> {
+        let this = self.eval_context_mut();
+        match &mut this.machine.process {
+            Some(process) => Ok(process),
+            None =>
+                throw_unsup_format!("`{name}` is only supported with `-Zmiri-emulate-processes`"),
+        }
+    }
+
+    /// Returns the place of the ID of the `posix_spawn_file_actions_t` `file_actions` points to.
+    fn file_actions_id(
+        &mut self,
+        file_actions: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, MPlaceTy<'tcx, Provenance>> {
+        let this = self.eval_context_mut();
+        let file_actions =
+            this.deref_pointer_as(file_actions, this.libc_ty_layout("posix_spawn_file_actions_t"))?;
+        file_actions.offset(Size::ZERO, this.machine.layouts.u32, this)
+    }
+
+    /// Adds `action` to the `posix_spawn_file_actions_t` `file_actions` points to.
+    fn add_file_action(
+        &mut self,
+        name: &str,
+        file_actions: &OpTy<'tcx, Provenance>,
+        action: FileAction,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        let id = this.file_actions_id(file_actions)?;
+        let id = this.read_scalar(&id)?.to_u32()?;
+        let Some(actions) = this.process(name)?.file_actions.get_mut(&id) else {
+            throw_ub_format!("`{name}` called on an uninitialized `posix_spawn_file_actions_t`");
+        };
+        actions.push(action);
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Reads the null-terminated array of C strings `ptr` points to, like `argv` and `envp`.
+    fn read_c_str_array(&self, ptr: &OpTy<'tcx, Provenance>) -> InterpResult<'tcx, Vec<Vec<u8>>> {
+        let this = self.eval_context_ref();
+        let layout = this.machine.layouts.mut_raw_ptr;
+        let array = this.deref_pointer_as(ptr, layout)?;
+        let mut strings = Vec::new();
+        for i in 0.. {
+            let offset = layout.size.checked_mul(i, this).unwrap();
+            let string = this.read_pointer(&array.offset(offset, layout, this)?)?;
+            if this.ptr_is_null(string)? {
+                break;
+            }
+            strings.push(this.read_c_str(string)?.to_owned());
+        }
+        Ok(strings)
+    }
+
+    /// Writes the wait status of the stopped child `pid` of the current process, or of any of
+    /// its stopped children if `pid` is `None`, to `status` and returns the child's PID. Returns
+    /// `None` if the thread should block until a child stops.
+    fn try_waitpid(
+        &mut self,
+        pid: Option<i32>,
+        status: Option<&MPlaceTy<'tcx, Provenance>>,
+    ) -> InterpResult<'tcx, Option<i32>> {
+        let this = self.eval_context_mut();
+        let process = this.machine.process.as_ref().unwrap();
+        let result = process.manager.borrow_mut().wait(process.pid, pid);
+        match result {
+            WaitResult::NoChild => {
+                let echild = this.eval_libc("ECHILD");
+                this.set_last_error(echild)?;
+                Ok(Some(-1))
+            }
+            WaitResult::Running => Ok(None),
+            WaitResult::Terminated(child_pid, wait_status) => {
+                if let Some(status) = status {
+                    this.write_scalar(Scalar::from_i32(wait_status), status)?;
+                }
+                Ok(Some(child_pid))
+            }
+        }
+    }
+}
+
+/// A `waitpid` for the child `pid`, or any child if `pid` is `None`, whose result is written to
+/// `dest`.
+struct WaitPid<'tcx> {
+    pid: Option<i32>,
+    status: Option<MPlaceTy<'tcx, Provenance>>,
+    dest: PlaceTy<'tcx, Provenance>,
+}
+
+impl VisitTags for WaitPid<'_> {
+    fn visit_tags(&self, visit: &mut dyn FnMut(BorTag)) {
+        let WaitPid { pid: _, status, dest } = self;
+        status.visit_tags(visit);
+        dest.visit_tags(visit);
+    }
+}
+
+impl<'mir, 'tcx: 'mir> BlockingIoOp<'mir, 'tcx> for WaitPid<'tcx> {
+    fn try_complete(&self, this: &mut MiriInterpCx<'mir, 'tcx>) -> InterpResult<'tcx, bool> {
+        let Some(result) = this.try_waitpid(self.pid, self.status.as_ref())? else {
+            return Ok(false);
+        };
+        this.write_scalar(Scalar::from_i32(result), &self.dest)?;
+        Ok(true)
+    }
+}
+
+impl<'mir, 'tcx: 'mir> EvalContextExt<'mir, 'tcx> for crate::MiriInterpCx<'mir, 'tcx> {}
+pub trait EvalContextExt<'mir, 'tcx: 'mir>: crate::MiriInterpCxExt<'mir, 'tcx> {
+    fn posix_spawn_file_actions_init(
+        &mut self,
+        file_actions: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        let process = this.process("posix_spawn_file_actions_init")?;
+        let id = process.next_file_actions_id;
+        process.next_file_actions_id = id.checked_add(1).unwrap();
+        process.file_actions.insert(id, Vec::new());
+        let id_place = this.file_actions_id(file_actions)?;
+        this.write_scalar(Scalar::from_u32(id), &id_place)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn posix_spawn_file_actions_destroy(
+        &mut self,
+        file_actions: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        let id_place = this.file_actions_id(file_actions)?;
+        let id = this.read_scalar(&id_place)?.to_u32()?;
+        if this.process("posix_spawn_file_actions_destroy")?.file_actions.remove(&id).is_none() {
+            throw_ub_format!(
+                "`posix_spawn_file_actions_destroy` called on an uninitialized `posix_spawn_file_actions_t`"
+            );
+        }
+        this.write_scalar(Scalar::from_u32(0), &id_place)?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    fn posix_spawn_file_actions_adddup2(
+        &mut self,
+        file_actions: &OpTy<'tcx, Provenance>,
+        fd: &OpTy<'tcx, Provenance>,
+        newfd: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        let newfd = this.read_scalar(newfd)?.to_i32()?;
+        if fd < 0 || newfd < 0 {
+            return Ok(this.eval_libc("EBADF"));
+        }
+        this.add_file_action(
+            "posix_spawn_file_actions_adddup2",
+            file_actions,
+            FileAction::Dup2 { fd, newfd },
+        )
+    }
+
+    fn posix_spawn_file_actions_addclose(
+        &mut self,
+        file_actions: &OpTy<'tcx, Provenance>,
+        fd: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        let fd = this.read_scalar(fd)?.to_i32()?;
+        if fd < 0 {
+            return Ok(this.eval_libc("EBADF"));
+        }
+        this.add_file_action(
+            "posix_spawn_file_actions_addclose",
+            file_actions,
+            FileAction::Close { fd },
+        )
+    }
+
+    fn posix_spawnattr_init(
+        &mut self,
+        attr: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        this.process("posix_spawnattr_init")?;
+        let attr = this.deref_pointer_as(attr, this.libc_ty_layout("posix_spawnattr_t"))?;
+        this.write_bytes_ptr(attr.ptr, iter::repeat(0u8).take(attr.layout.size.bytes_usize()))?;
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Spawned processes do not inherit any signal handlers, and signal masks have no effect
+    /// since signals are not supported, so the flags are only checked.
+    fn posix_spawnattr_setflags(
+        &mut self,
+        attr: &OpTy<'tcx, Provenance>,
+        flags: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        this.deref_pointer_as(attr, this.libc_ty_layout("posix_spawnattr_t"))?;
+        let flags = this.read_scalar(flags)?.to_i16()?;
+        let supported = this.eval_libc("POSIX_SPAWN_SETSIGDEF").to_i16()?
+            | this.eval_libc("POSIX_SPAWN_SETSIGMASK").to_i16()?;
+        if flags & !supported != 0 {
+            throw_unsup_format!("unsupported `posix_spawnattr_setflags` flags {flags:#x}");
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Spawns a new process running the interpreted program, which starts running the next time
+    /// the processes switch. `posix_spawnp` is the same as `posix_spawn`, since the only program
+    /// that can be spawned is found without searching `PATH`.
+    ///
+    /// The new process inherits the standard streams and the targets of `dup2` file actions. All
+    /// other file descriptors are treated as close-on-exec, so the `*_CLOEXEC` flags and `FIOCLEX`
+    /// have no effect.
+    ///
+    /// <https://man7.org/linux/man-pages/man3/posix_spawn.3.html>
+    #[allow(clippy::too_many_arguments)]
+    fn posix_spawn(
+        &mut self,
+        name: &str,
+        pid: &OpTy<'tcx, Provenance>,
+        path: &OpTy<'tcx, Provenance>,
+        file_actions: &OpTy<'tcx, Provenance>,
+        attrp: &OpTy<'tcx, Provenance>,
+        argv: &OpTy<'tcx, Provenance>,
+        envp: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        this.process(name)?;
+
+        let pid_ptr = this.read_pointer(pid)?;
+        let path = this.read_pointer(path)?;
+        let path = String::from_utf8_lossy(this.read_c_str(path)?).into_owned();
+        let file_actions = if this.ptr_is_null(this.read_pointer(file_actions)?)? {
+            Vec::new()
+        } else {
+            let id = this.file_actions_id(file_actions)?;
+            let id = this.read_scalar(&id)?.to_u32()?;
+            let Some(actions) = this.process(name)?.file_actions.get(&id) else {
+                throw_ub_format!(
+                    "`{name}` called with an uninitialized `posix_spawn_file_actions_t`"
+                );
+            };
+            actions.clone()
+        };
+        // The attributes were checked when they were set.
+        this.read_pointer(attrp)?;
+        let args = this
+            .read_c_str_array(argv)?
+            .into_iter()
+            .map(|arg| {
+                String::from_utf8(arg).map_err(|_| {
+                    err_unsup_format!("`{name}` only supports arguments that are valid UTF-8")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut env = Vec::new();
+        for var in this.read_c_str_array(envp)? {
+            // Like `getenv`, we ignore variables without a `=`.
+            if let Some(pos) = var.iter().position(|&byte| byte == b'=') {
+                let name = bytes_to_os_str(&var[..pos])?.to_owned();
+                let value = bytes_to_os_str(&var[pos.checked_add(1).unwrap()..])?.to_owned();
+                env.push((name, value));
+            }
+        }
+
+        let exe = this.process(name)?.manager.borrow().exe.clone();
+        if path != exe && path != "/proc/self/exe" {
+            throw_unsup_format!(
+                "`{name}` can only spawn the interpreted program `{exe}` itself, not `{path}`"
+            );
+        }
+
+        // Work out which file descriptions the new process gets, by taking the file actions on
+        // the file descriptors of the current process. Each file descriptor of the new process
+        // maps to the file descriptor of the current process it is a duplicate of, and whether
+        // it is closed on exec.
+        let mut fd_map: BTreeMap<i32, (i32, bool)> =
+            this.machine.file_handler.handles.keys().map(|&fd| (fd, (fd, fd > 2))).collect();
+        for action in file_actions {
+            match action {
+                FileAction::Dup2 { fd, newfd } => {
+                    let Some(&(source, _cloexec)) = fd_map.get(&fd) else {
+                        return Ok(this.eval_libc("EBADF"));
+                    };
+                    // The duplicate is never closed on exec, even if `fd == newfd`.
+                    fd_map.insert(newfd, (source, false));
+                }
+                FileAction::Close { fd } => {
+                    fd_map.remove(&fd);
+                }
+            }
+        }
+        let mut fds = BTreeMap::new();
+        for (fd, (source, cloexec)) in fd_map {
+            if cloexec {
+                continue;
+            }
+            let file_descriptor = this.machine.file_handler.handles.get_mut(&source).unwrap();
+            match file_descriptor.dup() {
+                Ok(duplicate) => {
+                    fds.insert(fd, duplicate);
+                }
+                Err(e) => return this.io_error_to_errnum(e.kind()),
+            }
+        }
+
+        let process = this.process(name)?;
+        let child = SpawnedProcess { pid: 0, args, env, fds };
+        let child_pid = process.manager.borrow_mut().spawn(process.pid, child);
+        if !this.ptr_is_null(pid_ptr)? {
+            let pid_place = this.deref_pointer_as(pid, this.libc_ty_layout("pid_t"))?;
+            this.write_scalar(Scalar::from_i32(child_pid), &pid_place)?;
+        }
+        Ok(Scalar::from_i32(0))
+    }
+
+    /// Waits for the child process `pid` to stop, or for any child process if `pid` is -1.
+    /// Process groups are not supported.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/waitpid.2.html>
+    fn waitpid(
+        &mut self,
+        pid: &OpTy<'tcx, Provenance>,
+        status: &OpTy<'tcx, Provenance>,
+        options: &OpTy<'tcx, Provenance>,
+        dest: &PlaceTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        this.process("waitpid")?;
+
+        let pid = this.read_scalar(pid)?.to_i32()?;
+        let status = if this.ptr_is_null(this.read_pointer(status)?)? {
+            None
+        } else {
+            Some(this.deref_pointer_as(status, this.machine.layouts.i32)?)
+        };
+        let options = this.read_scalar(options)?.to_i32()?;
+
+        let wnohang = this.eval_libc_i32("WNOHANG");
+        if options & !wnohang != 0 {
+            throw_unsup_format!("unsupported `waitpid` options {options:#x}");
+        }
+        let pid = match pid {
+            -1 => None,
+            pid if pid > 0 => Some(pid),
+            _ => throw_unsup_format!("`waitpid` does not support waiting for process groups"),
+        };
+
+        if options & wnohang != 0 {
+            let result = this.try_waitpid(pid, status.as_ref())?.unwrap_or(0);
+            return this.write_scalar(Scalar::from_i32(result), dest);
+        }
+        // If the child processes are still running, the I/O callbacks check again every time the
+        // processes switch.
+        this.block_on_io_op(WaitPid { pid, status, dest: dest.clone() })
+    }
+
+    /// Sends `SIGKILL` or `SIGTERM` to a child process, which both stop it immediately since
+    /// signal handlers are not supported. A signal of 0 only checks whether the process exists,
+    /// which also works for the root process.
+    ///
+    /// <https://man7.org/linux/man-pages/man2/kill.2.html>
+    fn kill(
+        &mut self,
+        pid: &OpTy<'tcx, Provenance>,
+        sig: &OpTy<'tcx, Provenance>,
+    ) -> InterpResult<'tcx, Scalar<Provenance>> {
+        let this = self.eval_context_mut();
+        this.process("kill")?;
+
+        let pid = this.read_scalar(pid)?.to_i32()?;
+        let sig = this.read_scalar(sig)?.to_i32()?;
+
+        let process = this.machine.process.as_ref().unwrap();
+        if pid <= 0 {
+            throw_unsup_format!("`kill` does not support sending signals to process groups");
+        }
+        if pid == process.pid {
+            throw_unsup_format!("`kill` does not support sending signals to the current process");
+        }
+        if sig != 0 && sig != this.eval_libc_i32("SIGKILL") && sig != this.eval_libc_i32("SIGTERM")
+        {
+            throw_unsup_format!("`kill` only supports `SIGKILL` and `SIGTERM`, not signal {sig}");
+        }
+        if sig != 0 && pid == ROOT_PID {
+            throw_unsup_format!("`kill` does not support signalling the root process");
+        }
+
+        let mut manager = process.manager.borrow_mut();
+        let exists = if sig == 0 {
+            manager.children.contains_key(&pid) || pid == ROOT_PID
+        } else {
+            manager.kill(pid, sig)
+        };
+        drop(manager);
+        if exists {
+            Ok(Scalar::from_i32(0))
+        } else {
+            let esrch = this.eval_libc("ESRCH");
+            this.set_last_error(esrch)?;
+            Ok(Scalar::from_i32(-1))
+        }
+    }
+}
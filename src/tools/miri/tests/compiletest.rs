@@ -1,8 +1,11 @@
 use colored::*;
 use regex::bytes::Regex;
+use std::collections::HashMap;
 use std::ffi::OsString;
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::{env, process::Command};
+use ui_test::color_eyre::eyre::bail;
 use ui_test::{color_eyre::Result, Config, Match, Mode, OutputConflictHandling};
 use ui_test::{status_emitter, CommandBuilder};
 
@@ -16,6 +19,11 @@ fn get_host() -> String {
         .host
 }
 
+/// The file the coverage tests write their report to.
+fn coverage_path() -> PathBuf {
+    PathBuf::from(env::var_os("CARGO_TARGET_DIR").unwrap()).join("miri-coverage.info")
+}
+
 // Build the shared object file for testing external C function calls.
 fn build_so_for_c_ffi_tests() -> PathBuf {
     let cc = option_env!("CC").unwrap_or("cc");
@@ -73,6 +81,13 @@ fn test_config(target: &str, path: &str, mode: Mode, with_dependencies: bool) ->
         program.args.push(flag);
     }
 
+    // The coverage tests write a report, which `check_coverage` checks once they ran.
+    if path.starts_with("tests/coverage") {
+        let mut flag = std::ffi::OsString::from("-Zmiri-coverage=");
+        flag.push(coverage_path().into_os_string());
+        program.args.push(flag);
+    }
+
     let bless = env::var_os("RUSTC_BLESS").is_some_and(|v| v !="0");
     let skip_ui_checks = env::var_os("MIRI_SKIP_UI_CHECKS").is_some();
 
@@ -112,10 +127,8 @@ fn test_config(target: &str, path: &str, mode: Mode, with_dependencies: bool) ->
     config
 }
 
-fn run_tests(mode: Mode, path: &str, target: &str, with_dependencies: bool) -> Result<()> {
-    let config = test_config(target, path, mode, with_dependencies);
-
-    // Handle command-line arguments.
+/// Returns the test filters passed on the command line, and whether `--quiet` was passed.
+fn parse_args() -> (Vec<String>, bool) {
     let mut after_dashdash = false;
     let mut quiet = false;
     let filters = std::env::args()
@@ -141,14 +154,25 @@ fn run_tests(mode: Mode, path: &str, target: &str, with_dependencies: bool) -> R
             }
         })
         .collect::<Vec<_>>();
+    (filters, quiet)
+}
+
+/// Whether the test at `path` is selected by the `filters` returned by `parse_args`.
+fn is_selected(path: &Path, filters: &[String]) -> bool {
+    filters.is_empty() || filters.iter().any(|f| path.display().to_string().contains(f))
+}
+
+fn run_tests(mode: Mode, path: &str, target: &str, with_dependencies: bool) -> Result<()> {
+    let config = test_config(target, path, mode, with_dependencies);
+
+    // Handle command-line arguments.
+    let (filters, quiet) = parse_args();
     eprintln!("   Compiler: {}", config.program.display());
     ui_test::run_tests_generic(
         config,
         // The files we're actually interested in (all `.rs` files).
         |path| {
-            path.extension().is_some_and(|ext| ext == "rs")
-                && (filters.is_empty()
-                    || filters.iter().any(|f| path.display().to_string().contains(f)))
+            path.extension().is_some_and(|ext| ext == "rs") && is_selected(path, &filters)
         },
         // This could be used to overwrite the `Config` on a per-test basis.
         |_, _| None,
@@ -263,6 +287,10 @@ fn main() -> Result<()> {
     ui(Mode::Pass, "tests/pass-dep", &target, WithDependencies)?;
     ui(Mode::Panic, "tests/panic", &target, WithDependencies)?;
     ui(Mode::Fail { require_patterns: true }, "tests/fail", &target, WithDependencies)?;
+    // The report is appended to, so we start with a fresh one.
+    let _ = fs::remove_file(coverage_path());
+    ui(Mode::Pass, "tests/coverage", &target, WithoutDependencies)?;
+    check_coverage()?;
     if cfg!(target_os = "linux") {
         ui(Mode::Pass, "tests/extern-so/pass", &target, WithoutDependencies)?;
         ui(
@@ -276,6 +304,54 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Checks the coverage report of `tests/coverage/lcov.rs` against its `// cov: <count>` comments,
+/// which give the count of their line, and its `// calls: <count>` comments, which give the
+/// number of calls of the function starting on the next line.
+fn check_coverage() -> Result<()> {
+    let test = "tests/coverage/lcov.rs";
+    if !is_selected(Path::new(test), &parse_args().0) {
+        return Ok(());
+    }
+    let report = match fs::read_to_string(coverage_path()) {
+        Ok(report) => report,
+        Err(e) => bail!("{test} did not write a coverage report: {e}"),
+    };
+    let mut lines = HashMap::new();
+    let mut functions = HashMap::new();
+    let mut calls = HashMap::new();
+    for record in report.lines() {
+        if let Some(record) = record.strip_prefix("DA:") {
+            let (line, count) = record.split_once(',').unwrap();
+            lines.insert(line.parse::<usize>()?, count.parse::<u64>()?);
+        } else if let Some(record) = record.strip_prefix("FN:") {
+            let (line, name) = record.split_once(',').unwrap();
+            functions.insert(line.parse::<usize>()?, name.to_owned());
+        } else if let Some(record) = record.strip_prefix("FNDA:") {
+            let (count, name) = record.split_once(',').unwrap();
+            calls.insert(name.to_owned(), count.parse::<u64>()?);
+        }
+    }
+
+    for (idx, source) in fs::read_to_string(test)?.lines().enumerate() {
+        let line = idx + 1;
+        if let Some((_, expected)) = source.split_once("// cov: ") {
+            let expected: u64 = expected.trim().parse()?;
+            let actual = lines.get(&line);
+            if actual != Some(&expected) {
+                bail!("{test}:{line}: expected {expected} executions, the report has {actual:?}");
+            }
+        }
+        if let Some((_, expected)) = source.split_once("// calls: ") {
+            let expected: u64 = expected.trim().parse()?;
+            let actual = functions.get(&(line + 1)).and_then(|name| calls.get(name));
+            if actual != Some(&expected) {
+                bail!("{test}:{}: expected {expected} calls, the report has {actual:?}", line + 1);
+            }
+        }
+    }
+    Ok(())
+}
+
 fn run_dep_mode(target: String, mut args: impl Iterator<Item = OsString>) -> Result<()> {
     let path = args.next().expect("./miri run-dep must be followed by a file name");
     let mut config = test_config(&target, "", Mode::Yolo, /* with dependencies */ true);
@@ -0,0 +1,35 @@
+// The `cov` and `calls` comments are checked against the coverage report by `check_coverage` in
+// `tests/compiletest.rs`: they state how often their line was executed, and how often the
+// function starting on the next line was called.
+
+// calls: 1
+fn main() {
+    let mut sum = 10u32; // cov: 1
+    for i in 0..4u32 {
+        if i % 2 == 0 {
+            sum += double(i); // cov: 2
+        } else {
+            sum -= 1; // cov: 2
+        }
+    }
+    // calls: 3
+    let triple = |x: u32| {
+        x * 3 // cov: 3
+    };
+    for x in [1, 2, 3] {
+        sum += triple(x);
+    }
+    if sum == 0 {
+        never_called(); // cov: 0
+    }
+}
+
+// calls: 2
+fn double(x: u32) -> u32 {
+    x * 2 // cov: 2
+}
+
+// calls: 0
+fn never_called() {
+    println!("unreachable"); // cov: 0
+}
@@ -0,0 +1,21 @@
+//@compile-flags: -Zmiri-explore-schedules=1
+//@normalize-stderr-test: "schedule [0-9]+" -> "schedule N"
+// Incrementing with a separate load and store loses an update when the main thread is preempted
+// between them, which only one of the explored schedules does.
+
+use std::sync::atomic::{AtomicU32, Ordering::SeqCst};
+use std::thread;
+
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+fn increment() {
+    let value = COUNTER.load(SeqCst);
+    COUNTER.store(value + 1, SeqCst);
+}
+
+fn main() {
+    let handle = thread::spawn(increment);
+    increment();
+    handle.join().unwrap();
+    assert!(COUNTER.load(SeqCst) == 2, "lost an update");
+}
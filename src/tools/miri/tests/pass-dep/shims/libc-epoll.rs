@@ -0,0 +1,258 @@
+//@only-target-linux
+
+use std::thread;
+
+fn main() {
+    test_eventfd();
+    test_socketpair();
+    test_pipe();
+    test_epoll_level_triggered();
+    test_epoll_edge_triggered();
+    test_epoll_oneshot();
+    test_epoll_hangup();
+    test_epoll_ctl_errors();
+    test_epoll_wait_timeout();
+    test_epoll_wait_blocking();
+}
+
+fn read_u64(fd: i32) -> Result<u64, i32> {
+    let mut buf = [0u8; 8];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), 8) };
+    if res < 0 {
+        return Err(std::io::Error::last_os_error().raw_os_error().unwrap());
+    }
+    assert_eq!(res, 8);
+    Ok(u64::from_ne_bytes(buf))
+}
+
+fn write_u64(fd: i32, val: u64) -> Result<(), i32> {
+    let buf = val.to_ne_bytes();
+    let res = unsafe { libc::write(fd, buf.as_ptr().cast(), 8) };
+    if res < 0 {
+        return Err(std::io::Error::last_os_error().raw_os_error().unwrap());
+    }
+    assert_eq!(res, 8);
+    Ok(())
+}
+
+fn write_all(fd: i32, data: &[u8]) {
+    let res = unsafe { libc::write(fd, data.as_ptr().cast(), data.len()) };
+    assert_eq!(res, data.len() as isize);
+}
+
+fn read_to_vec(fd: i32, len: usize) -> Vec<u8> {
+    let mut buf = vec![0u8; len];
+    let res = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), len) };
+    assert!(res >= 0);
+    buf.truncate(res as usize);
+    buf
+}
+
+fn epoll_add(epfd: i32, fd: i32, events: u32, data: u64) {
+    let mut event = libc::epoll_event { events, u64: data };
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) };
+    assert_eq!(res, 0);
+}
+
+/// Waits for events for up to `timeout` milliseconds, and returns the reported `(events, data)`
+/// pairs.
+fn epoll_poll(epfd: i32, timeout: i32) -> Vec<(u32, u64)> {
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 8];
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 8, timeout) };
+    assert!(res >= 0);
+    events[..res as usize].iter().map(|event| (event.events, event.u64)).collect()
+}
+
+fn test_eventfd() {
+    let fd = unsafe { libc::eventfd(3, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+    assert!(fd >= 0);
+    // Writes add to the counter, reads return it and reset it.
+    write_u64(fd, 4).unwrap();
+    assert_eq!(read_u64(fd), Ok(7));
+    assert_eq!(read_u64(fd), Err(libc::EAGAIN));
+    // The counter cannot exceed `u64::MAX - 1`.
+    assert_eq!(write_u64(fd, u64::MAX), Err(libc::EINVAL));
+    write_u64(fd, u64::MAX - 1).unwrap();
+    assert_eq!(write_u64(fd, 1), Err(libc::EAGAIN));
+    assert_eq!(read_u64(fd), Ok(u64::MAX - 1));
+
+    // Duplicates share the counter.
+    let dup = unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) };
+    write_u64(dup, 1).unwrap();
+    assert_eq!(read_u64(fd), Ok(1));
+    assert_eq!(unsafe { libc::close(dup) }, 0);
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+}
+
+fn test_socketpair() {
+    let mut fds = [-1, -1];
+    let res = unsafe {
+        libc::socketpair(
+            libc::AF_UNIX,
+            libc::SOCK_STREAM | libc::SOCK_NONBLOCK,
+            0,
+            fds.as_mut_ptr(),
+        )
+    };
+    assert_eq!(res, 0);
+    // Data flows in both directions.
+    write_all(fds[0], b"abcde");
+    write_all(fds[1], b"12");
+    assert_eq!(read_to_vec(fds[1], 3), b"abc");
+    assert_eq!(read_to_vec(fds[1], 8), b"de");
+    assert_eq!(read_to_vec(fds[0], 8), b"12");
+    let mut buf = [0u8; 4];
+    let res = unsafe { libc::read(fds[0], buf.as_mut_ptr().cast(), 4) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+    // Once the peer is closed, reads see the end of the stream.
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+    assert_eq!(read_to_vec(fds[0], 8), b"");
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+}
+
+fn test_pipe() {
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) }, 0);
+    let [reader, writer] = fds;
+    write_all(writer, b"hello");
+    assert_eq!(read_to_vec(reader, 16), b"hello");
+    // Writing to a pipe without readers fails with `EPIPE`.
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    let res = unsafe { libc::write(writer, b"x".as_ptr().cast(), 1) };
+    assert_eq!(res, -1);
+    assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EPIPE));
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+}
+
+fn test_epoll_level_triggered() {
+    let epfd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+    assert!(epfd >= 0);
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, (libc::EPOLLIN | libc::EPOLLOUT) as u32, 42);
+
+    // The counter is zero, so the eventfd is only writable.
+    assert_eq!(epoll_poll(epfd, 0), [(libc::EPOLLOUT as u32, 42)]);
+    write_u64(fd, 1).unwrap();
+    let both = (libc::EPOLLIN | libc::EPOLLOUT) as u32;
+    // Level-triggered events are reported for as long as the file descriptor is ready.
+    assert_eq!(epoll_poll(epfd, 0), [(both, 42)]);
+    assert_eq!(epoll_poll(epfd, 0), [(both, 42)]);
+    read_u64(fd).unwrap();
+    assert_eq!(epoll_poll(epfd, 0), [(libc::EPOLLOUT as u32, 42)]);
+
+    // Closing the file descriptor removes it from the interest list.
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert!(epoll_poll(epfd, 0).is_empty());
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_edge_triggered() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) }, 0);
+    let [reader, writer] = fds;
+    epoll_add(epfd, reader, (libc::EPOLLIN | libc::EPOLLET) as u32, 1);
+
+    assert!(epoll_poll(epfd, 0).is_empty());
+    write_all(writer, b"abc");
+    // Edge-triggered events are only reported once per change...
+    assert_eq!(epoll_poll(epfd, 0), [(libc::EPOLLIN as u32, 1)]);
+    assert!(epoll_poll(epfd, 0).is_empty());
+    // ...even if the data wasn't read entirely.
+    assert_eq!(read_to_vec(reader, 1), b"a");
+    assert!(epoll_poll(epfd, 0).is_empty());
+    write_all(writer, b"d");
+    assert_eq!(epoll_poll(epfd, 0), [(libc::EPOLLIN as u32, 1)]);
+    assert!(epoll_poll(epfd, 0).is_empty());
+
+    assert_eq!(unsafe { libc::close(reader) }, 0);
+    assert_eq!(unsafe { libc::close(writer) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_oneshot() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(1, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, (libc::EPOLLIN | libc::EPOLLONESHOT) as u32, 7);
+
+    assert_eq!(epoll_poll(epfd, 0), [(libc::EPOLLIN as u32, 7)]);
+    // The event is disabled until it is modified.
+    assert!(epoll_poll(epfd, 0).is_empty());
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 8 };
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_MOD, fd, &mut event) };
+    assert_eq!(res, 0);
+    assert_eq!(epoll_poll(epfd, 0), [(libc::EPOLLIN as u32, 8)]);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_hangup() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let mut fds = [-1, -1];
+    let res = unsafe { libc::socketpair(libc::AF_UNIX, libc::SOCK_STREAM, 0, fds.as_mut_ptr()) };
+    assert_eq!(res, 0);
+    epoll_add(epfd, fds[0], (libc::EPOLLIN | libc::EPOLLRDHUP) as u32, 0);
+
+    assert!(epoll_poll(epfd, 0).is_empty());
+    assert_eq!(unsafe { libc::close(fds[1]) }, 0);
+    // `EPOLLHUP` is reported even though it wasn't asked for.
+    let hangup = (libc::EPOLLIN | libc::EPOLLRDHUP | libc::EPOLLHUP) as u32;
+    assert_eq!(epoll_poll(epfd, 0), [(hangup, 0)]);
+
+    assert_eq!(unsafe { libc::close(fds[0]) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_ctl_errors() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(0, 0) };
+    let mut event = libc::epoll_event { events: libc::EPOLLIN as u32, u64: 0 };
+    let ctl = |op, fd, event: &mut libc::epoll_event| {
+        let res = unsafe { libc::epoll_ctl(epfd, op, fd, event) };
+        if res == 0 { Ok(()) } else { Err(std::io::Error::last_os_error().raw_os_error().unwrap()) }
+    };
+
+    assert_eq!(ctl(libc::EPOLL_CTL_MOD, fd, &mut event), Err(libc::ENOENT));
+    assert_eq!(ctl(libc::EPOLL_CTL_DEL, fd, &mut event), Err(libc::ENOENT));
+    assert_eq!(ctl(libc::EPOLL_CTL_ADD, fd, &mut event), Ok(()));
+    assert_eq!(ctl(libc::EPOLL_CTL_ADD, fd, &mut event), Err(libc::EEXIST));
+    assert_eq!(ctl(libc::EPOLL_CTL_DEL, fd, &mut event), Ok(()));
+    assert_eq!(ctl(libc::EPOLL_CTL_ADD, epfd, &mut event), Err(libc::EINVAL));
+    assert_eq!(ctl(libc::EPOLL_CTL_ADD, -1, &mut event), Err(libc::EBADF));
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_wait_timeout() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, libc::EPOLLIN as u32, 0);
+
+    let start = std::time::Instant::now();
+    assert!(epoll_poll(epfd, 100).is_empty());
+    assert!(start.elapsed().as_millis() >= 100);
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
+
+fn test_epoll_wait_blocking() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let fd = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK) };
+    epoll_add(epfd, fd, (libc::EPOLLIN | libc::EPOLLET) as u32, 5);
+
+    // The main thread blocks until the other thread writes to the eventfd.
+    let writer = thread::spawn(move || {
+        thread::yield_now();
+        write_u64(fd, 1).unwrap();
+    });
+    assert_eq!(epoll_poll(epfd, -1), [(libc::EPOLLIN as u32, 5)]);
+    assert_eq!(read_u64(fd), Ok(1));
+    writer.join().unwrap();
+
+    assert_eq!(unsafe { libc::close(fd) }, 0);
+    assert_eq!(unsafe { libc::close(epfd) }, 0);
+}
@@ -0,0 +1,290 @@
+//@only-target-linux
+
+use std::mem;
+use std::thread;
+
+fn main() {
+    test_socket_errors();
+    test_bind_errors();
+    test_accept_nonblock();
+    test_epoll_tcp();
+    test_epoll_udp();
+    test_blocking_accept();
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn sockaddr_in(port: u16) -> libc::sockaddr_in {
+    libc::sockaddr_in {
+        sin_family: libc::AF_INET as libc::sa_family_t,
+        sin_port: port.to_be(),
+        sin_addr: libc::in_addr { s_addr: u32::from_be_bytes([127, 0, 0, 1]).to_be() },
+        sin_zero: [0; 8],
+    }
+}
+
+fn bind(fd: i32, port: u16) -> i32 {
+    let addr = sockaddr_in(port);
+    unsafe {
+        libc::bind(
+            fd,
+            (&addr as *const libc::sockaddr_in).cast(),
+            mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+        )
+    }
+}
+
+fn connect(fd: i32, port: u16) -> i32 {
+    let addr = sockaddr_in(port);
+    unsafe {
+        libc::connect(
+            fd,
+            (&addr as *const libc::sockaddr_in).cast(),
+            mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+        )
+    }
+}
+
+fn local_port(fd: i32) -> u16 {
+    let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let res =
+        unsafe { libc::getsockname(fd, (&mut addr as *mut libc::sockaddr_in).cast(), &mut len) };
+    assert_eq!(res, 0);
+    assert_eq!(len as usize, mem::size_of::<libc::sockaddr_in>());
+    assert_eq!(addr.sin_family, libc::AF_INET as libc::sa_family_t);
+    u16::from_be(addr.sin_port)
+}
+
+fn tcp_socket(flags: i32) -> i32 {
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_STREAM | flags, 0) };
+    assert!(fd >= 0);
+    fd
+}
+
+/// Creates a listening socket, and returns it together with its port.
+fn tcp_listener(flags: i32) -> (i32, u16) {
+    let fd = tcp_socket(flags);
+    assert_eq!(bind(fd, 0), 0);
+    assert_eq!(unsafe { libc::listen(fd, 16) }, 0);
+    (fd, local_port(fd))
+}
+
+fn epoll_add(epfd: i32, fd: i32, events: u32, data: u64) {
+    let mut event = libc::epoll_event { events, u64: data };
+    let res = unsafe { libc::epoll_ctl(epfd, libc::EPOLL_CTL_ADD, fd, &mut event) };
+    assert_eq!(res, 0);
+}
+
+fn epoll_poll(epfd: i32) -> Vec<(u32, u64)> {
+    let mut events = [libc::epoll_event { events: 0, u64: 0 }; 8];
+    let res = unsafe { libc::epoll_wait(epfd, events.as_mut_ptr(), 8, 0) };
+    assert!(res >= 0);
+    events[..res as usize].iter().map(|event| (event.events, event.u64)).collect()
+}
+
+fn test_socket_errors() {
+    let mut buf = [0u8; 4];
+    // Socket functions on files that are not sockets.
+    let fd = unsafe { libc::eventfd(0, 0) };
+    let res = unsafe { libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), 0) };
+    assert_eq!((res, errno()), (-1, libc::ENOTSOCK));
+    unsafe { libc::close(fd) };
+    let res = unsafe { libc::recv(fd, buf.as_mut_ptr().cast(), buf.len(), 0) };
+    assert_eq!((res, errno()), (-1, libc::EBADF));
+
+    // Sockets that are not connected.
+    let fd = tcp_socket(0);
+    let res = unsafe { libc::send(fd, buf.as_ptr().cast(), buf.len(), 0) };
+    assert_eq!((res, errno()), (-1, libc::ENOTCONN));
+    let res = unsafe { libc::shutdown(fd, libc::SHUT_RDWR) };
+    assert_eq!((res, errno()), (-1, libc::ENOTCONN));
+    let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let res =
+        unsafe { libc::getpeername(fd, (&mut addr as *mut libc::sockaddr_in).cast(), &mut len) };
+    assert_eq!((res, errno()), (-1, libc::ENOTCONN));
+
+    // Connecting twice.
+    let (listener, port) = tcp_listener(0);
+    assert_eq!(connect(fd, port), 0);
+    assert_eq!((connect(fd, port), errno()), (-1, libc::EISCONN));
+
+    // Listening on a UDP socket.
+    let udp = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    assert_eq!((unsafe { libc::listen(udp, 1) }, errno()), (-1, libc::EOPNOTSUPP));
+
+    unsafe {
+        libc::close(fd);
+        libc::close(listener);
+        libc::close(udp);
+    }
+}
+
+fn test_bind_errors() {
+    let fd = tcp_socket(0);
+    // The address is too short.
+    let addr = sockaddr_in(0);
+    let res = unsafe { libc::bind(fd, (&addr as *const libc::sockaddr_in).cast(), 4) };
+    assert_eq!((res, errno()), (-1, libc::EINVAL));
+    // Only loopback addresses are available.
+    let mut addr = sockaddr_in(0);
+    addr.sin_addr.s_addr = u32::from_be_bytes([192, 168, 0, 1]).to_be();
+    let res = unsafe {
+        libc::bind(
+            fd,
+            (&addr as *const libc::sockaddr_in).cast(),
+            mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+        )
+    };
+    assert_eq!((res, errno()), (-1, libc::EADDRNOTAVAIL));
+    // Sockets can only be bound once.
+    assert_eq!(bind(fd, 0), 0);
+    assert_eq!((bind(fd, 0), errno()), (-1, libc::EINVAL));
+    unsafe { libc::close(fd) };
+}
+
+fn test_accept_nonblock() {
+    let (listener, port) = tcp_listener(libc::SOCK_NONBLOCK);
+    let res = unsafe { libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut()) };
+    assert_eq!((res, errno()), (-1, libc::EAGAIN));
+
+    let client = tcp_socket(0);
+    assert_eq!(connect(client, port), 0);
+    let server = unsafe {
+        libc::accept4(listener, std::ptr::null_mut(), std::ptr::null_mut(), libc::SOCK_NONBLOCK)
+    };
+    assert!(server >= 0);
+    // The accepted socket has its own flags.
+    let mut buf = [0u8; 4];
+    let res = unsafe { libc::recv(server, buf.as_mut_ptr().cast(), buf.len(), 0) };
+    assert_eq!((res, errno()), (-1, libc::EAGAIN));
+    // `MSG_DONTWAIT` makes a single call non-blocking.
+    let res = unsafe { libc::recv(client, buf.as_mut_ptr().cast(), buf.len(), libc::MSG_DONTWAIT) };
+    assert_eq!((res, errno()), (-1, libc::EAGAIN));
+
+    unsafe {
+        libc::close(client);
+        libc::close(server);
+        libc::close(listener);
+    }
+}
+
+fn test_epoll_tcp() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let (listener, port) = tcp_listener(libc::SOCK_NONBLOCK);
+    epoll_add(epfd, listener, libc::EPOLLIN as u32, 1);
+    assert_eq!(epoll_poll(epfd), vec![]);
+
+    let client = tcp_socket(libc::SOCK_NONBLOCK);
+    assert_eq!(connect(client, port), 0);
+    assert_eq!(epoll_poll(epfd), vec![(libc::EPOLLIN as u32, 1)]);
+
+    let server = unsafe { libc::accept(listener, std::ptr::null_mut(), std::ptr::null_mut()) };
+    assert!(server >= 0);
+    assert_eq!(epoll_poll(epfd), vec![]);
+
+    let events = (libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLRDHUP) as u32;
+    epoll_add(epfd, server, events, 2);
+    assert_eq!(epoll_poll(epfd), vec![(libc::EPOLLOUT as u32, 2)]);
+    let res = unsafe { libc::send(client, b"abc".as_ptr().cast(), 3, 0) };
+    assert_eq!(res, 3);
+    assert_eq!(epoll_poll(epfd), vec![((libc::EPOLLIN | libc::EPOLLOUT) as u32, 2)]);
+    unsafe { libc::close(client) };
+    let hangup = (libc::EPOLLIN | libc::EPOLLOUT | libc::EPOLLRDHUP | libc::EPOLLHUP) as u32;
+    assert_eq!(epoll_poll(epfd), vec![(hangup, 2)]);
+
+    unsafe {
+        libc::close(server);
+        libc::close(listener);
+        libc::close(epfd);
+    }
+}
+
+fn test_epoll_udp() {
+    let epfd = unsafe { libc::epoll_create1(0) };
+    let a = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_NONBLOCK, 0) };
+    let b = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_NONBLOCK, 0) };
+    assert_eq!(bind(a, 0), 0);
+    epoll_add(epfd, a, (libc::EPOLLIN | libc::EPOLLET) as u32, 1);
+    assert_eq!(epoll_poll(epfd), vec![]);
+
+    // Sending implicitly binds `b`.
+    let addr = sockaddr_in(local_port(a));
+    let res = unsafe {
+        libc::sendto(
+            b,
+            b"xy".as_ptr().cast(),
+            2,
+            0,
+            (&addr as *const libc::sockaddr_in).cast(),
+            mem::size_of::<libc::sockaddr_in>() as libc::socklen_t,
+        )
+    };
+    assert_eq!(res, 2);
+    assert_ne!(local_port(b), 0);
+    assert_eq!(epoll_poll(epfd), vec![(libc::EPOLLIN as u32, 1)]);
+    assert_eq!(epoll_poll(epfd), vec![]);
+
+    let mut buf = [0u8; 4];
+    let mut from: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let res = unsafe {
+        libc::recvfrom(
+            a,
+            buf.as_mut_ptr().cast(),
+            buf.len(),
+            0,
+            (&mut from as *mut libc::sockaddr_in).cast(),
+            &mut len,
+        )
+    };
+    assert_eq!(res, 2);
+    assert_eq!(u16::from_be(from.sin_port), local_port(b));
+
+    unsafe {
+        libc::close(a);
+        libc::close(b);
+        libc::close(epfd);
+    }
+}
+
+fn test_blocking_accept() {
+    let (listener, port) = tcp_listener(0);
+    let handle = thread::spawn(move || {
+        let client = tcp_socket(0);
+        assert_eq!(connect(client, port), 0);
+        let res = unsafe { libc::send(client, b"hello".as_ptr().cast(), 5, 0) };
+        assert_eq!(res, 5);
+        unsafe { libc::close(client) };
+    });
+
+    let mut addr: libc::sockaddr_in = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_in>() as libc::socklen_t;
+    let server =
+        unsafe { libc::accept(listener, (&mut addr as *mut libc::sockaddr_in).cast(), &mut len) };
+    assert!(server >= 0);
+    assert_eq!(addr.sin_addr.s_addr, u32::from_be_bytes([127, 0, 0, 1]).to_be());
+
+    let mut buf = [0u8; 8];
+    let mut received = 0;
+    loop {
+        let res = unsafe {
+            libc::recv(server, buf[received..].as_mut_ptr().cast(), buf.len() - received, 0)
+        };
+        assert!(res >= 0);
+        if res == 0 {
+            break;
+        }
+        received += res as usize;
+    }
+    assert_eq!(&buf[..received], b"hello");
+    handle.join().unwrap();
+
+    unsafe {
+        libc::close(server);
+        libc::close(listener);
+    }
+}
@@ -0,0 +1,111 @@
+//@only-target-linux
+//@compile-flags: -Zmiri-emulate-processes
+
+use std::env;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStringExt;
+use std::ptr;
+
+fn main() {
+    if env::args().nth(1).as_deref() == Some("child") {
+        // Write to the pipe the parent set up as file descriptor 3.
+        let res = unsafe { libc::write(3, b"ok".as_ptr().cast(), 2) };
+        assert_eq!(res, 2);
+        std::process::exit(7);
+    }
+
+    test_no_children();
+    test_poll_pipe();
+    test_spawn_wait();
+}
+
+fn errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn pipe() -> [i32; 2] {
+    let mut fds = [-1; 2];
+    assert_eq!(unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) }, 0);
+    fds
+}
+
+fn poll(fds: &mut [libc::pollfd]) -> i32 {
+    unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, 0) }
+}
+
+fn test_no_children() {
+    let mut status = 0;
+    let res = unsafe { libc::waitpid(-1, &mut status, 0) };
+    assert_eq!((res, errno()), (-1, libc::ECHILD));
+    let res = unsafe { libc::kill(12345, libc::SIGKILL) };
+    assert_eq!((res, errno()), (-1, libc::ESRCH));
+}
+
+fn test_poll_pipe() {
+    let [read, write] = pipe();
+    let mut fds = [
+        libc::pollfd { fd: read, events: libc::POLLIN, revents: 0 },
+        libc::pollfd { fd: write, events: libc::POLLOUT, revents: 0 },
+        // Negative file descriptors are ignored.
+        libc::pollfd { fd: -1, events: libc::POLLIN, revents: 0 },
+    ];
+    assert_eq!(poll(&mut fds), 1);
+    assert_eq!(fds.map(|fd| fd.revents), [0, libc::POLLOUT, 0]);
+
+    assert_eq!(unsafe { libc::write(write, b"x".as_ptr().cast(), 1) }, 1);
+    assert_eq!(poll(&mut fds), 2);
+    assert_eq!(fds.map(|fd| fd.revents), [libc::POLLIN, libc::POLLOUT, 0]);
+
+    // Closing the writing end hangs up the pipe.
+    unsafe { libc::close(write) };
+    assert_eq!(poll(&mut fds[..2]), 2);
+    assert_eq!(fds[0].revents, libc::POLLIN | libc::POLLHUP);
+    assert_eq!(fds[1].revents, libc::POLLNVAL);
+    unsafe { libc::close(read) };
+}
+
+fn test_spawn_wait() {
+    let [read, write] = pipe();
+    let exe = CString::new(env::current_exe().unwrap().into_os_string().into_vec()).unwrap();
+    let arg = CString::new("child").unwrap();
+    let argv = [exe.as_ptr().cast_mut(), arg.as_ptr().cast_mut(), ptr::null_mut()];
+    let envp = [ptr::null_mut()];
+
+    let mut pid = 0;
+    unsafe {
+        let mut actions = std::mem::zeroed();
+        assert_eq!(libc::posix_spawn_file_actions_init(&mut actions), 0);
+        assert_eq!(libc::posix_spawn_file_actions_adddup2(&mut actions, write, 3), 0);
+        let res = libc::posix_spawn(
+            &mut pid,
+            exe.as_ptr(),
+            &actions,
+            ptr::null(),
+            argv.as_ptr(),
+            envp.as_ptr(),
+        );
+        assert_eq!(res, 0);
+        assert_eq!(libc::posix_spawn_file_actions_destroy(&mut actions), 0);
+    }
+    unsafe { libc::close(write) };
+
+    // Signal 0 only checks whether the process exists.
+    assert_eq!(unsafe { libc::kill(pid, 0) }, 0);
+    // The child did not get to run yet.
+    let mut status = 0;
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, libc::WNOHANG) }, 0);
+
+    let mut buf = [0u8; 4];
+    assert_eq!(unsafe { libc::read(read, buf.as_mut_ptr().cast(), buf.len()) }, 2);
+    assert_eq!(&buf[..2], b"ok");
+    // The child exited, and closed its end of the pipe.
+    assert_eq!(unsafe { libc::read(read, buf.as_mut_ptr().cast(), buf.len()) }, 0);
+
+    assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+    assert!(libc::WIFEXITED(status));
+    assert_eq!(libc::WEXITSTATUS(status), 7);
+    // A child can only be waited for once.
+    let res = unsafe { libc::waitpid(pid, &mut status, 0) };
+    assert_eq!((res, errno()), (-1, libc::ECHILD));
+    unsafe { libc::close(read) };
+}
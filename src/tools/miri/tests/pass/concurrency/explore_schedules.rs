@@ -0,0 +1,24 @@
+//@compile-flags: -Zmiri-explore-schedules=1
+//@normalize-stderr-test: "all [0-9]+ schedules" -> "all N schedules"
+
+use std::sync::atomic::{AtomicU32, Ordering::SeqCst};
+use std::sync::Mutex;
+use std::thread;
+
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+static LOCKED: Mutex<u32> = Mutex::new(0);
+
+fn increment() {
+    COUNTER.fetch_add(1, SeqCst);
+    let mut locked = LOCKED.lock().unwrap();
+    let value = *locked;
+    *locked = value + 1;
+}
+
+fn main() {
+    let handle = thread::spawn(increment);
+    increment();
+    handle.join().unwrap();
+    assert_eq!(COUNTER.load(SeqCst), 2);
+    assert_eq!(*LOCKED.lock().unwrap(), 2);
+}
@@ -0,0 +1,15 @@
+//@compile-flags: -Zmiri-explore-schedules=1
+//@normalize-stderr-test: "all [0-9]+ schedules" -> "all N schedules"
+
+// Without preemptions, the main thread would spin forever waiting for the other thread.
+
+use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+use std::thread;
+
+static FLAG: AtomicBool = AtomicBool::new(false);
+
+fn main() {
+    let handle = thread::spawn(|| FLAG.store(true, SeqCst));
+    while !FLAG.load(SeqCst) {}
+    handle.join().unwrap();
+}
@@ -0,0 +1,176 @@
+//@ignore-target-windows: No libc sockets on Windows
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, Shutdown, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::thread;
+
+fn main() {
+    test_tcp_echo();
+    test_tcp_addrs();
+    test_tcp_shutdown();
+    test_tcp_nonblocking();
+    test_tcp_connection_refused();
+    test_tcp_ipv6();
+    test_udp();
+    test_udp_connected();
+}
+
+fn test_tcp_echo() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        for _ in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = Vec::new();
+            stream.read_to_end(&mut buf).unwrap();
+            stream.write_all(&buf).unwrap();
+        }
+    });
+
+    for msg in [&b"hello"[..], &b"world"[..]] {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.set_nodelay(true).unwrap();
+        assert!(stream.nodelay().unwrap());
+        stream.write_all(msg).unwrap();
+        stream.shutdown(Shutdown::Write).unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, msg);
+    }
+    server.join().unwrap();
+}
+
+fn test_tcp_addrs() {
+    let listener = TcpListener::bind("0.0.0.0:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    assert_ne!(port, 0);
+    // Ports cannot be bound twice.
+    let err = TcpListener::bind(("127.0.0.1", port)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AddrInUse);
+
+    let client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let (server, peer) = listener.accept().unwrap();
+    assert_eq!(peer, client.local_addr().unwrap());
+    assert_eq!(client.peer_addr().unwrap(), server.local_addr().unwrap());
+    assert_eq!(server.local_addr().unwrap(), SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+    assert_eq!(server.peer_addr().unwrap(), client.local_addr().unwrap());
+
+    // Closing the listener frees the port.
+    drop(listener);
+    TcpListener::bind(("127.0.0.1", port)).unwrap();
+}
+
+fn test_tcp_shutdown() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    client.write_all(b"abc").unwrap();
+    let mut buf = [0; 8];
+    assert_eq!(server.peek(&mut buf).unwrap(), 3);
+    assert_eq!(server.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"abc");
+
+    // Writing after shutting down the writing half fails, and the peer sees the end of the
+    // stream.
+    client.shutdown(Shutdown::Write).unwrap();
+    assert_eq!(client.write(b"x").unwrap_err().kind(), ErrorKind::BrokenPipe);
+    assert_eq!(server.read(&mut buf).unwrap(), 0);
+
+    // Writing to a closed peer fails.
+    drop(client);
+    assert_eq!(server.write(b"x").unwrap_err().kind(), ErrorKind::BrokenPipe);
+}
+
+fn test_tcp_nonblocking() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.set_nonblocking(true).unwrap();
+    assert_eq!(listener.accept().unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+    server.set_nonblocking(true).unwrap();
+    let mut buf = [0; 8];
+    assert_eq!(server.read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+    client.write_all(b"abc").unwrap();
+    assert_eq!(server.read(&mut buf).unwrap(), 3);
+}
+
+fn test_tcp_connection_refused() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let err = TcpStream::connect(addr).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ConnectionRefused);
+}
+
+fn test_tcp_ipv6() {
+    let listener = TcpListener::bind("[::1]:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    assert!(addr.is_ipv6());
+
+    let handle = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"over ipv6").unwrap();
+    });
+    let (mut stream, peer) = listener.accept().unwrap();
+    assert!(peer.ip().is_loopback());
+    let mut buf = String::new();
+    stream.read_to_string(&mut buf).unwrap();
+    assert_eq!(buf, "over ipv6");
+    handle.join().unwrap();
+}
+
+fn test_udp() {
+    let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let a_addr = a.local_addr().unwrap();
+    let b_addr = b.local_addr().unwrap();
+
+    assert_eq!(a.send_to(b"ping", b_addr).unwrap(), 4);
+    let mut buf = [0; 16];
+    let (len, from) = b.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..len], b"ping");
+    assert_eq!(from, a_addr);
+
+    // Datagrams are not merged, and are truncated if the buffer is too small.
+    b.send_to(b"pong", a_addr).unwrap();
+    b.send_to(b"!", a_addr).unwrap();
+    let mut small = [0; 2];
+    assert_eq!(a.recv_from(&mut small).unwrap(), (2, b_addr));
+    assert_eq!(&small, b"po");
+    assert_eq!(a.recv_from(&mut buf).unwrap(), (1, b_addr));
+
+    // A blocking receive waits for another thread to send something.
+    let handle = thread::spawn(move || {
+        let mut buf = [0; 16];
+        let (len, from) = b.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"late");
+        assert_eq!(from, a_addr);
+    });
+    thread::yield_now();
+    a.send_to(b"late", b_addr).unwrap();
+    handle.join().unwrap();
+
+    a.set_nonblocking(true).unwrap();
+    assert_eq!(a.recv_from(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
+
+fn test_udp_connected() {
+    let a = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let b = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let c = UdpSocket::bind("127.0.0.1:0").unwrap();
+    a.connect(b.local_addr().unwrap()).unwrap();
+    assert_eq!(a.peer_addr().unwrap(), b.local_addr().unwrap());
+
+    // A connected socket only receives datagrams from its peer.
+    c.send_to(b"ignored", a.local_addr().unwrap()).unwrap();
+    b.send_to(b"hi", a.local_addr().unwrap()).unwrap();
+    let mut buf = [0; 16];
+    assert_eq!(a.recv(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], b"hi");
+
+    a.send(b"back").unwrap();
+    assert_eq!(b.recv_from(&mut buf).unwrap(), (4, a.local_addr().unwrap()));
+}
@@ -0,0 +1,106 @@
+//@only-target-linux: processes are only emulated on Linux
+//@compile-flags: -Zmiri-emulate-processes
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{self, Command, Stdio};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => {}
+        Some(mode) => return child(mode, &args[1..]),
+    }
+
+    test_output();
+    test_stdin();
+    test_exit_code();
+    test_env();
+    test_pid();
+    test_kill();
+    test_panic();
+}
+
+/// What the spawned processes do, depending on their first argument.
+fn child(mode: &str, args: &[String]) {
+    match mode {
+        "hello" => {
+            println!("hello {}", args.join(" "));
+            eprintln!("to stderr");
+        }
+        "cat" => {
+            let mut input = Vec::new();
+            io::stdin().read_to_end(&mut input).unwrap();
+            io::stdout().write_all(&input).unwrap();
+        }
+        "exit" => process::exit(args[0].parse().unwrap()),
+        "env" => print!("{}", env::var("MIRI_TEST_VAR").unwrap()),
+        "pid" => print!("{}", process::id()),
+        "block" => {
+            // Blocks until the parent kills this process.
+            io::stdin().read_to_end(&mut Vec::new()).unwrap();
+            unreachable!();
+        }
+        "panic" => panic!("the child panicked"),
+        _ => unreachable!(),
+    }
+}
+
+fn command(mode: &str) -> Command {
+    let mut command = Command::new(env::current_exe().unwrap());
+    command.arg(mode);
+    command
+}
+
+fn test_output() {
+    let output = command("hello").arg("world").output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"hello world\n");
+    assert_eq!(output.stderr, b"to stderr\n");
+}
+
+fn test_stdin() {
+    let mut child = command("cat").stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    // Dropping the pipe closes it, so that the child sees the end of its input.
+    child.stdin.take().unwrap().write_all(b"piped through").unwrap();
+    let mut output = String::new();
+    child.stdout.take().unwrap().read_to_string(&mut output).unwrap();
+    assert_eq!(output, "piped through");
+    assert!(child.wait().unwrap().success());
+}
+
+fn test_exit_code() {
+    let status = command("exit").arg("42").status().unwrap();
+    assert_eq!(status.code(), Some(42));
+    assert!(!status.success());
+}
+
+fn test_env() {
+    let output = command("env").env("MIRI_TEST_VAR", "forwarded").output().unwrap();
+    assert_eq!(output.stdout, b"forwarded");
+}
+
+fn test_pid() {
+    let child = command("pid").stdout(Stdio::piped()).spawn().unwrap();
+    let pid = child.id();
+    assert_ne!(pid, process::id());
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), pid.to_string());
+}
+
+fn test_kill() {
+    let mut child = command("block").stdin(Stdio::piped()).spawn().unwrap();
+    assert!(child.try_wait().unwrap().is_none());
+    child.kill().unwrap();
+    let status = child.wait().unwrap();
+    assert_eq!(status.code(), None);
+    assert_eq!(status.signal(), Some(9)); // `SIGKILL`
+}
+
+fn test_panic() {
+    let output = command("panic").output().unwrap();
+    assert_eq!(output.status.code(), Some(101));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("the child panicked"));
+}
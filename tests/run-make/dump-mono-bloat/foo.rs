@@ -0,0 +1,11 @@
+pub fn generic<T: Default>() -> T {
+    T::default()
+}
+
+fn helper<T: Default>() -> T {
+    generic()
+}
+
+pub fn caller() -> u32 {
+    helper::<u32>()
+}
@@ -0,0 +1,11 @@
+pub mod changed {
+    pub fn answer() -> u32 {
+        43
+    }
+}
+
+pub mod unchanged {
+    pub fn double(x: u32) -> u32 {
+        x * 2
+    }
+}
@@ -0,0 +1,5 @@
+extern crate dep;
+
+fn main() {
+    assert_eq!(dep::answer(), 42);
+}
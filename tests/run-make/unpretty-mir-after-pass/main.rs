@@ -0,0 +1,10 @@
+mod hot {
+    pub fn square(x: u32) -> u32 {
+        let y = x;
+        y * y
+    }
+}
+
+fn main() {
+    hot::square(3);
+}
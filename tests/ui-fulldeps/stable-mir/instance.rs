@@ -0,0 +1,156 @@
+// run-pass
+// Test that users are able to use stable mir APIs to resolve and monomorphize instances
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_smir;
+
+use rustc_driver::{Callbacks, Compilation, RunCompiler};
+use rustc_hir::def::DefKind;
+use rustc_hir::Mutability;
+use rustc_interface::{interface, Queries};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::EarlyErrorHandler;
+use rustc_smir::{rustc_internal, stable_mir};
+use stable_mir::mir::mono::{Instance, InstanceKind, MonoItem};
+use stable_mir::mir::Operand;
+use stable_mir::ty::{RigidTy, TyKind, UintTy};
+use std::assert_matches::assert_matches;
+use std::io::Write;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to resolve calls into monomorphized instances.
+fn test_stable_mir(tcx: TyCtxt<'_>) {
+    let items = stable_mir::all_local_items();
+
+    // Generic items have no mono instance.
+    let generic = get_item(tcx, &items, (DefKind::Fn, "generic")).unwrap();
+    assert!(Instance::mono(generic).is_none());
+
+    // Constants and statics are not functions, so they have no instance either.
+    let constant = get_item(tcx, &items, (DefKind::Const, "CONSTANT")).unwrap();
+    assert!(Instance::mono(constant).is_none());
+    let stat = get_item(tcx, &items, (DefKind::Static(Mutability::Not), "STATIC")).unwrap();
+    assert!(Instance::mono(stat).is_none());
+
+    let caller = get_item(tcx, &items, (DefKind::Fn, "caller")).unwrap();
+    let caller = Instance::mono(caller).unwrap();
+    assert_eq!(caller.kind, InstanceKind::Item);
+    assert_eq!(caller.name(), "caller");
+
+    // The caller is code generated, together with the instantiation of `generic` it requests.
+    let mono_items = stable_mir::mono_items();
+    let fn_names: Vec<_> = mono_items
+        .iter()
+        .filter_map(|item| match item {
+            MonoItem::Fn(instance) => Some(instance.name()),
+            _ => None,
+        })
+        .collect();
+    assert!(fn_names.iter().any(|name| name == "caller"), "{fn_names:?}");
+    assert!(fn_names.iter().any(|name| name == "generic::<u32>"), "{fn_names:?}");
+
+    let body = caller.body().unwrap();
+    let stable_mir::mir::Terminator::Call { func: Operand::Constant(func), .. } =
+        &body.blocks[0].terminator
+    else {
+        panic!("{:?}", body.blocks[0].terminator)
+    };
+    let callee = Instance::resolve(func.ty).unwrap();
+    assert_eq!(callee.kind, InstanceKind::Item);
+    assert_eq!(callee.name(), "generic::<u32>");
+    assert_matches!(callee.ty().kind(), TyKind::RigidTy(RigidTy::FnDef(..)));
+
+    // The monomorphized body has its generic arguments substituted.
+    let callee_body = callee.body().unwrap();
+    assert_matches!(callee_body.locals[0].kind(), TyKind::RigidTy(RigidTy::Uint(UintTy::U32)));
+
+    // Drop glue is a shim generated by the compiler.
+    let drop_glue = Instance::resolve_drop_in_place(body.locals[1]);
+    assert_eq!(drop_glue.kind, InstanceKind::Shim);
+    assert!(drop_glue.body().is_some());
+}
+
+// Use internal API to find a function in a crate.
+fn get_item<'a>(
+    tcx: TyCtxt,
+    items: &'a stable_mir::CrateItems,
+    item: (DefKind, &str),
+) -> Option<&'a stable_mir::CrateItem> {
+    items.iter().find(|crate_item| {
+        let def_id = rustc_internal::item_def_id(crate_item);
+        tcx.def_kind(def_id) == item.0 && tcx.def_path_str(def_id) == item.1
+    })
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// It will invoke the compiler using a custom Callback implementation, which will
+/// invoke Stable MIR APIs after the compiler has finished its analysis.
+fn main() {
+    let path = "instance_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    rustc_driver::catch_fatal_errors(|| {
+        RunCompiler::new(&args, &mut SMirCalls {}).run().unwrap();
+    })
+    .unwrap();
+}
+
+struct SMirCalls {}
+
+impl Callbacks for SMirCalls {
+    /// Called after analysis. Return value instructs the compiler whether to
+    /// continue the compilation afterwards (defaults to `Compilation::Continue`)
+    fn after_analysis<'tcx>(
+        &mut self,
+        _handler: &EarlyErrorHandler,
+        _compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        queries.global_ctxt().unwrap().enter(|tcx| {
+            rustc_smir::rustc_internal::run(tcx, || test_stable_mir(tcx));
+        });
+        // No need to keep going.
+        Compilation::Stop
+    }
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    pub fn generic<T: Default>() -> T {{
+        T::default()
+    }}
+
+    pub fn caller() -> Vec<u32> {{
+        vec![generic::<u32>()]
+    }}
+
+    pub const CONSTANT: u32 = 1 + 2;
+
+    pub static STATIC: u32 = CONSTANT * 2;
+    "#
+    )?;
+    Ok(())
+}
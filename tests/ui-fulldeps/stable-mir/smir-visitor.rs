@@ -0,0 +1,171 @@
+// run-pass
+// Test that users are able to visit and build bodies using the stable mir APIs
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_smir;
+
+use rustc_driver::{Callbacks, Compilation, RunCompiler};
+use rustc_hir::def::DefKind;
+use rustc_interface::{interface, Queries};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::EarlyErrorHandler;
+use rustc_smir::{rustc_internal, stable_mir};
+use stable_mir::mir::builder::BodyBuilder;
+use stable_mir::mir::visit::{Location, MirVisitor, PlaceContext};
+use stable_mir::mir::{Local, Statement, Terminator};
+use stable_mir::ty::Ty;
+use std::assert_matches::assert_matches;
+use std::collections::HashSet;
+use std::io::Write;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR visitor and builder on the bodies of the test crate.
+fn test_stable_mir(tcx: TyCtxt<'_>) {
+    let items = stable_mir::all_local_items();
+
+    let foo_bar = get_item(tcx, &items, (DefKind::Fn, "foo_bar")).unwrap();
+    let body = foo_bar.body();
+
+    let mut visitor = Visitor::default();
+    visitor.visit_body(&body);
+    assert_eq!(visitor.locals, body.locals.len());
+    assert_eq!(visitor.calls, 3);
+    // Both arguments are read.
+    assert!(visitor.read_locals.contains(&1));
+    assert!(visitor.read_locals.contains(&2));
+    // The return place is written.
+    assert!(visitor.written_locals.contains(&0));
+
+    // Insert a new block before the first call, and a new local.
+    let mut builder = BodyBuilder::from_body(body.clone());
+    let new_local = builder.new_local(body.locals[0]);
+    assert_eq!(new_local, body.locals.len());
+    let location = Location { block: 0, statement_index: body.blocks[0].statements.len() };
+    let tail = builder.split_block(location);
+    assert_eq!(tail, body.blocks.len());
+    assert_matches!(builder.block(0).terminator, Terminator::Goto { target } if target == tail);
+    assert_matches!(builder.block(tail).terminator, Terminator::Call { .. });
+    builder.push_statement(0, Statement::StorageLive(new_local));
+    let new_body = builder.build();
+    assert_eq!(new_body.blocks.len(), body.blocks.len() + 1);
+    assert_eq!(new_body.locals.len(), body.locals.len() + 1);
+
+    let mut visitor = Visitor::default();
+    visitor.visit_body(&new_body);
+    assert_eq!(visitor.calls, 3);
+}
+
+#[derive(Default)]
+struct Visitor {
+    locals: usize,
+    calls: usize,
+    read_locals: HashSet<Local>,
+    written_locals: HashSet<Local>,
+}
+
+impl MirVisitor for Visitor {
+    fn visit_local_decl(&mut self, local: Local, ty: Ty) {
+        self.locals += 1;
+        self.super_local_decl(local, ty);
+    }
+
+    fn visit_terminator(&mut self, term: &Terminator, location: Location) {
+        if let Terminator::Call { .. } = term {
+            self.calls += 1;
+        }
+        self.super_terminator(term, location);
+    }
+
+    fn visit_local(&mut self, local: &Local, ptx: PlaceContext, _location: Location) {
+        if ptx.is_mut {
+            self.written_locals.insert(*local);
+        } else {
+            self.read_locals.insert(*local);
+        }
+    }
+}
+
+// Use internal API to find a function in a crate.
+fn get_item<'a>(
+    tcx: TyCtxt,
+    items: &'a stable_mir::CrateItems,
+    item: (DefKind, &str),
+) -> Option<&'a stable_mir::CrateItem> {
+    items.iter().find(|crate_item| {
+        let def_id = rustc_internal::item_def_id(crate_item);
+        tcx.def_kind(def_id) == item.0 && tcx.def_path_str(def_id) == item.1
+    })
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// It will invoke the compiler using a custom Callback implementation, which will
+/// invoke Stable MIR APIs after the compiler has finished its analysis.
+fn main() {
+    let path = "visitor_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    rustc_driver::catch_fatal_errors(|| {
+        RunCompiler::new(&args, &mut SMirCalls {}).run().unwrap();
+    })
+    .unwrap();
+}
+
+struct SMirCalls {}
+
+impl Callbacks for SMirCalls {
+    /// Called after analysis. Return value instructs the compiler whether to
+    /// continue the compilation afterwards (defaults to `Compilation::Continue`)
+    fn after_analysis<'tcx>(
+        &mut self,
+        _handler: &EarlyErrorHandler,
+        _compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        queries.global_ctxt().unwrap().enter(|tcx| {
+            rustc_smir::rustc_internal::run(tcx, || test_stable_mir(tcx));
+        });
+        // No need to keep going.
+        Compilation::Stop
+    }
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    mod foo {{
+        pub fn bar(i: i32) -> i64 {{
+            i as i64
+        }}
+    }}
+
+    pub fn foo_bar(x: i32, y: i32) -> i64 {{
+        let x_64 = foo::bar(x);
+        let y_64 = foo::bar(y);
+        x_64.wrapping_add(y_64)
+    }}
+    "#
+    )?;
+    Ok(())
+}
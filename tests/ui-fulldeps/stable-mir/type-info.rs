@@ -0,0 +1,178 @@
+// run-pass
+// Test that users are able to use stable mir APIs to retrieve type layouts, ADT definitions
+// and trait impls.
+
+// ignore-stage1
+// ignore-cross-compile
+// ignore-remote
+// edition: 2021
+
+#![feature(rustc_private)]
+#![feature(assert_matches)]
+
+extern crate rustc_driver;
+extern crate rustc_hir;
+extern crate rustc_interface;
+extern crate rustc_middle;
+extern crate rustc_session;
+extern crate rustc_smir;
+
+use rustc_driver::{Callbacks, Compilation, RunCompiler};
+use rustc_hir::def::DefKind;
+use rustc_interface::{interface, Queries};
+use rustc_middle::ty::TyCtxt;
+use rustc_session::EarlyErrorHandler;
+use rustc_smir::{rustc_internal, stable_mir};
+use stable_mir::ty::{
+    AdtKind, FieldsShape, GenericParamDefKind, RigidTy, TagEncoding, TyKind, VariantsShape,
+};
+use std::assert_matches::assert_matches;
+use std::io::Write;
+
+const CRATE_NAME: &str = "input";
+
+/// This function uses the Stable MIR APIs to get information about the test crate.
+fn test_stable_mir(tcx: TyCtxt<'_>) {
+    let items = stable_mir::all_local_items();
+
+    // `fn pair(_: Pair) -> Option<&u8>`
+    let pair = get_item(tcx, &items, (DefKind::Fn, "pair")).unwrap();
+    let body = pair.body();
+
+    // The return type is a nullable pointer.
+    let ret = body.locals[0].layout().unwrap();
+    assert_eq!(ret.size, std::mem::size_of::<Option<&u8>>() as u64);
+    match ret.variants {
+        VariantsShape::Multiple { tag_encoding: TagEncoding::Niche { .. }, variants, .. } => {
+            assert_eq!(variants.len(), 2);
+        }
+        other => panic!("{other:?}"),
+    }
+
+    let arg = body.locals[1];
+    let layout = arg.layout().unwrap();
+    assert_eq!(layout.size, 8);
+    assert_eq!(layout.align, 4);
+    match &layout.fields {
+        FieldsShape::Arbitrary { offsets } => assert_eq!(offsets.len(), 2),
+        other => panic!("{other:?}"),
+    }
+
+    let TyKind::RigidTy(RigidTy::Adt(adt, _)) = arg.kind() else { panic!() };
+    assert_eq!(adt.kind(), AdtKind::Struct);
+    let variants = adt.variants();
+    assert_eq!(variants.len(), 1);
+    let fields = &variants[0].fields;
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].name, "first");
+    assert_matches!(fields[0].ty.kind(), TyKind::RigidTy(RigidTy::Uint(_)));
+    assert_matches!(fields[1].ty.kind(), TyKind::RigidTy(RigidTy::Int(_)));
+
+    // `fn wrap<T>(_: Wrapper<T>)` has a generic argument, so no layout is available.
+    let wrap = get_item(tcx, &items, (DefKind::Fn, "wrap")).unwrap();
+    let body = wrap.body();
+    assert!(body.locals[1].layout().is_none());
+    let TyKind::RigidTy(RigidTy::Adt(adt, _)) = body.locals[1].kind() else { panic!() };
+    let generics = adt.generics();
+    assert_eq!(generics.params.len(), 1);
+    assert_eq!(generics.params[0].name, "T");
+    assert_matches!(generics.params[0].kind, GenericParamDefKind::Type { .. });
+
+    // `fn choice(_: Choice)`
+    let choice = get_item(tcx, &items, (DefKind::Fn, "choice")).unwrap();
+    let body = choice.body();
+    let TyKind::RigidTy(RigidTy::Adt(adt, _)) = body.locals[1].kind() else { panic!() };
+    assert_eq!(adt.kind(), AdtKind::Enum);
+    let discrs: Vec<_> = adt.variants().iter().map(|variant| variant.discr).collect();
+    assert_eq!(discrs, vec![Some(0), Some(10), Some(11)]);
+
+    // The local `impl Clone for Pair` must be among all known trait impls.
+    let impls = stable_mir::all_trait_impls();
+    assert!(impls.iter().any(|impl_def| {
+        let trait_ref = impl_def.trait_impl().value;
+        let def_id = rustc_internal::impl_def_id(impl_def);
+        def_id.is_local() && trait_ref.args.0.len() == 1
+    }));
+}
+
+// Use internal API to find a function in a crate.
+fn get_item<'a>(
+    tcx: TyCtxt,
+    items: &'a stable_mir::CrateItems,
+    item: (DefKind, &str),
+) -> Option<&'a stable_mir::CrateItem> {
+    items.iter().find(|crate_item| {
+        let def_id = rustc_internal::item_def_id(crate_item);
+        tcx.def_kind(def_id) == item.0 && tcx.def_path_str(def_id) == item.1
+    })
+}
+
+/// This test will generate and analyze a dummy crate using the stable mir.
+/// For that, it will first write the dummy crate into a file.
+/// It will invoke the compiler using a custom Callback implementation, which will
+/// invoke Stable MIR APIs after the compiler has finished its analysis.
+fn main() {
+    let path = "type_info_input.rs";
+    generate_input(&path).unwrap();
+    let args = vec![
+        "rustc".to_string(),
+        "--crate-type=lib".to_string(),
+        "--crate-name".to_string(),
+        CRATE_NAME.to_string(),
+        path.to_string(),
+    ];
+    rustc_driver::catch_fatal_errors(|| {
+        RunCompiler::new(&args, &mut SMirCalls {}).run().unwrap();
+    })
+    .unwrap();
+}
+
+struct SMirCalls {}
+
+impl Callbacks for SMirCalls {
+    /// Called after analysis. Return value instructs the compiler whether to
+    /// continue the compilation afterwards (defaults to `Compilation::Continue`)
+    fn after_analysis<'tcx>(
+        &mut self,
+        _handler: &EarlyErrorHandler,
+        _compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        queries.global_ctxt().unwrap().enter(|tcx| {
+            rustc_smir::rustc_internal::run(tcx, || test_stable_mir(tcx));
+        });
+        // No need to keep going.
+        Compilation::Stop
+    }
+}
+
+fn generate_input(path: &str) -> std::io::Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    write!(
+        file,
+        r#"
+    #[derive(Clone)]
+    pub struct Pair {{
+        pub first: u32,
+        pub second: i32,
+    }}
+
+    pub struct Wrapper<T>(T);
+
+    pub enum Choice {{
+        A,
+        B = 10,
+        C,
+    }}
+
+    pub fn pair(_: Pair) -> Option<&'static u8> {{
+        None
+    }}
+
+    pub fn wrap<T>(_: Wrapper<T>) {{}}
+
+    pub fn choice(_: Choice) {{}}
+    "#
+    )?;
+    Ok(())
+}
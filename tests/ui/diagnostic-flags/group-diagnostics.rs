@@ -0,0 +1,11 @@
+// Tests that `-Zgroup-diagnostics` prints only the first of similar errors,
+// and lists the locations of the others at the end.
+
+// compile-flags: -Zgroup-diagnostics
+
+fn main() {
+    let _ = missing; //~ ERROR cannot find value `missing` in this scope
+    let _ = missing;
+    let _ = missing;
+    let _ = other; //~ ERROR cannot find value `other` in this scope
+}
@@ -0,0 +1,2 @@
+// compile-flags: --explain unused-must-use
+// check-pass
@@ -0,0 +1,16 @@
+// Checks the remarks of the MIR inliner requested with `-Zmir-remark`.
+
+// build-pass
+// compile-flags: -O -Zmir-remark=Inline
+
+fn square(x: u32) -> u32 {
+    x * x
+}
+
+#[inline(never)]
+fn never_inlined() {}
+
+fn main() {
+    square(3);
+    never_inlined();
+}
@@ -0,0 +1,21 @@
+// compile-flags: -Z print-type-sizes -Z print-type-sizes-format=json --crate-type=lib
+// build-pass
+
+// This file illustrates the JSON output of `-Z print-type-sizes`, including padding
+// and niches, which the text output doesn't show.
+//
+// It avoids using u64/i64 because on some targets that is only 4-byte
+// aligned (while on most it is 8-byte aligned) and so the resulting
+// padding and overall computed sizes can be quite different.
+
+#![allow(dead_code)]
+
+struct S {
+    a: bool,
+    g: i32,
+}
+
+enum E {
+    A(i32, i8),
+    B(S),
+}
@@ -0,0 +1,44 @@
+// no-prefer-dynamic
+// compile-flags: --test
+// run-flags: --test-threads=1 -Zunstable-options --capture-fd
+// run-fail
+// check-run-results
+// exec-env:RUST_BACKTRACE=0
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+
+// ignore-wasm no subprocess support
+// ignore-emscripten no subprocess support
+// ignore-sgx no subprocess support
+
+#![cfg(test)]
+
+use std::env;
+use std::io::Write;
+use std::process::Command;
+
+#[test]
+fn it_works() {
+    writeln!(std::io::stdout(), "not shown").unwrap();
+    assert_eq!(1 + 1, 2);
+}
+
+// Capturing at the file descriptor level runs the test in a subprocess, where it can still catch
+// its own panics.
+#[test]
+fn it_catches_a_panic() {
+    assert!(std::panic::catch_unwind(|| panic!("caught")).is_err());
+}
+
+#[test]
+fn it_fails() {
+    println!("hello, world");
+    writeln!(std::io::stdout(), "testing123").unwrap();
+    writeln!(std::io::stderr(), "testing321").unwrap();
+    // The output of child processes is attributed to the test as well.
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["--list", "--exact", "it_fails"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    assert_eq!(1 + 1, 5);
+}
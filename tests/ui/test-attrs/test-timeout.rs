@@ -0,0 +1,35 @@
+// no-prefer-dynamic
+// compile-flags: --test
+// run-flags: --test-threads=1 -Zunstable-options --test-timeout=1
+// run-fail
+// check-run-results
+// normalize-stdout-test "finished in \d+\.\d+s" -> "finished in $$TIME"
+
+// ignore-wasm no subprocess support
+// ignore-emscripten no subprocess support
+// ignore-sgx no subprocess support
+
+#![cfg(test)]
+
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn it_works() {
+    assert_eq!(1 + 1, 2);
+}
+
+// Tests run in a subprocess under panic=unwind still can catch their own panics.
+#[test]
+fn it_catches_a_panic() {
+    assert!(std::panic::catch_unwind(|| panic!("caught")).is_err());
+    assert!(thread::spawn(|| panic!("joined")).join().is_err());
+}
+
+#[test]
+fn it_hangs() {
+    println!("about to hang");
+    loop {
+        thread::sleep(Duration::from_secs(60));
+    }
+}
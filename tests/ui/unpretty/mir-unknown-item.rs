@@ -0,0 +1,3 @@
+// compile-flags: -Z unpretty=mir=does_not_exist
+
+fn main() {}
@@ -0,0 +1,3 @@
+// compile-flags: -Z unpretty=mir=main@after:NotAPass
+
+fn main() {}